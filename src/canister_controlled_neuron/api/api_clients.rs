@@ -1,10 +1,31 @@
-use ic_ledger_types::MAINNET_GOVERNANCE_CANISTER_ID;
+use toolkit_utils::{cell::CellStorage, result::CanisterResult};
 
-use super::icp_governance_api::IcpGovernanceApi;
+use crate::storage::config_storage::config_store;
+
+use super::{
+    icp_governance_api::IcpGovernanceApi, icp_ledger_api::IcpLedgerApi,
+    icrc_ledger_api::IcrcLedgerApi, sns_governance_api::SnsGovernanceApi,
+};
 pub struct ApiClients;
 
 impl ApiClients {
-    pub fn icp_governance() -> IcpGovernanceApi {
-        IcpGovernanceApi(MAINNET_GOVERNANCE_CANISTER_ID)
+    pub fn icp_governance() -> CanisterResult<IcpGovernanceApi> {
+        Ok(IcpGovernanceApi(
+            config_store().get()?.icp_governance_canister_id,
+        ))
+    }
+
+    pub fn icp_ledger() -> CanisterResult<IcpLedgerApi> {
+        Ok(IcpLedgerApi(config_store().get()?.icp_ledger_canister_id))
+    }
+
+    pub fn sns_governance() -> CanisterResult<SnsGovernanceApi> {
+        Ok(SnsGovernanceApi(
+            config_store().get()?.governance_canister_id,
+        ))
+    }
+
+    pub fn sns_ledger() -> CanisterResult<IcrcLedgerApi> {
+        Ok(IcrcLedgerApi(config_store().get()?.sns_ledger_canister_id))
     }
 }
@@ -0,0 +1,14 @@
+#![allow(deprecated)]
+use candid::Principal;
+use ic_cdk::api::call::CallResult as Result;
+use ic_ledger_types::{AccountBalanceArgs, Tokens, TransferArgs, TransferResult};
+
+pub struct IcpLedgerApi(pub Principal);
+impl IcpLedgerApi {
+    pub async fn account_balance(&self, arg0: AccountBalanceArgs) -> Result<(Tokens,)> {
+        ic_cdk::call(self.0, "account_balance", (arg0,)).await
+    }
+    pub async fn transfer(&self, arg0: TransferArgs) -> Result<(TransferResult,)> {
+        ic_cdk::call(self.0, "transfer", (arg0,)).await
+    }
+}
@@ -0,0 +1,23 @@
+#![allow(deprecated)]
+use candid::{Nat, Principal};
+use ic_cdk::api::call::CallResult as Result;
+use toolkit_utils::icrc_ledger_types::icrc1::{
+    account::Account,
+    transfer::{TransferArg, TransferError},
+};
+
+pub struct IcrcLedgerApi(pub Principal);
+impl IcrcLedgerApi {
+    pub async fn icrc1_balance_of(&self, arg0: Account) -> Result<(Nat,)> {
+        ic_cdk::call(self.0, "icrc1_balance_of", (arg0,)).await
+    }
+    pub async fn icrc1_fee(&self) -> Result<(Nat,)> {
+        ic_cdk::call(self.0, "icrc1_fee", ()).await
+    }
+    pub async fn icrc1_transfer(
+        &self,
+        arg0: TransferArg,
+    ) -> Result<(std::result::Result<Nat, TransferError>,)> {
+        ic_cdk::call(self.0, "icrc1_transfer", (arg0,)).await
+    }
+}
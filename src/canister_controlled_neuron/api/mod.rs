@@ -1,2 +1,5 @@
 pub mod api_clients;
 pub mod icp_governance_api;
+pub mod icp_ledger_api;
+pub mod icrc_ledger_api;
+pub mod sns_governance_api;
@@ -0,0 +1,258 @@
+// This is an experimental feature to generate Rust binding from Candid.
+// You may want to manually adjust some of the types.
+// Only the subset of the SNS governance interface used by this canister is included.
+#![allow(deprecated)]
+use candid::{CandidType, Deserialize, Principal};
+use ic_cdk::api::call::CallResult as Result;
+use serde::Serialize;
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct NeuronId {
+    pub id: Vec<u8>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Followees {
+    pub followees: Vec<NeuronId>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct GovernanceError {
+    pub error_message: String,
+    pub error_type: i32,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ProposalId {
+    pub id: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Subaccount {
+    pub subaccount: Vec<u8>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Account {
+    pub owner: Option<Principal>,
+    pub subaccount: Option<Subaccount>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Amount {
+    pub e8s: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Disburse {
+    pub to_account: Option<Account>,
+    pub amount: Option<Amount>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Follow {
+    pub function_id: u64,
+    pub followees: Vec<NeuronId>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct MemoAndController {
+    pub controller: Option<Principal>,
+    pub memo: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum By {
+    MemoAndController(MemoAndController),
+    NeuronId {},
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ClaimOrRefresh {
+    pub by: Option<By>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ChangeAutoStakeMaturity {
+    pub requested_setting_for_auto_stake_maturity: bool,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct IncreaseDissolveDelay {
+    pub additional_dissolve_delay_seconds: u32,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct SetDissolveTimestamp {
+    pub dissolve_timestamp_seconds: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum Operation {
+    ChangeAutoStakeMaturity(ChangeAutoStakeMaturity),
+    StopDissolving {},
+    StartDissolving {},
+    IncreaseDissolveDelay(IncreaseDissolveDelay),
+    SetDissolveTimestamp(SetDissolveTimestamp),
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Configure {
+    pub operation: Option<Operation>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct RegisterVote {
+    pub vote: i32,
+    pub proposal: Option<ProposalId>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum Command {
+    Follow(Follow),
+    ClaimOrRefresh(ClaimOrRefresh),
+    Configure(Configure),
+    RegisterVote(RegisterVote),
+    Disburse(Disburse),
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ManageNeuron {
+    pub subaccount: Vec<u8>,
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ClaimOrRefreshResponse {
+    pub refreshed_neuron_id: Option<NeuronId>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct DisburseResponse {
+    pub transfer_block_height: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum Command1 {
+    Error(GovernanceError),
+    Follow {},
+    ClaimOrRefresh(ClaimOrRefreshResponse),
+    Configure {},
+    RegisterVote {},
+    Disburse(DisburseResponse),
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ManageNeuronResponse {
+    pub command: Option<Command1>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct NeuronPermission {
+    pub principal: Option<Principal>,
+    pub permission_type: Vec<i32>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum DissolveState {
+    DissolveDelaySeconds(u64),
+    WhenDissolvedTimestampSeconds(u64),
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Neuron {
+    pub id: Option<NeuronId>,
+    pub staked_maturity_e8s_equivalent: Option<u64>,
+    pub permissions: Vec<NeuronPermission>,
+    pub maturity_e8s_equivalent: u64,
+    pub cached_neuron_stake_e8s: u64,
+    pub created_timestamp_seconds: u64,
+    pub auto_stake_maturity: Option<bool>,
+    pub aging_since_timestamp_seconds: u64,
+    pub dissolve_state: Option<DissolveState>,
+    pub voting_power_percentage_multiplier: u64,
+    pub followees: Vec<(u64, Followees)>,
+    pub neuron_fees_e8s: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct GetNeuron {
+    pub neuron_id: Option<NeuronId>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum Result1 {
+    Error(GovernanceError),
+    Neuron(Neuron),
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct GetNeuronResponse {
+    pub result: Option<Result1>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct NervousSystemParameters {
+    pub neuron_minimum_stake_e8s: Option<u64>,
+    pub transaction_fee_e8s: Option<u64>,
+    pub max_dissolve_delay_seconds: Option<u64>,
+    pub neuron_minimum_dissolve_delay_to_vote_seconds: Option<u64>,
+    pub max_followees_per_function: Option<u64>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ExecuteGenericNervousSystemFunction {
+    pub function_id: u64,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum Action {
+    ExecuteGenericNervousSystemFunction(ExecuteGenericNervousSystemFunction),
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Proposal {
+    pub url: String,
+    pub title: String,
+    pub action: Option<Action>,
+    pub summary: String,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ProposalData {
+    pub id: Option<ProposalId>,
+    pub proposal: Option<Proposal>,
+    pub decided_timestamp_seconds: u64,
+    pub executed_timestamp_seconds: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ListProposals {
+    pub include_reward_status: Vec<i32>,
+    pub before_proposal: Option<ProposalId>,
+    pub limit: u32,
+    pub exclude_type: Vec<u64>,
+    pub include_status: Vec<i32>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ListProposalsResponse {
+    pub proposals: Vec<ProposalData>,
+}
+
+pub struct SnsGovernanceApi(pub Principal);
+impl SnsGovernanceApi {
+    pub async fn get_nervous_system_parameters(&self) -> Result<(NervousSystemParameters,)> {
+        ic_cdk::call(self.0, "get_nervous_system_parameters", ((),)).await
+    }
+    pub async fn get_neuron(&self, arg0: GetNeuron) -> Result<(GetNeuronResponse,)> {
+        ic_cdk::call(self.0, "get_neuron", (arg0,)).await
+    }
+    pub async fn list_proposals(&self, arg0: ListProposals) -> Result<(ListProposalsResponse,)> {
+        ic_cdk::call(self.0, "list_proposals", (arg0,)).await
+    }
+    pub async fn manage_neuron(&self, arg0: ManageNeuron) -> Result<(ManageNeuronResponse,)> {
+        ic_cdk::call(self.0, "manage_neuron", (arg0,)).await
+    }
+}
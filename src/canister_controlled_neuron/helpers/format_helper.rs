@@ -0,0 +1,33 @@
+const E8S_PER_TOKEN: u64 = 100_000_000;
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+pub fn format_icp(amount_e8s: u64) -> String {
+    format_tokens(amount_e8s, "ICP")
+}
+
+/// Formats an e8s amount in whole tokens without trailing zeros, e.g. `1.5 ICP`.
+pub fn format_tokens(amount_e8s: u64, symbol: &str) -> String {
+    let fraction = format!("{:08}", amount_e8s % E8S_PER_TOKEN);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        format!("{} {}", amount_e8s / E8S_PER_TOKEN, symbol)
+    } else {
+        format!("{}.{} {}", amount_e8s / E8S_PER_TOKEN, fraction, symbol)
+    }
+}
+
+pub fn format_days(seconds: u64) -> String {
+    if seconds % SECONDS_PER_DAY == 0 {
+        format!("{} days", seconds / SECONDS_PER_DAY)
+    } else {
+        format!("{:.2} days", seconds as f64 / SECONDS_PER_DAY as f64)
+    }
+}
+
+pub fn format_subaccount(subaccount: [u8; 32]) -> String {
+    format_hex(&subaccount)
+}
+
+pub fn format_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
@@ -0,0 +1,140 @@
+use candid::Nat;
+use sha2::{Digest, Sha256};
+
+use crate::types::icrc3::Icrc3Value;
+
+/// The representation independent hash of an ICRC-3 value.
+pub fn hash_value(value: &Icrc3Value) -> [u8; 32] {
+    match value {
+        Icrc3Value::Blob(bytes) => Sha256::digest(bytes).into(),
+        Icrc3Value::Text(text) => Sha256::digest(text.as_bytes()).into(),
+        Icrc3Value::Nat(nat) => {
+            let mut bytes = vec![];
+            nat.encode(&mut bytes).expect("Failed to encode nat");
+            Sha256::digest(bytes).into()
+        }
+        Icrc3Value::Int(int) => {
+            let mut bytes = vec![];
+            int.encode(&mut bytes).expect("Failed to encode int");
+            Sha256::digest(bytes).into()
+        }
+        Icrc3Value::Array(values) => {
+            let mut hasher = Sha256::new();
+            for value in values {
+                hasher.update(hash_value(value));
+            }
+            hasher.finalize().into()
+        }
+        Icrc3Value::Map(entries) => {
+            let mut pairs: Vec<Vec<u8>> = entries
+                .iter()
+                .map(|(key, value)| {
+                    let mut pair = Sha256::digest(key.as_bytes()).to_vec();
+                    pair.extend(hash_value(value));
+                    pair
+                })
+                .collect();
+            pairs.sort();
+
+            let mut hasher = Sha256::new();
+            for pair in pairs {
+                hasher.update(pair);
+            }
+            hasher.finalize().into()
+        }
+    }
+}
+
+/// The hash tree certifying the tip of the block log, as specified by ICRC-3. Labels are in
+/// lexicographic order.
+pub fn tip_hash_tree(last_block_index: u64, last_block_hash: [u8; 32]) -> HashTree {
+    let mut index = vec![];
+    Nat::from(last_block_index)
+        .encode(&mut index)
+        .expect("Failed to encode nat");
+
+    HashTree::Fork(
+        Box::new(HashTree::Labeled(
+            b"last_block_hash".to_vec(),
+            Box::new(HashTree::Leaf(last_block_hash.to_vec())),
+        )),
+        Box::new(HashTree::Labeled(
+            b"last_block_index".to_vec(),
+            Box::new(HashTree::Leaf(index)),
+        )),
+    )
+}
+
+/// The subset of the IC hash tree needed to certify the tip.
+pub enum HashTree {
+    Fork(Box<HashTree>, Box<HashTree>),
+    Labeled(Vec<u8>, Box<HashTree>),
+    Leaf(Vec<u8>),
+}
+
+impl HashTree {
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        match self {
+            HashTree::Fork(left, right) => {
+                hasher.update(domain_separator("ic-hashtree-fork"));
+                hasher.update(left.digest());
+                hasher.update(right.digest());
+            }
+            HashTree::Labeled(label, tree) => {
+                hasher.update(domain_separator("ic-hashtree-labeled"));
+                hasher.update(label);
+                hasher.update(tree.digest());
+            }
+            HashTree::Leaf(value) => {
+                hasher.update(domain_separator("ic-hashtree-leaf"));
+                hasher.update(value);
+            }
+        }
+        hasher.finalize().into()
+    }
+
+    /// Encodes the tree as self-describing CBOR.
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut bytes = vec![0xd9, 0xd9, 0xf7];
+        self.write_cbor(&mut bytes);
+        bytes
+    }
+
+    fn write_cbor(&self, bytes: &mut Vec<u8>) {
+        match self {
+            HashTree::Fork(left, right) => {
+                bytes.extend([0x83, 0x01]);
+                left.write_cbor(bytes);
+                right.write_cbor(bytes);
+            }
+            HashTree::Labeled(label, tree) => {
+                bytes.extend([0x83, 0x02]);
+                write_cbor_bytes(bytes, label);
+                tree.write_cbor(bytes);
+            }
+            HashTree::Leaf(value) => {
+                bytes.extend([0x82, 0x03]);
+                write_cbor_bytes(bytes, value);
+            }
+        }
+    }
+}
+
+fn domain_separator(name: &str) -> Vec<u8> {
+    let mut bytes = vec![name.len() as u8];
+    bytes.extend(name.as_bytes());
+    bytes
+}
+
+fn write_cbor_bytes(bytes: &mut Vec<u8>, value: &[u8]) {
+    match value.len() {
+        len if len < 24 => bytes.push(0x40 | len as u8),
+        len if len <= u8::MAX as usize => bytes.extend([0x58, len as u8]),
+        len => {
+            bytes.push(0x59);
+            bytes.extend((len as u16).to_be_bytes());
+        }
+    }
+    bytes.extend(value);
+}
@@ -1 +1,5 @@
+pub mod format_helper;
+pub mod icrc3_helper;
+pub mod nat_helper;
 pub mod subaccount_helper;
+pub mod versioned_storable;
@@ -0,0 +1,6 @@
+use candid::Nat;
+use toolkit_utils::{api_error::ApiError, result::CanisterResult};
+
+pub fn nat_to_u64(value: &Nat) -> CanisterResult<u64> {
+    u64::try_from(&value.0).map_err(|_| ApiError::bad_request("Value does not fit in u64"))
+}
@@ -0,0 +1,75 @@
+use candid::{decode_one, encode_one, CandidType};
+use serde::de::DeserializeOwned;
+
+/// Marks an entry written with a schema version header. Legacy entries were written as plain
+/// serialized structs, which never start with `0xff`.
+const VERSION_PREFIX: [u8; 3] = [0xff, b'c', b'v'];
+
+/// A stored type whose stable-memory layout is versioned.
+///
+/// Entries are written as `VERSION_PREFIX ++ version ++ candid(value)`. Adding an `Option` field
+/// is backwards compatible with candid and does not need a new version, any other change to the
+/// layout must bump `VERSION` and handle the previous version in `migrate`.
+pub trait VersionedStorable: CandidType + DeserializeOwned + Sized {
+    /// The schema version written for new entries.
+    const VERSION: u8;
+
+    /// Converts an entry written with an older schema `version` into the current type.
+    /// Version `0` refers to entries written before schema versioning was introduced.
+    fn migrate(version: u8, bytes: &[u8]) -> Self;
+}
+
+pub fn encode_versioned<T: VersionedStorable>(value: &T) -> Vec<u8> {
+    let mut bytes = VERSION_PREFIX.to_vec();
+    bytes.push(T::VERSION);
+    bytes.extend(encode_one(value).expect("Failed to encode versioned entry"));
+    bytes
+}
+
+/// Traps on an entry that can not be decoded, `UpgradeLogic::post_upgrade` decodes every entry
+/// so such an entry fails the upgrade instead of the calls that read it later.
+pub fn decode_versioned<T: VersionedStorable>(bytes: &[u8]) -> T {
+    match bytes.strip_prefix(&VERSION_PREFIX) {
+        Some([version, payload @ ..]) if *version == T::VERSION => decode_one(payload)
+            .unwrap_or_else(|e| {
+                panic!(
+                    "Failed to decode schema version {} of {}: {}",
+                    version,
+                    std::any::type_name::<T>(),
+                    e
+                )
+            }),
+        Some([version, payload @ ..]) => T::migrate(*version, payload),
+        Some([]) => panic!(
+            "Versioned entry of {} is missing its version",
+            std::any::type_name::<T>()
+        ),
+        None => T::migrate(0, bytes),
+    }
+}
+
+pub fn unsupported_version<T>(version: u8) -> T {
+    panic!(
+        "Unsupported schema version {} of {}, the entry was written by a newer or unknown version",
+        version,
+        std::any::type_name::<T>()
+    )
+}
+
+#[macro_export]
+macro_rules! impl_versioned_storable_for {
+    ($t:ty) => {
+        impl ic_stable_structures::Storable for $t {
+            fn to_bytes(&self) -> std::borrow::Cow<[u8]> {
+                std::borrow::Cow::Owned($crate::helpers::versioned_storable::encode_versioned(self))
+            }
+
+            fn from_bytes(bytes: std::borrow::Cow<[u8]>) -> Self {
+                $crate::helpers::versioned_storable::decode_versioned(bytes.as_ref())
+            }
+
+            const BOUND: ic_stable_structures::storable::Bound =
+                ic_stable_structures::storable::Bound::Unbounded;
+        }
+    };
+}
@@ -14,9 +14,27 @@ use ic_cdk::query;
 #[query]
 pub fn __get_candid_interface_tmp_hack() -> String {
     use crate::api::icp_governance_api::Neuron as GovNeuron;
-    use crate::types::config::Config;
+    use crate::api::sns_governance_api::Neuron as SnsGovNeuron;
+    use crate::types::audit_record::{AuditRecordsResponse, GetAuditRecordsArgs};
+    use crate::types::config::{Config, InitArgs, UpdateConfigArgs};
+    use crate::types::config_change::ConfigChangeResponse;
+    use crate::types::icrc21::{Icrc21ConsentMessageRequest, Icrc21ConsentMessageResponse};
+    use crate::types::icrc3::{
+        GetArchivesArgs, GetBlocksArgs, GetBlocksResult, Icrc3ArchiveInfo, Icrc3DataCertificate,
+        SupportedBlockType,
+    };
+    use crate::types::log_entry::{GetLogsArgs, LogsResponse};
     use crate::types::modules::*;
+    use crate::types::neuron_creation::NeuronCreationOperationResponse;
     use crate::types::neuron_reference::NeuronReferenceResponse;
+    use crate::types::policy::Policy;
+    use crate::types::queued_operation::QueuedOperationResponse;
+    use crate::types::reconcile::ReconcileReport;
+    use crate::types::role::{RoleAssignmentResponse, SetRoleArgs};
+    use crate::types::sns_neuron_reference::SnsNeuronReferenceResponse;
+    use crate::types::voting_power_refresh::VotingPowerRefreshResponse;
+    use crate::types::voting_rule::{GetVoteDecisionsArgs, VoteDecisionsResponse, VotingRule};
+    use candid::Principal;
     use toolkit_utils::icrc_types::*;
     use toolkit_utils::result::CanisterResult;
     export_service!();
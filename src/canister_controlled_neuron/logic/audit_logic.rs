@@ -0,0 +1,173 @@
+use candid::{decode_one, encode_one, Principal};
+use ic_cdk::api::time;
+use toolkit_utils::{api_error::ApiError, result::CanisterResult, storage::StorageInsertable};
+
+use crate::{
+    api::{
+        api_clients::ApiClients,
+        sns_governance_api::{Action, ListProposals, ProposalData, ProposalId},
+    },
+    logic::{config_logic::ConfigLogic, log_logic::LogLogic},
+    storage::audit_record_storage::AuditRecordStore,
+    types::{
+        audit_record::{AuditRecord, AuditRecordsResponse, GetAuditRecordsArgs, ProposalMatch},
+        modules::{ModuleResponse, NeuronType},
+    },
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 100;
+const MAX_PAGE_SIZE: u64 = 500;
+/// `ProposalDecisionStatus::Adopted`, a proposal keeps this status while it is being executed.
+const ADOPTED_PROPOSAL_STATUS: i32 = 3;
+const LIST_PROPOSALS_LIMIT: u32 = 50;
+const MAX_LIST_PROPOSALS_PAGES: usize = 20;
+
+pub struct AuditLogic;
+
+impl AuditLogic {
+    pub async fn record(
+        caller: Principal,
+        args: &NeuronType,
+        result: &CanisterResult<ModuleResponse>,
+    ) {
+        let proposal_match = Self::get_proposal_match(caller, args).await;
+        Self::insert(caller, args, result, proposal_match);
+    }
+
+    /// The proposal that is executing the args, only calls from the governance canister are
+    /// executed proposals.
+    pub async fn get_proposal_match(caller: Principal, args: &NeuronType) -> Option<ProposalMatch> {
+        match ConfigLogic::get_config() {
+            Ok(config) if config.governance_canister_id == caller => {
+                Some(Self::find_proposal(&encode_one(args).unwrap_or_default()).await)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn insert(
+        caller: Principal,
+        args: &NeuronType,
+        result: &CanisterResult<ModuleResponse>,
+        proposal_match: Option<ProposalMatch>,
+    ) {
+        let record = AuditRecord {
+            timestamp: time(),
+            caller,
+            operation: args.operation().to_string(),
+            neuron_key: args.neuron_key(),
+            args: encode_one(args).unwrap_or_default(),
+            result: match result {
+                Ok(response) => Ok(response.clone()),
+                Err(e) => Err(e.to_string()),
+            },
+            proposal_id: proposal_match.as_ref().and_then(ProposalMatch::proposal_id),
+            proposal_match,
+        };
+
+        if let Err(e) = AuditRecordStore::insert(record) {
+            LogLogic::error(
+                args.operation(),
+                args.neuron_key(),
+                format!("Error storing audit record: {}", e),
+            );
+        }
+    }
+
+    pub fn get_records(args: GetAuditRecordsArgs) -> AuditRecordsResponse {
+        let limit = args
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE) as usize;
+
+        let (records, next) =
+            AuditRecordStore::get_page(args.start.unwrap_or(0), limit, |record| {
+                args.neuron_key
+                    .is_none_or(|key| record.neuron_key == Some(key))
+                    && args
+                        .from_timestamp
+                        .is_none_or(|from| record.timestamp >= from)
+                    && args.to_timestamp.is_none_or(|to| record.timestamp <= to)
+            });
+
+        AuditRecordsResponse {
+            records: records
+                .into_iter()
+                .map(|(id, record)| record.to_response(id))
+                .collect(),
+            next,
+        }
+    }
+
+    /// Looks for the adopted, not yet executed, governance proposals whose generic function
+    /// payload decodes to the executed args. Payloads are compared after re-encoding because
+    /// the proposer may have used a different candid encoder.
+    async fn find_proposal(encoded_args: &[u8]) -> ProposalMatch {
+        let mut matches = vec![];
+        let mut before_proposal = None;
+
+        for _ in 0..MAX_LIST_PROPOSALS_PAGES {
+            let proposals = match Self::list_adopted_proposals(before_proposal.clone()).await {
+                Ok(proposals) => proposals,
+                Err(e) => {
+                    LogLogic::error(
+                        "audit",
+                        None,
+                        format!(
+                            "Error listing proposals to find the executing proposal: {}",
+                            e
+                        ),
+                    );
+                    break;
+                }
+            };
+            let is_last_page = proposals.len() < LIST_PROPOSALS_LIMIT as usize;
+            before_proposal = proposals.last().and_then(|proposal| proposal.id.clone());
+
+            matches.extend(proposals.into_iter().filter_map(|proposal| {
+                if proposal.executed_timestamp_seconds != 0 {
+                    return None;
+                }
+
+                let Some(Action::ExecuteGenericNervousSystemFunction(function)) =
+                    proposal.proposal?.action
+                else {
+                    return None;
+                };
+
+                let payload = decode_one::<NeuronType>(&function.payload).ok()?;
+                if encode_one(payload).ok()?.as_slice() == encoded_args {
+                    proposal.id.map(|id| id.id)
+                } else {
+                    None
+                }
+            }));
+
+            if is_last_page || before_proposal.is_none() {
+                break;
+            }
+        }
+
+        match matches.as_slice() {
+            [] => ProposalMatch::NotFound,
+            [proposal_id] => ProposalMatch::Found(*proposal_id),
+            _ => ProposalMatch::Ambiguous(matches),
+        }
+    }
+
+    async fn list_adopted_proposals(
+        before_proposal: Option<ProposalId>,
+    ) -> CanisterResult<Vec<ProposalData>> {
+        let (response,) = ApiClients::sns_governance()?
+            .list_proposals(ListProposals {
+                include_reward_status: vec![],
+                before_proposal,
+                limit: LIST_PROPOSALS_LIMIT,
+                exclude_type: vec![],
+                include_status: vec![ADOPTED_PROPOSAL_STATUS],
+            })
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+        Ok(response.proposals)
+    }
+}
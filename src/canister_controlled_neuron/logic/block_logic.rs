@@ -0,0 +1,181 @@
+use candid::{encode_one, Nat, Principal};
+use ic_cdk::api::{certified_data_set, data_certificate, time};
+
+use crate::{
+    api::icp_governance_api::ManageNeuronCommandRequest,
+    helpers::{
+        icrc3_helper::{hash_value, tip_hash_tree, HashTree},
+        nat_helper::nat_to_u64,
+    },
+    logic::consent_logic::ConsentLogic,
+    storage::block_storage::BlockStore,
+    types::{
+        icrc3::{
+            Block, BlockWithId, GetArchivesArgs, GetBlocksArgs, GetBlocksResult, Icrc3ArchiveInfo,
+            Icrc3DataCertificate, SupportedBlockType,
+        },
+        modules::{IcpNeuronArgs, ModuleResponse, NeuronType, SnsNeuronArgs},
+    },
+};
+
+const MAX_BLOCKS_PER_RESPONSE: u64 = 100;
+/// The schemas of the block types are documented in this repository, relative to its root.
+const BLOCK_TYPES_URL: &str = "src/canister_controlled_neuron/BLOCKS.md";
+
+const CREATE_BLOCK_TYPE: &str = "neuron_create";
+const TOP_UP_BLOCK_TYPE: &str = "neuron_top_up";
+const DISBURSE_BLOCK_TYPE: &str = "neuron_disburse";
+const VOTE_BLOCK_TYPE: &str = "neuron_vote";
+const FOLLOW_BLOCK_TYPE: &str = "neuron_follow";
+const SPAWN_BLOCK_TYPE: &str = "neuron_spawn";
+const SPLIT_BLOCK_TYPE: &str = "neuron_split";
+const MERGE_BLOCK_TYPE: &str = "neuron_merge";
+
+/// Keeps an ICRC-3 hash chained block log of the neuron operations that move funds or use
+/// voting power. All blocks are kept on this canister, there are no archives.
+pub struct BlockLogic;
+
+impl BlockLogic {
+    /// Appends a block for a successfully executed operation and certifies the new tip,
+    /// operations without a block type are not recorded.
+    pub fn append(caller: Principal, args: &NeuronType, response: &ModuleResponse) {
+        let Some(block_type) = Self::block_type(args) else {
+            return;
+        };
+
+        // A created neuron is only known after the operation
+        let neuron_key = args.neuron_key().or(match response {
+            ModuleResponse::Neuron(neuron) => Some(neuron.subaccount),
+            ModuleResponse::SnsNeuron(neuron) => Some(neuron.subaccount),
+            _ => None,
+        });
+
+        let block = Block {
+            block_type: block_type.to_string(),
+            timestamp: time(),
+            parent_hash: BlockStore::last().map(|(_, block)| hash_value(&block.to_value())),
+            caller,
+            operation: args.operation().to_string(),
+            neuron_key,
+            args: encode_one(args).unwrap_or_default(),
+        };
+
+        BlockStore::append(block);
+        Self::certify_tip();
+    }
+
+    /// Sets the certified data to the root hash of the tip hash tree, needs to be called after
+    /// every append and after an upgrade.
+    pub fn certify_tip() {
+        if let Some(tree) = Self::tip_hash_tree() {
+            certified_data_set(tree.digest());
+        }
+    }
+
+    pub fn get_tip_certificate() -> Option<Icrc3DataCertificate> {
+        let certificate = data_certificate()?;
+        let tree = Self::tip_hash_tree()?;
+        Some(Icrc3DataCertificate {
+            certificate,
+            hash_tree: tree.to_cbor(),
+        })
+    }
+
+    pub fn get_blocks(args: Vec<GetBlocksArgs>) -> GetBlocksResult {
+        let mut blocks = vec![];
+        for range in args {
+            let remaining = MAX_BLOCKS_PER_RESPONSE - blocks.len() as u64;
+            if remaining == 0 {
+                break;
+            }
+
+            let (Ok(start), Ok(length)) = (nat_to_u64(&range.start), nat_to_u64(&range.length))
+            else {
+                continue;
+            };
+
+            blocks.extend(
+                BlockStore::get_range(start, length.min(remaining))
+                    .into_iter()
+                    .map(|(id, block)| BlockWithId {
+                        id: Nat::from(id),
+                        block: block.to_value(),
+                    }),
+            );
+        }
+
+        GetBlocksResult {
+            log_length: Nat::from(BlockStore::len()),
+            blocks,
+            archived_blocks: vec![],
+        }
+    }
+
+    pub fn get_archives(_args: GetArchivesArgs) -> Vec<Icrc3ArchiveInfo> {
+        vec![]
+    }
+
+    pub fn supported_block_types() -> Vec<SupportedBlockType> {
+        [
+            CREATE_BLOCK_TYPE,
+            TOP_UP_BLOCK_TYPE,
+            DISBURSE_BLOCK_TYPE,
+            VOTE_BLOCK_TYPE,
+            FOLLOW_BLOCK_TYPE,
+            SPAWN_BLOCK_TYPE,
+            SPLIT_BLOCK_TYPE,
+            MERGE_BLOCK_TYPE,
+        ]
+        .into_iter()
+        .map(|block_type| SupportedBlockType {
+            block_type: block_type.to_string(),
+            url: format!("{}#{}", BLOCK_TYPES_URL, block_type),
+        })
+        .collect()
+    }
+
+    fn block_type(args: &NeuronType) -> Option<&'static str> {
+        match args {
+            NeuronType::Icp(args) => match args {
+                IcpNeuronArgs::Create(_) => Some(CREATE_BLOCK_TYPE),
+                IcpNeuronArgs::TopUp(_) => Some(TOP_UP_BLOCK_TYPE),
+                IcpNeuronArgs::Disburse(_) | IcpNeuronArgs::DisburseMaturity(_) => {
+                    Some(DISBURSE_BLOCK_TYPE)
+                }
+                IcpNeuronArgs::Vote(_) => Some(VOTE_BLOCK_TYPE),
+                IcpNeuronArgs::SetFollowing(_) => Some(FOLLOW_BLOCK_TYPE),
+                IcpNeuronArgs::Spawn(_) => Some(SPAWN_BLOCK_TYPE),
+                IcpNeuronArgs::Split(_) => Some(SPLIT_BLOCK_TYPE),
+                IcpNeuronArgs::Merge(_) => Some(MERGE_BLOCK_TYPE),
+                IcpNeuronArgs::Command(args) => Self::command_block_type(&args.command),
+                _ => None,
+            },
+            NeuronType::Sns(args) => match args {
+                SnsNeuronArgs::Create(_) => Some(CREATE_BLOCK_TYPE),
+                SnsNeuronArgs::TopUp(_) => Some(TOP_UP_BLOCK_TYPE),
+                SnsNeuronArgs::Disburse(_) => Some(DISBURSE_BLOCK_TYPE),
+                SnsNeuronArgs::Vote(_) => Some(VOTE_BLOCK_TYPE),
+                SnsNeuronArgs::SetFollowing(_) => Some(FOLLOW_BLOCK_TYPE),
+                _ => None,
+            },
+        }
+    }
+
+    /// Raw commands get the block type of the operation they perform.
+    fn command_block_type(command: &ManageNeuronCommandRequest) -> Option<&'static str> {
+        match ConsentLogic::command_name(command) {
+            "Disburse" | "DisburseMaturity" | "DisburseToNeuron" => Some(DISBURSE_BLOCK_TYPE),
+            "RegisterVote" => Some(VOTE_BLOCK_TYPE),
+            "Follow" | "SetFollowing" => Some(FOLLOW_BLOCK_TYPE),
+            "Spawn" => Some(SPAWN_BLOCK_TYPE),
+            "Split" => Some(SPLIT_BLOCK_TYPE),
+            "Merge" => Some(MERGE_BLOCK_TYPE),
+            _ => None,
+        }
+    }
+
+    fn tip_hash_tree() -> Option<HashTree> {
+        let (index, block) = BlockStore::last()?;
+        Some(tip_hash_tree(index, hash_value(&block.to_value())))
+    }
+}
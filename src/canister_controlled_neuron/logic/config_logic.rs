@@ -1,7 +1,20 @@
 use candid::Principal;
-use toolkit_utils::{cell::CellStorage, result::CanisterResult};
+use ic_cdk::api::{msg_caller, time};
+use toolkit_utils::{
+    api_error::ApiError,
+    cell::CellStorage,
+    result::CanisterResult,
+    storage::{StorageInsertable, StorageQueryable},
+};
 
-use crate::{storage::config_storage::config_store, types::config::Config};
+use crate::{
+    logic::log_logic::LogLogic,
+    storage::{config_change_storage::ConfigChangeStore, config_storage::config_store},
+    types::{
+        config::{Config, UpdateConfigArgs},
+        config_change::{ConfigChange, ConfigChangeResponse},
+    },
+};
 
 pub struct ConfigLogic;
 
@@ -9,11 +22,78 @@ impl ConfigLogic {
     pub fn init(
         governance_canister_id: Principal,
         sns_ledger_canister_id: Principal,
+        icp_governance_canister_id: Option<Principal>,
+        icp_ledger_canister_id: Option<Principal>,
     ) -> CanisterResult<Config> {
-        config_store().set(Config::new(governance_canister_id, sns_ledger_canister_id))
+        config_store().set(Config::new(
+            governance_canister_id,
+            sns_ledger_canister_id,
+            icp_governance_canister_id,
+            icp_ledger_canister_id,
+        ))
     }
 
     pub fn get_config() -> CanisterResult<Config> {
         config_store().get()
     }
+
+    pub fn get_config_changes() -> Vec<ConfigChangeResponse> {
+        ConfigChangeStore::get_all()
+            .into_iter()
+            .map(|(_, change)| change.to_response())
+            .collect()
+    }
+
+    pub fn update_config(args: UpdateConfigArgs) -> CanisterResult<Config> {
+        Self::validate_args(&args)?;
+
+        let previous = config_store().get()?;
+        let mut updated = previous.clone();
+
+        if let Some(governance_canister_id) = args.governance_canister_id {
+            updated.governance_canister_id = governance_canister_id;
+        }
+        if let Some(sns_ledger_canister_id) = args.sns_ledger_canister_id {
+            updated.sns_ledger_canister_id = sns_ledger_canister_id;
+        }
+        if let Some(icp_governance_canister_id) = args.icp_governance_canister_id {
+            updated.icp_governance_canister_id = icp_governance_canister_id;
+        }
+        if let Some(icp_ledger_canister_id) = args.icp_ledger_canister_id {
+            updated.icp_ledger_canister_id = icp_ledger_canister_id;
+        }
+
+        let config = config_store().set(updated.clone())?;
+
+        ConfigChangeStore::insert(ConfigChange::new(time(), msg_caller(), &previous, &updated))?;
+        LogLogic::info("update_config", None, "Config updated");
+
+        Ok(config)
+    }
+
+    pub fn validate_update_config(args: UpdateConfigArgs) -> CanisterResult<String> {
+        Self::validate_args(&args)?;
+        Ok(serde_json::to_string(&args).unwrap())
+    }
+
+    fn validate_args(args: &UpdateConfigArgs) -> CanisterResult<()> {
+        let principals = [
+            args.governance_canister_id,
+            args.sns_ledger_canister_id,
+            args.icp_governance_canister_id,
+            args.icp_ledger_canister_id,
+        ];
+
+        if principals.iter().all(Option::is_none) {
+            return Err(ApiError::bad_request("No config changes provided"));
+        }
+
+        if principals.iter().flatten().any(|principal| {
+            *principal == Principal::anonymous() || *principal == Principal::management_canister()
+        }) {
+            return Err(ApiError::bad_request("Invalid canister id"));
+        }
+
+        Ok(())
+    }
 }
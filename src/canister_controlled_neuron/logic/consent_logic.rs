@@ -0,0 +1,465 @@
+use candid::{decode_one, Principal};
+use ic_cdk::api::canister_self;
+use toolkit_utils::icrc_ledger_types::icrc1::account::Account;
+
+use crate::{
+    api::icp_governance_api::{Account as GovAccount, ManageNeuronCommandRequest},
+    helpers::format_helper::{
+        format_days, format_hex, format_icp, format_subaccount, format_tokens,
+    },
+    logic::neuron_logic::NeuronLogic,
+    storage::{
+        neuron_reference_storage::NeuronReferenceStore,
+        sns_neuron_reference_storage::SnsNeuronReferenceStore,
+    },
+    types::{
+        icrc21::{
+            Icrc21ConsentInfo, Icrc21ConsentMessage, Icrc21ConsentMessageRequest, Icrc21DeviceSpec,
+            Icrc21Error, Icrc21ErrorInfo, Icrc21LineDisplayPage,
+        },
+        modules::{IcpNeuronArgs, NeuronSelector, NeuronType, SnsNeuronArgs, Vote},
+    },
+};
+
+const CONSENT_METHODS: [&str; 2] = [
+    "tk_service_manage_neuron",
+    "tk_service_validate_manage_neuron",
+];
+
+/// Renders `NeuronType` args as text a voter can read, for ICRC-21 signers.
+pub struct ConsentLogic;
+
+impl ConsentLogic {
+    pub fn consent_message(
+        request: Icrc21ConsentMessageRequest,
+    ) -> Result<Icrc21ConsentInfo, Icrc21Error> {
+        if !CONSENT_METHODS.contains(&request.method.as_str()) {
+            return Err(Icrc21Error::UnsupportedCanisterCall(Icrc21ErrorInfo {
+                description: format!("No consent message for method {}", request.method),
+            }));
+        }
+
+        let args = decode_one::<NeuronType>(&request.arg).map_err(|e| {
+            Icrc21Error::UnsupportedCanisterCall(Icrc21ErrorInfo {
+                description: format!("Failed to decode the call argument: {}", e),
+            })
+        })?;
+
+        let message = Self::describe(&args);
+        let consent_message = match request.user_preferences.device_spec {
+            Some(Icrc21DeviceSpec::LineDisplay {
+                characters_per_line,
+                lines_per_page,
+            }) => Icrc21ConsentMessage::LineDisplayMessage {
+                pages: Self::paginate(&message, characters_per_line, lines_per_page),
+            },
+            _ => Icrc21ConsentMessage::GenericDisplayMessage(message),
+        };
+
+        Ok(Icrc21ConsentInfo {
+            consent_message,
+            metadata: request.user_preferences.metadata,
+        })
+    }
+
+    /// A Markdown description of what executing the args does.
+    pub fn describe(args: &NeuronType) -> String {
+        match args {
+            NeuronType::Icp(args) => Self::describe_icp(args),
+            NeuronType::Sns(args) => Self::describe_sns(args),
+        }
+    }
+
+    fn describe_icp(args: &IcpNeuronArgs) -> String {
+        match args {
+            IcpNeuronArgs::Create(args) => {
+                let mut message = format!(
+                    "## Create ICP neuron\n\nStake **{}** in a new neuron.",
+                    format_icp(args.amount_e8s)
+                );
+                if let Some(dissolve_delay_seconds) = args.dissolve_delay_seconds {
+                    message.push_str(&format!(
+                        "\n\nDissolve delay: **{}**.",
+                        format_days(dissolve_delay_seconds)
+                    ));
+                }
+                if args.auto_stake == Some(true) {
+                    message.push_str("\n\nMaturity is staked automatically.");
+                }
+                message
+            }
+            IcpNeuronArgs::TopUp(args) => format!(
+                "## Top up ICP neuron\n\nAdd **{}** to the stake of {}.",
+                format_icp(args.amount_e8s),
+                Self::icp_neuron_label(args.neuron)
+            ),
+            IcpNeuronArgs::AddDissolveDelay(args) => format!(
+                "## Increase dissolve delay\n\nIncrease the dissolve delay of {} by **{}**.",
+                Self::icp_neuron_label(args.neuron),
+                format_days(args.dissolve_delay_seconds)
+            ),
+            IcpNeuronArgs::SetDissolveState(args) => format!(
+                "## {} dissolving\n\n{} {} dissolving.",
+                if args.start_dissolving {
+                    "Start"
+                } else {
+                    "Stop"
+                },
+                Self::icp_neuron_label(args.neuron),
+                if args.start_dissolving {
+                    "starts"
+                } else {
+                    "stops"
+                }
+            ),
+            IcpNeuronArgs::AutoStake(args) => format!(
+                "## Automatic maturity staking\n\n{} automatic maturity staking for {}.",
+                if args.auto_stake {
+                    "Enable"
+                } else {
+                    "Disable"
+                },
+                Self::icp_neuron_label(args.neuron)
+            ),
+            IcpNeuronArgs::Spawn(args) => format!(
+                "## Spawn neuron\n\nSpawn a new neuron from the maturity of {}.{}",
+                Self::icp_neuron_label(args.parent),
+                if args.start_dissolving {
+                    " The new neuron starts dissolving."
+                } else {
+                    ""
+                }
+            ),
+            IcpNeuronArgs::CreateProposal(args) => format!(
+                "## Create NNS proposal\n\nSubmit the proposal **{}** with {}.\n\nURL: {}",
+                args.proposal.title.clone().unwrap_or_default(),
+                Self::icp_neuron_label(args.neuron),
+                args.proposal.url
+            ),
+            IcpNeuronArgs::Vote(args) => format!(
+                "## Vote on NNS proposal\n\nVote **{}** on proposal **{}** with {}.",
+                Self::format_vote(&args.vote),
+                args.proposal_id,
+                Self::icp_neuron_label(args.neuron)
+            ),
+            IcpNeuronArgs::Disburse(args) => {
+                let destination = NeuronLogic::resolve_disburse_destination(
+                    args.destination.clone(),
+                )
+                .map(|account| account.to_hex())
+                .unwrap_or_else(|_| "an unknown account".to_string());
+
+                format!(
+                    "## Disburse ICP neuron\n\nDisburse **{}** of {} to account `{}`.",
+                    args.amount_e8s
+                        .map(format_icp)
+                        .unwrap_or("the full stake".to_string()),
+                    Self::icp_neuron_label(args.neuron),
+                    destination
+                )
+            }
+            IcpNeuronArgs::SetFollowing(args) => {
+                let mut message = format!(
+                    "## Set following\n\nChange the followees of {}:\n",
+                    Self::icp_neuron_label(args.neuron)
+                );
+                for following in &args.following {
+                    if following.followees.is_empty() {
+                        message.push_str(&format!(
+                            "\n- **{:?}**: stop following",
+                            following.topic
+                        ));
+                    } else {
+                        message.push_str(&format!(
+                            "\n- **{:?}**: follow {}",
+                            following.topic,
+                            following
+                                .followees
+                                .iter()
+                                .map(|id| id.to_string())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        ));
+                    }
+                }
+                message
+            }
+            IcpNeuronArgs::Command(args) => {
+                let mut message = format!(
+                    "## Manage neuron command\n\nSend a raw **{}** command to {}.",
+                    Self::command_name(&args.command),
+                    Self::icp_neuron_label(args.neuron)
+                );
+                if let Some(payload) = Self::describe_command(&args.command) {
+                    message.push_str(&format!("\n\n{}", payload));
+                }
+                message
+            }
+            IcpNeuronArgs::Split(args) => format!(
+                "## Split neuron\n\nSplit **{}** off {} into a new neuron.",
+                format_icp(args.amount_e8s),
+                Self::icp_neuron_label(args.neuron)
+            ),
+            IcpNeuronArgs::Merge(args) => format!(
+                "## Merge neurons\n\nMerge {} into {}. The source neuron is no longer managed afterwards.",
+                Self::icp_neuron_label(args.source),
+                Self::icp_neuron_label(args.target)
+            ),
+            IcpNeuronArgs::StakeMaturity(args) => format!(
+                "## Stake maturity\n\nStake **{}%** of the maturity of {}.",
+                args.percentage,
+                Self::icp_neuron_label(args.neuron)
+            ),
+            IcpNeuronArgs::DisburseMaturity(args) => {
+                let to_account = args
+                    .to_account
+                    .as_ref()
+                    .map(|account| account.to_string())
+                    .unwrap_or_else(|| {
+                        Account {
+                            owner: canister_self(),
+                            subaccount: None,
+                        }
+                        .to_string()
+                    });
+                format!(
+                    "## Disburse maturity\n\nDisburse **{}%** of the maturity of {} to account `{}`.",
+                    args.percentage,
+                    Self::icp_neuron_label(args.neuron),
+                    to_account
+                )
+            }
+            IcpNeuronArgs::AddHotKey(args) => format!(
+                "## Add hot key\n\nAllow `{}` to vote and follow with {}.",
+                args.hot_key,
+                Self::icp_neuron_label(args.neuron)
+            ),
+            IcpNeuronArgs::RemoveHotKey(args) => format!(
+                "## Remove hot key\n\nRemove the hot key `{}` from {}.",
+                args.hot_key,
+                Self::icp_neuron_label(args.neuron)
+            ),
+            IcpNeuronArgs::Reconcile => "## Reconcile neurons\n\nCompare the neuron references with the neurons on the NNS governance canister and import unknown neurons.".to_string(),
+        }
+    }
+
+    /// The payload of the raw commands that move stake, maturity or control of a neuron.
+    /// Governance uses this canister as controller and destination when they are omitted.
+    fn describe_command(command: &ManageNeuronCommandRequest) -> Option<String> {
+        let this_canister = format!("this canister `{}`", canister_self());
+        let controller = |new_controller: Option<Principal>| {
+            new_controller
+                .map(|controller| format!("`{}`", controller))
+                .unwrap_or(this_canister.clone())
+        };
+
+        match command {
+            ManageNeuronCommandRequest::Disburse(disburse) => Some(format!(
+                "Disburse **{}** to account {}.",
+                disburse
+                    .amount
+                    .as_ref()
+                    .map(|amount| format_icp(amount.e8s))
+                    .unwrap_or("the full stake".to_string()),
+                disburse
+                    .to_account
+                    .as_ref()
+                    .map(|account| format!("`{}`", format_hex(&account.hash)))
+                    .unwrap_or(this_canister.clone())
+            )),
+            ManageNeuronCommandRequest::DisburseMaturity(disburse) => Some(format!(
+                "Disburse **{}%** of the maturity to account {}.",
+                disburse.percentage_to_disburse,
+                match (&disburse.to_account, &disburse.to_account_identifier) {
+                    (Some(account), _) => Self::format_gov_account(account),
+                    (None, Some(account)) => format!("`{}`", format_hex(&account.hash)),
+                    (None, None) => this_canister.clone(),
+                }
+            )),
+            ManageNeuronCommandRequest::DisburseToNeuron(disburse) => Some(format!(
+                "Move **{}** of the stake into a new neuron controlled by {}, with a dissolve delay of **{}**.",
+                format_icp(disburse.amount_e8s),
+                controller(disburse.new_controller),
+                format_days(disburse.dissolve_delay_seconds)
+            )),
+            ManageNeuronCommandRequest::Spawn(spawn) => Some(format!(
+                "Spawn **{}%** of the maturity into a new neuron controlled by {}.",
+                spawn.percentage_to_spawn.unwrap_or(100),
+                controller(spawn.new_controller)
+            )),
+            ManageNeuronCommandRequest::Split(split) => Some(format!(
+                "Split **{}** off into a new neuron controlled by {}.",
+                format_icp(split.amount_e8s),
+                this_canister
+            )),
+            _ => None,
+        }
+    }
+
+    fn format_gov_account(account: &GovAccount) -> String {
+        let subaccount = account
+            .subaccount
+            .as_ref()
+            .map(|subaccount| <[u8; 32]>::try_from(subaccount.as_slice()));
+        match (account.owner, subaccount) {
+            (Some(owner), None) => format!(
+                "`{}`",
+                Account {
+                    owner,
+                    subaccount: None
+                }
+            ),
+            (Some(owner), Some(Ok(subaccount))) => format!(
+                "`{}`",
+                Account {
+                    owner,
+                    subaccount: Some(subaccount)
+                }
+            ),
+            _ => "an invalid account".to_string(),
+        }
+    }
+
+    fn describe_sns(args: &SnsNeuronArgs) -> String {
+        match args {
+            SnsNeuronArgs::Create(args) => format!(
+                "## Create SNS neuron\n\nStake **{}** in a new SNS neuron.",
+                format_tokens(args.amount_e8s, "tokens")
+            ),
+            SnsNeuronArgs::TopUp(args) => format!(
+                "## Top up SNS neuron\n\nAdd **{}** to the stake of {}.",
+                format_tokens(args.amount_e8s, "tokens"),
+                Self::sns_neuron_label(args.subaccount)
+            ),
+            SnsNeuronArgs::Claim(args) => format!(
+                "## Claim SNS neuron\n\nClaim {}.",
+                Self::sns_neuron_label(args.subaccount)
+            ),
+            SnsNeuronArgs::AddDissolveDelay(args) => format!(
+                "## Increase dissolve delay\n\nIncrease the dissolve delay of {} by **{}**.",
+                Self::sns_neuron_label(args.subaccount),
+                format_days(args.dissolve_delay_seconds)
+            ),
+            SnsNeuronArgs::SetDissolveState(args) => format!(
+                "## {} dissolving\n\n{} {} dissolving.",
+                if args.start_dissolving {
+                    "Start"
+                } else {
+                    "Stop"
+                },
+                Self::sns_neuron_label(args.subaccount),
+                if args.start_dissolving {
+                    "starts"
+                } else {
+                    "stops"
+                }
+            ),
+            SnsNeuronArgs::Vote(args) => format!(
+                "## Vote on SNS proposal\n\nVote **{}** on proposal **{}** with {}.",
+                Self::format_vote(&args.vote),
+                args.proposal_id,
+                Self::sns_neuron_label(args.subaccount)
+            ),
+            SnsNeuronArgs::SetFollowing(args) => format!(
+                "## Set following\n\nChange the followees of {} for {} functions.",
+                Self::sns_neuron_label(args.subaccount),
+                args.following.len()
+            ),
+            SnsNeuronArgs::Disburse(args) => format!(
+                "## Disburse SNS neuron\n\nDisburse the full stake of {} to this canister.",
+                Self::sns_neuron_label(args.subaccount)
+            ),
+        }
+    }
+
+    /// Splits the message into lines of at most `characters_per_line` characters, breaking on
+    /// whitespace where possible.
+    fn paginate(
+        message: &str,
+        characters_per_line: u16,
+        lines_per_page: u16,
+    ) -> Vec<Icrc21LineDisplayPage> {
+        let characters_per_line = characters_per_line.max(1) as usize;
+        let lines_per_page = lines_per_page.max(1) as usize;
+
+        let mut lines = vec![];
+        for paragraph in message.lines().filter(|line| !line.is_empty()) {
+            let mut line = String::new();
+            for word in paragraph.split_whitespace() {
+                for chunk in word.chars().collect::<Vec<_>>().chunks(characters_per_line) {
+                    let chunk: String = chunk.iter().collect();
+                    let length = line.chars().count();
+                    if length > 0 && length + 1 + chunk.chars().count() > characters_per_line {
+                        lines.push(std::mem::take(&mut line));
+                    }
+                    if !line.is_empty() {
+                        line.push(' ');
+                    }
+                    line.push_str(&chunk);
+                }
+            }
+            if !line.is_empty() {
+                lines.push(line);
+            }
+        }
+
+        lines
+            .chunks(lines_per_page)
+            .map(|lines| Icrc21LineDisplayPage {
+                lines: lines.to_vec(),
+            })
+            .collect()
+    }
+
+    fn icp_neuron_label(neuron: NeuronSelector) -> String {
+        match NeuronReferenceStore::get_by_selector(neuron) {
+            Ok((_, reference)) => match reference.neuron_id {
+                Some(neuron_id) => format!("neuron **{}**", neuron_id),
+                None => format!(
+                    "unclaimed neuron `{}`",
+                    format_subaccount(reference.subaccount)
+                ),
+            },
+            Err(_) => match neuron {
+                NeuronSelector::Subaccount(subaccount) => {
+                    format!("unknown neuron `{}`", format_subaccount(subaccount))
+                }
+                NeuronSelector::NeuronId(neuron_id) => format!("unknown neuron **{}**", neuron_id),
+            },
+        }
+    }
+
+    fn sns_neuron_label(subaccount: [u8; 32]) -> String {
+        match SnsNeuronReferenceStore::get_by_subaccount(subaccount) {
+            Ok(_) => format!("SNS neuron `{}`", format_subaccount(subaccount)),
+            Err(_) => format!("unknown SNS neuron `{}`", format_subaccount(subaccount)),
+        }
+    }
+
+    fn format_vote(vote: &Vote) -> &'static str {
+        match vote {
+            Vote::Approve => "Approve",
+            Vote::Reject => "Reject",
+        }
+    }
+
+    pub fn command_name(command: &ManageNeuronCommandRequest) -> &'static str {
+        match command {
+            ManageNeuronCommandRequest::Spawn(_) => "Spawn",
+            ManageNeuronCommandRequest::Split(_) => "Split",
+            ManageNeuronCommandRequest::Follow(_) => "Follow",
+            ManageNeuronCommandRequest::DisburseMaturity(_) => "DisburseMaturity",
+            ManageNeuronCommandRequest::RefreshVotingPower(_) => "RefreshVotingPower",
+            ManageNeuronCommandRequest::ClaimOrRefresh(_) => "ClaimOrRefresh",
+            ManageNeuronCommandRequest::Configure(_) => "Configure",
+            ManageNeuronCommandRequest::RegisterVote(_) => "RegisterVote",
+            ManageNeuronCommandRequest::Merge(_) => "Merge",
+            ManageNeuronCommandRequest::DisburseToNeuron(_) => "DisburseToNeuron",
+            ManageNeuronCommandRequest::SetFollowing(_) => "SetFollowing",
+            ManageNeuronCommandRequest::MakeProposal(_) => "MakeProposal",
+            ManageNeuronCommandRequest::StakeMaturity(_) => "StakeMaturity",
+            ManageNeuronCommandRequest::MergeMaturity(_) => "MergeMaturity",
+            ManageNeuronCommandRequest::Disburse(_) => "Disburse",
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use ic_cdk::api::{msg_caller, time};
+
+use crate::{
+    storage::log_storage::LogStore,
+    types::log_entry::{GetLogsArgs, LogEntry, LogLevel, LogsResponse},
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 100;
+const MAX_PAGE_SIZE: u64 = 500;
+
+pub struct LogLogic;
+
+impl LogLogic {
+    pub fn info(operation: &str, neuron_key: Option<[u8; 32]>, message: impl Into<String>) {
+        Self::log(LogLevel::Info, operation, neuron_key, message.into());
+    }
+
+    pub fn warning(operation: &str, neuron_key: Option<[u8; 32]>, message: impl Into<String>) {
+        Self::log(LogLevel::Warning, operation, neuron_key, message.into());
+    }
+
+    pub fn error(operation: &str, neuron_key: Option<[u8; 32]>, message: impl Into<String>) {
+        Self::log(LogLevel::Error, operation, neuron_key, message.into());
+    }
+
+    pub fn get_logs(args: GetLogsArgs) -> LogsResponse {
+        let limit = args
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE) as usize;
+
+        let (entries, next) = LogStore::get_page(args.start.unwrap_or(0), limit, |entry| {
+            args.level.is_none_or(|level| entry.level == level)
+                && args
+                    .neuron_key
+                    .is_none_or(|key| entry.neuron_key == Some(key))
+                && args
+                    .from_timestamp
+                    .is_none_or(|from| entry.timestamp >= from)
+                && args.to_timestamp.is_none_or(|to| entry.timestamp <= to)
+        });
+
+        LogsResponse {
+            entries: entries
+                .into_iter()
+                .map(|(id, entry)| entry.to_response(id))
+                .collect(),
+            next,
+        }
+    }
+
+    fn log(level: LogLevel, operation: &str, neuron_key: Option<[u8; 32]>, message: String) {
+        LogStore::insert(LogEntry {
+            timestamp: time(),
+            level,
+            operation: operation.to_string(),
+            neuron_key,
+            message,
+            caller: msg_caller(),
+        });
+    }
+}
@@ -1,2 +1,17 @@
+pub mod audit_logic;
+pub mod block_logic;
 pub mod config_logic;
+pub mod consent_logic;
+pub mod log_logic;
+pub mod neuron_creation_logic;
 pub mod neuron_logic;
+pub mod policy_logic;
+pub mod queue_logic;
+pub mod reconcile_logic;
+pub mod role_logic;
+pub mod sns_neuron_logic;
+pub mod stake_refresh_logic;
+pub mod upgrade_logic;
+pub mod validation_summary_logic;
+pub mod voting_power_logic;
+pub mod voting_rule_logic;
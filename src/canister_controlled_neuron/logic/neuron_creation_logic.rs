@@ -0,0 +1,380 @@
+use std::{cell::RefCell, collections::HashMap, future::Future, time::Duration};
+
+use ic_cdk::api::time;
+use ic_ledger_types::TransferError;
+use toolkit_utils::{
+    api_error::ApiError,
+    icrc_ledger_types::icrc1::transfer::TransferError as IcrcTransferError,
+    result::CanisterResult,
+    storage::{StorageInsertable, StorageQueryable, StorageUpdateable},
+};
+
+use crate::{
+    api::icp_governance_api::SetVisibility,
+    logic::{log_logic::LogLogic, voting_power_logic::VotingPowerLogic},
+    storage::{
+        neuron_creation_storage::NeuronCreationStore,
+        neuron_reference_storage::NeuronReferenceStore,
+        sns_neuron_reference_storage::SnsNeuronReferenceStore,
+    },
+    timers::storages::JobTimers,
+    traits::timer_traits::TimerActions,
+    types::{
+        neuron_creation::{
+            NeuronCreationKind, NeuronCreationOperation, NeuronCreationOperationResponse,
+            NeuronCreationState,
+        },
+        neuron_reference::{NeuronReference, NeuronReferenceResponse},
+        sns_neuron_reference::{SnsNeuronReference, SnsNeuronReferenceResponse},
+    },
+};
+
+const RESUME_JOB: &str = "resume_pending_operations";
+const RETRY_INTERVAL: Duration = Duration::from_secs(5 * 60);
+const MAX_AUTOMATIC_ATTEMPTS: u32 = 10;
+const MINIMUM_STAKE_E8S: u64 = 100_000_000;
+const TRANSACTION_FEE_E8S: u64 = 10_000;
+/// An operation that has been running for longer than this is assumed to have trapped.
+const STALE_AFTER_NANOS: u64 = 10 * 60 * 1_000_000_000;
+
+thread_local! {
+    /// Operations that are currently being advanced, with the time they were picked up.
+    static IN_PROGRESS: RefCell<HashMap<u64, u64>> = RefCell::new(HashMap::default());
+}
+
+pub struct NeuronCreationLogic;
+
+impl NeuronCreationLogic {
+    pub fn get_pending_operations() -> Vec<NeuronCreationOperationResponse> {
+        NeuronCreationStore::get_pending()
+            .into_iter()
+            .map(|(id, operation)| operation.to_response(id))
+            .collect()
+    }
+
+    /// Stores the neuron reference and the creation operation before anything is sent to the
+    /// ledger, so a failed or interrupted creation can always be picked up again.
+    pub fn start(
+        amount_e8s: u64,
+        auto_stake: Option<bool>,
+        dissolve_delay_seconds: Option<u64>,
+    ) -> CanisterResult<u64> {
+        if amount_e8s < MINIMUM_STAKE_E8S + TRANSACTION_FEE_E8S {
+            return Err(ApiError::bad_request(
+                "Amount must be greater than 1 ICP + fee",
+            ));
+        }
+
+        let nonce = NeuronReferenceStore::next_nonce()?;
+        let (neuron_reference_id, _) = NeuronReferenceStore::insert(NeuronReference::new(nonce))?;
+        let (id, _) = NeuronCreationStore::insert(NeuronCreationOperation::new(
+            NeuronCreationKind::Icp,
+            neuron_reference_id,
+            amount_e8s,
+            auto_stake,
+            dissolve_delay_seconds,
+        ))?;
+        Ok(id)
+    }
+
+    /// The SNS counterpart of `start`, the minimum stake is checked by the caller because it is
+    /// a parameter of the SNS governance.
+    pub fn start_sns(amount_e8s: u64, dissolve_delay_seconds: Option<u64>) -> CanisterResult<u64> {
+        let nonce = SnsNeuronReferenceStore::next_nonce()?;
+        let (neuron_reference_id, _) =
+            SnsNeuronReferenceStore::insert(SnsNeuronReference::new(nonce))?;
+        let (id, _) = NeuronCreationStore::insert(NeuronCreationOperation::new(
+            NeuronCreationKind::Sns,
+            neuron_reference_id,
+            amount_e8s,
+            None,
+            dissolve_delay_seconds,
+        ))?;
+        Ok(id)
+    }
+
+    /// Runs the remaining steps of the operation. On failure the error is recorded on the
+    /// operation and an automatic retry is scheduled.
+    pub async fn advance(id: u64) -> CanisterResult<NeuronReferenceResponse> {
+        Self::guarded(id, Self::run(id)).await
+    }
+
+    pub async fn advance_sns(id: u64) -> CanisterResult<SnsNeuronReferenceResponse> {
+        Self::guarded(id, Self::run_sns(id)).await
+    }
+
+    pub async fn resume_pending_operations() -> Vec<NeuronCreationOperationResponse> {
+        JobTimers::clear(&RESUME_JOB.to_string());
+
+        for (id, operation) in NeuronCreationStore::get_pending() {
+            match operation.kind() {
+                NeuronCreationKind::Icp => {
+                    let _ = Self::advance(id).await;
+                }
+                NeuronCreationKind::Sns => {
+                    let _ = Self::advance_sns(id).await;
+                }
+            }
+        }
+
+        Self::get_pending_operations()
+    }
+
+    /// Schedules a background run of `resume_pending_operations` when there are pending
+    /// operations that have not yet exhausted their automatic retries.
+    pub fn schedule_retry() {
+        let has_retryable = NeuronCreationStore::get_pending()
+            .iter()
+            .any(|(_, operation)| operation.attempts < MAX_AUTOMATIC_ATTEMPTS);
+
+        if has_retryable {
+            JobTimers::create_once(&RESUME_JOB.to_string(), RETRY_INTERVAL, || {
+                ic_cdk::futures::spawn(async {
+                    let _ = NeuronCreationLogic::resume_pending_operations().await;
+                });
+            });
+        }
+    }
+
+    async fn run(id: u64) -> CanisterResult<NeuronReferenceResponse> {
+        let (_, mut operation) = NeuronCreationStore::get(id)?;
+        let (neuron_reference_id, mut neuron) =
+            NeuronReferenceStore::get(operation.neuron_reference_id)?;
+
+        if operation.state == NeuronCreationState::Pending {
+            // A previous attempt may have transferred the stake without recording it
+            let balance = if operation.attempts > 0 {
+                neuron.stake_balance().await?
+            } else {
+                0
+            };
+
+            if balance < operation.amount_e8s {
+                if let Some(blockheight) = Self::fund(id, &mut operation, &neuron).await? {
+                    neuron.blockheight = blockheight;
+                    NeuronReferenceStore::update(neuron_reference_id, neuron.clone())?;
+                }
+            }
+
+            operation = Self::set_state(id, operation, NeuronCreationState::Transferred)?;
+        }
+
+        if operation.state == NeuronCreationState::Transferred {
+            let claimed_neuron = neuron.claim_or_refresh().await?;
+            let neuron_id = claimed_neuron
+                .refreshed_neuron_id
+                .ok_or(ApiError::external_service_error("Neuron not claimed"))?;
+
+            neuron.neuron_id = Some(neuron_id.id);
+            NeuronReferenceStore::update(neuron_reference_id, neuron.clone())?;
+
+            operation = Self::set_state(id, operation, NeuronCreationState::Claimed)?;
+        }
+
+        if operation.state == NeuronCreationState::Claimed {
+            // Make neuron public by default
+            neuron
+                .set_publicity(SetVisibility {
+                    visibility: Some(2),
+                })
+                .await?;
+
+            if let Some(dissolve_delay) = operation.dissolve_delay_seconds {
+                neuron.ensure_dissolve_delay(dissolve_delay).await?;
+            }
+
+            if let Some(auto_stake) = operation.auto_stake {
+                neuron.auto_stake_maturity(auto_stake).await?;
+            }
+
+            Self::set_state(id, operation, NeuronCreationState::Configured)?;
+            VotingPowerLogic::schedule(neuron_reference_id, &neuron);
+        }
+
+        neuron.to_response(neuron_reference_id)
+    }
+
+    /// Transfers the stake, `None` when it turns out the stake already arrived. The ledger only
+    /// deduplicates transfers within its transaction window, a retry after that window is sent
+    /// with a fresh timestamp once the balance shows that the earlier transfer did not arrive.
+    async fn fund(
+        id: u64,
+        operation: &mut NeuronCreationOperation,
+        neuron: &NeuronReference,
+    ) -> CanisterResult<Option<u64>> {
+        match neuron
+            .fund(operation.amount_e8s, operation.transfer_created_at())
+            .await?
+        {
+            Ok(blockheight) => return Ok(Some(blockheight)),
+            Err(TransferError::TxTooOld { .. }) => {}
+            Err(e) => return Err(ApiError::external_service_error(&e.to_string())),
+        }
+
+        if neuron.stake_balance().await? >= operation.amount_e8s {
+            return Ok(None);
+        }
+
+        operation.transfer_created_at = Some(time());
+        operation.updated_at = time();
+        let (_, updated) = NeuronCreationStore::update(id, operation.clone())?;
+        *operation = updated;
+
+        match neuron
+            .fund(operation.amount_e8s, operation.transfer_created_at())
+            .await?
+        {
+            Ok(blockheight) => Ok(Some(blockheight)),
+            Err(e) => Err(ApiError::external_service_error(&e.to_string())),
+        }
+    }
+
+    async fn run_sns(id: u64) -> CanisterResult<SnsNeuronReferenceResponse> {
+        let (_, mut operation) = NeuronCreationStore::get(id)?;
+        let (neuron_reference_id, mut neuron) =
+            SnsNeuronReferenceStore::get(operation.neuron_reference_id)?;
+
+        if operation.state == NeuronCreationState::Pending {
+            // A previous attempt may have transferred the stake without recording it
+            let balance = if operation.attempts > 0 {
+                neuron.stake_balance().await?
+            } else {
+                0
+            };
+
+            if balance < operation.amount_e8s {
+                if let Some(blockheight) = Self::fund_sns(id, &mut operation, &neuron).await? {
+                    neuron.blockheight = blockheight;
+                    SnsNeuronReferenceStore::update(neuron_reference_id, neuron.clone())?;
+                }
+            }
+
+            operation = Self::set_state(id, operation, NeuronCreationState::Transferred)?;
+        }
+
+        if operation.state == NeuronCreationState::Transferred {
+            let claimed_neuron = neuron.claim_or_refresh().await?;
+            let neuron_id = claimed_neuron
+                .refreshed_neuron_id
+                .and_then(|neuron_id| neuron_id.id.try_into().ok())
+                .ok_or(ApiError::external_service_error("Neuron not claimed"))?;
+
+            neuron.neuron_id = Some(neuron_id);
+            SnsNeuronReferenceStore::update(neuron_reference_id, neuron.clone())?;
+
+            operation = Self::set_state(id, operation, NeuronCreationState::Claimed)?;
+        }
+
+        if operation.state == NeuronCreationState::Claimed {
+            if let Some(dissolve_delay) = operation.dissolve_delay_seconds {
+                neuron.ensure_dissolve_delay(dissolve_delay).await?;
+            }
+
+            Self::set_state(id, operation, NeuronCreationState::Configured)?;
+        }
+
+        neuron.to_response(neuron_reference_id)
+    }
+
+    /// The SNS counterpart of `fund`.
+    async fn fund_sns(
+        id: u64,
+        operation: &mut NeuronCreationOperation,
+        neuron: &SnsNeuronReference,
+    ) -> CanisterResult<Option<u64>> {
+        match neuron
+            .fund(operation.amount_e8s, operation.transfer_created_at())
+            .await?
+        {
+            Ok(blockheight) => return Ok(Some(blockheight)),
+            Err(IcrcTransferError::TooOld) => {}
+            Err(e) => return Err(ApiError::external_service_error(&format!("{:?}", e))),
+        }
+
+        if neuron.stake_balance().await? >= operation.amount_e8s {
+            return Ok(None);
+        }
+
+        operation.transfer_created_at = Some(time());
+        operation.updated_at = time();
+        let (_, updated) = NeuronCreationStore::update(id, operation.clone())?;
+        *operation = updated;
+
+        match neuron
+            .fund(operation.amount_e8s, operation.transfer_created_at())
+            .await?
+        {
+            Ok(blockheight) => Ok(Some(blockheight)),
+            Err(e) => Err(ApiError::external_service_error(&format!("{:?}", e))),
+        }
+    }
+
+    /// Runs `future` unless the operation is already being advanced, and records its failure.
+    async fn guarded<T>(
+        id: u64,
+        future: impl Future<Output = CanisterResult<T>>,
+    ) -> CanisterResult<T> {
+        if !Self::acquire(id) {
+            return Err(ApiError::bad_request(
+                "Neuron creation is already in progress",
+            ));
+        }
+
+        let result = future.await;
+        Self::release(id);
+
+        if let Err(e) = &result {
+            Self::record_failure(id, e.to_string());
+        }
+
+        result
+    }
+
+    fn set_state(
+        id: u64,
+        mut operation: NeuronCreationOperation,
+        state: NeuronCreationState,
+    ) -> CanisterResult<NeuronCreationOperation> {
+        operation.state = state;
+        operation.last_error = None;
+        operation.updated_at = time();
+        let (_, operation) = NeuronCreationStore::update(id, operation)?;
+        Ok(operation)
+    }
+
+    fn record_failure(id: u64, error: String) {
+        LogLogic::error(
+            "create_neuron",
+            None,
+            format!("Error creating neuron (operation {}): {}", id, error),
+        );
+
+        if let Ok((_, mut operation)) = NeuronCreationStore::get(id) {
+            operation.attempts += 1;
+            operation.last_error = Some(error);
+            operation.updated_at = time();
+            let _ = NeuronCreationStore::update(id, operation);
+        }
+
+        Self::schedule_retry();
+    }
+
+    fn acquire(id: u64) -> bool {
+        IN_PROGRESS.with(|in_progress| {
+            let mut in_progress = in_progress.borrow_mut();
+            match in_progress.get(&id) {
+                Some(started_at) if time() - started_at < STALE_AFTER_NANOS => false,
+                _ => {
+                    in_progress.insert(id, time());
+                    true
+                }
+            }
+        })
+    }
+
+    fn release(id: u64) {
+        IN_PROGRESS.with(|in_progress| {
+            in_progress.borrow_mut().remove(&id);
+        });
+    }
+}
@@ -1,108 +1,81 @@
+use std::collections::{HashMap, HashSet};
+
+use candid::Principal;
 use ic_cdk::api::{canister_self, time};
+use ic_ledger_types::{AccountBalanceArgs, AccountIdentifier, Subaccount, DEFAULT_SUBACCOUNT};
 use toolkit_utils::{
-    api_error::ApiError,
-    result::CanisterResult,
-    storage::{StorageInsertable, StorageQueryable, StorageUpdateable},
-    transactions::get_icp_balance,
+    api_error::ApiError, icrc_ledger_types::icrc1::account::Account, result::CanisterResult,
+    storage::StorageQueryable,
 };
 
 use crate::{
     api::{
         api_clients::ApiClients,
         icp_governance_api::{
-            DisburseResponse, ListNeurons, ListNeuronsResponse, MakeProposalRequest,
-            MakeProposalResponse, ManageNeuronCommandRequest, ManageNeuronResponse,
-            Neuron as GovNeuron, SetVisibility,
+            Account as GovAccount, AddHotKey, ChangeAutoStakeMaturity, Command1, Configure,
+            DisburseResponse, DissolveState, IncreaseDissolveDelay, ListNeurons,
+            MakeProposalRequest, MakeProposalResponse, ManageNeuronCommandRequest,
+            ManageNeuronResponse, Merge, Neuron as GovNeuron, NeuronInfo, Operation, RemoveHotKey,
+            Result5, SetVisibility, Split,
         },
     },
-    helpers::subaccount_helper::generate_subaccount_by_nonce,
-    storage::{log_storage::LogStore, neuron_reference_storage::NeuronReferenceStore},
+    helpers::format_helper::{format_days, format_icp},
+    logic::{
+        config_logic::ConfigLogic, log_logic::LogLogic, neuron_creation_logic::NeuronCreationLogic,
+        policy_logic::PolicyLogic, reconcile_logic::ReconcileLogic,
+        sns_neuron_logic::SnsNeuronLogic, validation_summary_logic::ValidationSummary,
+        voting_power_logic::VotingPowerLogic,
+    },
+    storage::neuron_reference_storage::NeuronReferenceStore,
     types::{
-        modules::{IcpNeuronArgs, ModuleResponse, NeuronType, Vote},
+        modules::{
+            DisburseDestination, FollowingArgs, IcpNeuronArgs, ModuleResponse, NeuronType,
+            TopicFollowees, Vote,
+        },
         neuron_reference::{NeuronReference, NeuronReferenceResponse},
-        topic::Topic,
     },
 };
 
+const MINIMUM_STAKE_E8S: u64 = 100_000_000;
+const TRANSACTION_FEE_E8S: u64 = 10_000;
+const MINIMUM_MATURITY_DISBURSEMENT_E8S: u64 = 100_000_000;
+const MAX_MATURITY_DISBURSEMENTS_IN_PROGRESS: usize = 10;
+const MAX_HOT_KEYS: usize = 10;
+const MAX_FOLLOWEES_PER_TOPIC: usize = 15;
+const MAX_DISSOLVE_DELAY_SECONDS: u64 = 252_460_800;
+const LIST_NEURONS_PAGE_SIZE: u64 = 500;
+
 pub struct NeuronLogic;
 
 impl NeuronLogic {
     pub fn remove_neuron(id: u64) -> CanisterResult<()> {
+        let (_, neuron) = NeuronReferenceStore::get(id)?;
+        VotingPowerLogic::unschedule(id, &neuron);
         NeuronReferenceStore::remove(id);
         Ok(())
     }
 
+    pub fn get_neuron_by_neuron_id(neuron_id: u64) -> CanisterResult<NeuronReferenceResponse> {
+        let (id, neuron) = NeuronReferenceStore::get_by_neuron_id(neuron_id)?;
+        neuron.to_response(id)
+    }
+
     pub fn get_neurons() -> CanisterResult<Vec<NeuronReferenceResponse>> {
-        let neurons = NeuronReferenceStore::get_all();
-        Ok(neurons
+        NeuronReferenceStore::get_all()
             .into_iter()
             .map(|(id, neuron)| neuron.to_response(id))
-            .collect())
+            .collect()
     }
 
+    /// Creates a neuron through a persisted creation operation, see `NeuronCreationLogic`.
+    /// When a step fails the operation is kept and retried in the background.
     pub async fn create_neuron(
         amount_e8s: u64,
         auto_stake: Option<bool>,
         dissolve_delay: Option<u64>,
     ) -> CanisterResult<NeuronReferenceResponse> {
-        let neuron = NeuronReference::new(amount_e8s).await.map_err(|e| {
-            let _ = LogStore::insert(format!("{}: Error creating neuron: {}", time(), e));
-            e
-        })?;
-        let (id, mut neuron) = NeuronReferenceStore::insert(neuron.clone())?;
-
-        let claimed_neuron = neuron.claim_or_refresh().await.map_err(|e| {
-            let _ = LogStore::insert(format!(
-                "{}: Error claiming or refreshing neuron: {}",
-                time(),
-                e
-            ));
-            e
-        })?;
-
-        if let Some(refreshed_neuron_id) = claimed_neuron.refreshed_neuron_id {
-            neuron.neuron_id = Some(refreshed_neuron_id.id);
-        }
-
-        let response = NeuronReferenceStore::update(id, neuron.clone())?;
-
-        // Make neuron public by default
-        neuron
-            .set_publicity(SetVisibility {
-                visibility: Some(2),
-            })
-            .await
-            .map_err(|e| {
-                let _ = LogStore::insert(format!("{}: Error setting visibility: {}", time(), e));
-                e
-            })?;
-
-        if let Some(dissolve_delay) = dissolve_delay {
-            neuron
-                .increase_dissolve_delay(dissolve_delay)
-                .await
-                .map_err(|e| {
-                    let _ = LogStore::insert(format!(
-                        "{}: Error setting dissolve delay: {}",
-                        time(),
-                        e
-                    ));
-                    e
-                })?;
-        }
-
-        if let Some(auto_stake) = auto_stake {
-            neuron.auto_stake_maturity(auto_stake).await.map_err(|e| {
-                let _ = LogStore::insert(format!(
-                    "{}: Error setting auto stake maturity: {}",
-                    time(),
-                    e
-                ));
-                e
-            })?;
-        }
-
-        Ok(response.1.to_response(response.0))
+        let id = NeuronCreationLogic::start(amount_e8s, auto_stake, dissolve_delay)?;
+        NeuronCreationLogic::advance(id).await
     }
 
     pub async fn top_up_neuron_by_subaccount(
@@ -112,16 +85,20 @@ impl NeuronLogic {
         let (_, mut neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
 
         let _ = neuron.top_up(amount_e8s).await.map_err(|e| {
-            let _ = LogStore::insert(format!("{}: Error topping up neuron: {}", time(), e));
+            LogLogic::error(
+                "top_up",
+                Some(subaccount),
+                format!("Error topping up neuron: {}", e),
+            );
             e
         })?;
 
         neuron.claim_or_refresh().await.map_err(|e| {
-            let _ = LogStore::insert(format!(
-                "{}: Error claiming or refreshing neuron: {}",
-                time(),
-                e
-            ));
+            LogLogic::error(
+                "top_up",
+                Some(subaccount),
+                format!("Error claiming or refreshing neuron: {}", e),
+            );
             e
         })?;
 
@@ -134,7 +111,11 @@ impl NeuronLogic {
     ) -> CanisterResult<ManageNeuronResponse> {
         let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
         let neuron = neuron.command(command).await.map_err(|e| {
-            let _ = LogStore::insert(format!("{}: Error commanding neuron: {}", time(), e));
+            LogLogic::error(
+                "command_neuron",
+                Some(subaccount),
+                format!("Error commanding neuron: {}", e),
+            );
             e
         })?;
         Ok(neuron)
@@ -146,7 +127,11 @@ impl NeuronLogic {
     ) -> CanisterResult<bool> {
         let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
         neuron.increase_dissolve_delay(dissolve_delay).await?;
-        let _ = LogStore::insert(format!("Dissolve delay set to {} seconds", dissolve_delay));
+        LogLogic::info(
+            "add_dissolve_delay",
+            Some(subaccount),
+            format!("Dissolve delay set to {} seconds", dissolve_delay),
+        );
         Ok(true)
     }
 
@@ -165,7 +150,11 @@ impl NeuronLogic {
     ) -> CanisterResult<bool> {
         let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
         neuron.auto_stake_maturity(auto_stake).await?;
-        let _ = LogStore::insert(format!("Auto stake maturity set to {}", auto_stake));
+        LogLogic::info(
+            "auto_stake_maturity",
+            Some(subaccount),
+            format!("Auto stake maturity set to {}", auto_stake),
+        );
         Ok(true)
     }
 
@@ -174,23 +163,19 @@ impl NeuronLogic {
         start_dissolving: bool,
     ) -> CanisterResult<bool> {
         let (_, parent_neuron) = NeuronReferenceStore::get_by_subaccount(parent_subaccount)?;
-        let new_nonce = NeuronReferenceStore::get_latest_key() + 1;
+        let new_nonce = NeuronReferenceStore::next_nonce()?;
         let new_neuron_id = parent_neuron
             .spawn(new_nonce)
             .await?
             .created_neuron_id
             .map(|id| id.id);
 
-        let subaccount = generate_subaccount_by_nonce(new_nonce);
-        let spawned_neuron = NeuronReference {
-            blockheight: 0,
-            subaccount,
-            nonce: new_nonce,
-            neuron_id: new_neuron_id,
-            parent_subaccount: Some(parent_subaccount),
-        };
+        let mut spawned_neuron = NeuronReference::new(new_nonce);
+        spawned_neuron.neuron_id = new_neuron_id;
+        spawned_neuron.parent_subaccount = Some(parent_subaccount);
 
-        let _ = NeuronReferenceStore::insert(spawned_neuron.clone())?;
+        let (id, _) = NeuronReferenceStore::insert(spawned_neuron.clone())?;
+        VotingPowerLogic::schedule(id, &spawned_neuron);
 
         spawned_neuron
             .set_publicity(SetVisibility {
@@ -198,39 +183,247 @@ impl NeuronLogic {
             })
             .await
             .map_err(|e| {
-                let _ = LogStore::insert(format!(
-                    "{}: Error setting visibility for spawned neuron: {}",
-                    time(),
-                    e
-                ));
+                LogLogic::error(
+                    "spawn_neuron",
+                    Some(parent_subaccount),
+                    format!("Error setting visibility for spawned neuron: {}", e),
+                );
                 e
             })?;
 
-        let _ = LogStore::insert(format!(
-            "{}: Spawned neuron with neuron_id: {:?}",
-            time(),
-            new_neuron_id
-        ));
+        LogLogic::info(
+            "spawn_neuron",
+            Some(parent_subaccount),
+            format!("Spawned neuron with neuron_id: {:?}", new_neuron_id),
+        );
 
         if start_dissolving {
             spawned_neuron.set_dissolve_state(true).await?;
-            let _ = LogStore::insert(format!(
-                "{}: Started dissolving neuron with neuron_id: {:?}",
-                time(),
-                new_neuron_id
-            ));
+            LogLogic::info(
+                "spawn_neuron",
+                Some(parent_subaccount),
+                format!(
+                    "Started dissolving neuron with neuron_id: {:?}",
+                    new_neuron_id
+                ),
+            );
+        }
+
+        Ok(true)
+    }
+
+    /// Splits `amount_e8s` off the neuron into a new neuron, the stake of the new neuron
+    /// is the amount minus the transaction fee. Governance does not derive the subaccount of the
+    /// new neuron from the nonce, so it is read from the new neuron.
+    pub async fn split_neuron(
+        parent_subaccount: [u8; 32],
+        amount_e8s: u64,
+        memo: Option<u64>,
+    ) -> CanisterResult<NeuronReferenceResponse> {
+        let (_, parent_neuron) = NeuronReferenceStore::get_by_subaccount(parent_subaccount)?;
+        let nonce = NeuronReferenceStore::next_nonce()?;
+
+        let new_neuron_id = parent_neuron
+            .split(amount_e8s, memo.unwrap_or(nonce))
+            .await
+            .map_err(|e| {
+                LogLogic::error(
+                    "split_neuron",
+                    Some(parent_subaccount),
+                    format!("Error splitting neuron: {}", e),
+                );
+                e
+            })?
+            .created_neuron_id
+            .map(|id| id.id)
+            .ok_or(ApiError::external_service_error("Split neuron not created"))?;
+
+        // The split already happened, so the child is always stored. When its account can not be
+        // read back it keeps the subaccount derived from the nonce until the voting power refresh
+        // or reconcile, which both look it up by neuron id, replace it with the actual one.
+        let mut split_neuron = NeuronReference::new(nonce);
+        split_neuron.neuron_id = Some(new_neuron_id);
+        split_neuron.parent_subaccount = Some(parent_subaccount);
+        match split_neuron.get_full_neuron().await.and_then(|neuron| {
+            neuron
+                .account
+                .try_into()
+                .map_err(|_| ApiError::external_service_error("Invalid neuron account"))
+        }) {
+            Ok(subaccount) => split_neuron.subaccount = subaccount,
+            Err(e) => LogLogic::warning(
+                "split_neuron",
+                Some(parent_subaccount),
+                format!(
+                    "Error getting the account of split neuron {}, it is filled in later: {}",
+                    new_neuron_id, e
+                ),
+            ),
+        }
+
+        let (id, split_neuron) = NeuronReferenceStore::insert(split_neuron)?;
+        VotingPowerLogic::schedule(id, &split_neuron);
+        split_neuron.to_response(id)
+    }
+
+    pub async fn merge_neurons(
+        target_subaccount: [u8; 32],
+        source_subaccount: [u8; 32],
+    ) -> CanisterResult<NeuronReferenceResponse> {
+        let (target_id, mut target_neuron) =
+            NeuronReferenceStore::get_by_subaccount(target_subaccount)?;
+        let (source_id, source_neuron) =
+            NeuronReferenceStore::get_by_subaccount(source_subaccount)?;
+
+        let source_neuron_id = source_neuron
+            .neuron_id
+            .ok_or(ApiError::bad_request("Source neuron not claimed yet"))?;
+
+        target_neuron.merge(source_neuron_id).await.map_err(|e| {
+            LogLogic::error(
+                "merge_neurons",
+                Some(target_subaccount),
+                format!("Error merging neurons: {}", e),
+            );
+            e
+        })?;
+
+        // The emptied source neuron stays on the governance canister but is no longer managed,
+        // its nonce is not handed out again so a new neuron never reuses its subaccount
+        NeuronLogic::remove_neuron(source_id)?;
+        LogLogic::info(
+            "merge_neurons",
+            Some(target_subaccount),
+            format!(
+                "Merged neuron {} into neuron {:?}",
+                source_neuron_id, target_neuron.neuron_id
+            ),
+        );
+
+        if let Err(e) = target_neuron.claim_or_refresh().await {
+            LogLogic::error(
+                "merge_neurons",
+                Some(target_subaccount),
+                format!("Error refreshing neuron after merge: {}", e),
+            );
+        }
+
+        target_neuron.to_response(target_id)
+    }
+
+    pub async fn stake_maturity(subaccount: [u8; 32], percentage: u32) -> CanisterResult<bool> {
+        let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
+        neuron.stake_maturity(percentage).await.map_err(|e| {
+            LogLogic::error(
+                "stake_maturity",
+                Some(subaccount),
+                format!("Error staking maturity: {}", e),
+            );
+            e
+        })?;
+        Ok(true)
+    }
+
+    pub async fn disburse_maturity(
+        subaccount: [u8; 32],
+        percentage: u32,
+        to_account: Option<Account>,
+    ) -> CanisterResult<bool> {
+        let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
+        let to_account = to_account.unwrap_or(Account {
+            owner: canister_self(),
+            subaccount: None,
+        });
+
+        let response = neuron
+            .disburse_maturity(
+                percentage,
+                GovAccount {
+                    owner: Some(to_account.owner),
+                    subaccount: to_account.subaccount.map(|subaccount| subaccount.to_vec()),
+                },
+            )
+            .await
+            .map_err(|e| {
+                LogLogic::error(
+                    "disburse_maturity",
+                    Some(subaccount),
+                    format!("Error disbursing maturity: {}", e),
+                );
+                e
+            })?;
+
+        LogLogic::info(
+            "disburse_maturity",
+            Some(subaccount),
+            format!(
+                "Disbursing {:?} e8s of maturity to {}",
+                response.amount_disbursed_e8s, to_account
+            ),
+        );
+        Ok(true)
+    }
+
+    pub fn get_hot_keys(subaccount: [u8; 32]) -> CanisterResult<Vec<Principal>> {
+        let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
+        Ok(neuron.hot_keys)
+    }
+
+    pub async fn add_hot_key(subaccount: [u8; 32], hot_key: Principal) -> CanisterResult<bool> {
+        let (id, mut neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
+        neuron.add_hot_key(hot_key).await.map_err(|e| {
+            LogLogic::error(
+                "add_hot_key",
+                Some(subaccount),
+                format!("Error adding hot key: {}", e),
+            );
+            e
+        })?;
+
+        if !neuron.hot_keys.contains(&hot_key) {
+            neuron.hot_keys.push(hot_key);
         }
+        NeuronLogic::sync_hot_keys(id, neuron).await?;
+        Ok(true)
+    }
+
+    pub async fn remove_hot_key(subaccount: [u8; 32], hot_key: Principal) -> CanisterResult<bool> {
+        let (id, mut neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
+        neuron.remove_hot_key(hot_key).await.map_err(|e| {
+            LogLogic::error(
+                "remove_hot_key",
+                Some(subaccount),
+                format!("Error removing hot key: {}", e),
+            );
+            e
+        })?;
 
+        neuron.hot_keys.retain(|key| key != &hot_key);
+        NeuronLogic::sync_hot_keys(id, neuron).await?;
         Ok(true)
     }
 
+    /// Stores the hot keys as reported by the governance canister, which also picks up hot keys
+    /// that were set before they were tracked here. Falls back to the locally updated set.
+    async fn sync_hot_keys(id: u64, mut neuron: NeuronReference) -> CanisterResult<()> {
+        if let Ok(full_neuron) = neuron.get_full_neuron().await {
+            neuron.hot_keys = full_neuron.hot_keys;
+        }
+        NeuronReferenceStore::update(id, neuron)?;
+        Ok(())
+    }
+
     pub async fn create_proposal(
         subaccount: [u8; 32],
         proposal: MakeProposalRequest,
     ) -> CanisterResult<MakeProposalResponse> {
         let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
         let result = neuron.create_proposal(proposal).await.map_err(|e| {
-            let _ = LogStore::insert(format!("{}: Error creating proposal: {}", time(), e));
+            LogLogic::error(
+                "create_proposal",
+                Some(subaccount),
+                format!("Error creating proposal: {}", e),
+            );
             e
         })?;
         Ok(result)
@@ -239,16 +432,46 @@ impl NeuronLogic {
     pub async fn vote(subaccount: [u8; 32], proposal_id: u64, vote: Vote) -> CanisterResult<bool> {
         let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
         let result = neuron.vote(proposal_id, vote).await.map_err(|e| {
-            let _ = LogStore::insert(format!("{}: Error voting: {}", time(), e));
+            LogLogic::error("vote", Some(subaccount), format!("Error voting: {}", e));
             e
         })?;
 
         Ok(result)
     }
 
-    pub async fn disburse(subaccount: [u8; 32]) -> CanisterResult<DisburseResponse> {
+    pub async fn disburse(
+        subaccount: [u8; 32],
+        amount_e8s: Option<u64>,
+        destination: Option<DisburseDestination>,
+    ) -> CanisterResult<DisburseResponse> {
         let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
-        neuron.disburse().await
+        let to_account = NeuronLogic::resolve_disburse_destination(destination)?;
+        neuron.disburse(amount_e8s, to_account).await.map_err(|e| {
+            LogLogic::error(
+                "disburse",
+                Some(subaccount),
+                format!("Error disbursing neuron: {}", e),
+            );
+            e
+        })
+    }
+
+    pub fn resolve_disburse_destination(
+        destination: Option<DisburseDestination>,
+    ) -> CanisterResult<AccountIdentifier> {
+        match destination {
+            Some(DisburseDestination::Account(account)) => Ok(AccountIdentifier::new(
+                &account.owner,
+                &Subaccount(account.subaccount.unwrap_or(DEFAULT_SUBACCOUNT.0)),
+            )),
+            Some(DisburseDestination::AccountIdentifier(account_identifier)) => {
+                Ok(account_identifier)
+            }
+            None => Ok(AccountIdentifier::new(
+                &ConfigLogic::get_config()?.governance_canister_id,
+                &DEFAULT_SUBACCOUNT,
+            )),
+        }
     }
 
     pub async fn set_visibility(subaccount: [u8; 32], visibility: i32) -> CanisterResult<()> {
@@ -259,7 +482,11 @@ impl NeuronLogic {
             })
             .await
             .map_err(|e| {
-                let _ = LogStore::insert(format!("{}: Error setting visibility: {}", time(), e));
+                LogLogic::error(
+                    "set_visibility",
+                    Some(subaccount),
+                    format!("Error setting visibility: {}", e),
+                );
                 e
             })?;
         Ok(())
@@ -267,11 +494,45 @@ impl NeuronLogic {
 
     pub async fn set_following(
         subaccount: [u8; 32],
-        topic: Topic,
-        following_neurons: Vec<u64>,
-    ) -> CanisterResult<()> {
+        following: Vec<FollowingArgs>,
+    ) -> CanisterResult<Vec<TopicFollowees>> {
         let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
-        neuron.set_following(topic, following_neurons).await
+        let applied: Vec<TopicFollowees> = following
+            .iter()
+            .map(|args| TopicFollowees {
+                topic: args.topic.clone().into(),
+                followees: args.followees.clone(),
+            })
+            .collect();
+
+        neuron.set_following(following).await.map_err(|e| {
+            LogLogic::error(
+                "set_following",
+                Some(subaccount),
+                format!("Error setting following: {}", e),
+            );
+            e
+        })?;
+
+        // The following is applied at this point, a failed read-back must not report a failure
+        match neuron.get_full_neuron().await {
+            Ok(full_neuron) => Ok(full_neuron
+                .followees
+                .into_iter()
+                .map(|(topic, followees)| TopicFollowees {
+                    topic,
+                    followees: followees.followees.into_iter().map(|id| id.id).collect(),
+                })
+                .collect()),
+            Err(e) => {
+                LogLogic::warning(
+                    "set_following",
+                    Some(subaccount),
+                    format!("Following set, but reading it back failed: {}", e),
+                );
+                Ok(applied)
+            }
+        }
     }
 
     pub async fn get_full_neuron(subaccount: [u8; 32]) -> CanisterResult<GovNeuron> {
@@ -279,24 +540,80 @@ impl NeuronLogic {
         neuron.get_full_neuron().await
     }
 
-    pub async fn list_controlled_neurons() -> CanisterResult<ListNeuronsResponse> {
-        let (result,) = ApiClients::icp_governance()
-            .list_neurons(ListNeurons {
-                page_size: Some(1000),
-                include_public_neurons_in_full_neurons: None,
-                neuron_ids: vec![],
-                page_number: None,
-                include_empty_neurons_readable_by_caller: None,
-                neuron_subaccounts: None,
-                include_neurons_readable_by_caller: true,
+    pub async fn get_neuron_info(neuron_id: u64) -> CanisterResult<NeuronInfo> {
+        let (result,) = ApiClients::icp_governance()?
+            .get_neuron_info(neuron_id)
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        match result {
+            Result5::Ok(neuron_info) => Ok(neuron_info),
+            Result5::Err(_) => Err(ApiError::not_found(&format!(
+                "Neuron {} not found",
+                neuron_id
+            ))),
+        }
+    }
+
+    pub async fn get_icp_balance() -> CanisterResult<u64> {
+        let (balance,) = ApiClients::icp_ledger()?
+            .account_balance(AccountBalanceArgs {
+                account: AccountIdentifier::new(&canister_self(), &DEFAULT_SUBACCOUNT),
             })
             .await
             .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
 
-        Ok(result)
+        Ok(balance.e8s())
+    }
+
+    fn validate_percentage(percentage: u32) -> CanisterResult<()> {
+        if percentage == 0 || percentage > 100 {
+            return Err(ApiError::bad_request(
+                "Percentage must be between 1 and 100",
+            ));
+        }
+        Ok(())
     }
 
+    /// Returns every neuron this canister can read, including empty neurons, across all pages.
+    pub async fn list_controlled_neurons() -> CanisterResult<Vec<GovNeuron>> {
+        let mut neurons = vec![];
+        let mut page_number = 0;
+
+        loop {
+            let (result,) = ApiClients::icp_governance()?
+                .list_neurons(ListNeurons {
+                    page_size: Some(LIST_NEURONS_PAGE_SIZE),
+                    include_public_neurons_in_full_neurons: None,
+                    neuron_ids: vec![],
+                    page_number: Some(page_number),
+                    include_empty_neurons_readable_by_caller: Some(true),
+                    neuron_subaccounts: None,
+                    include_neurons_readable_by_caller: true,
+                })
+                .await
+                .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+            neurons.extend(result.full_neurons);
+
+            page_number += 1;
+            if page_number >= result.total_pages_available.unwrap_or_default() {
+                return Ok(neurons);
+            }
+        }
+    }
+
+    /// Executes the args after checking them against the policy, see `PolicyLogic`.
     pub async fn tk_service_manage_neuron(module: NeuronType) -> CanisterResult<ModuleResponse> {
+        let disbursement = PolicyLogic::enforce(&module).await?;
+        let result = NeuronLogic::execute(module).await;
+        if let (Err(_), Some(key)) = (&result, disbursement) {
+            PolicyLogic::release(key);
+        }
+        result
+    }
+
+    async fn execute(module: NeuronType) -> CanisterResult<ModuleResponse> {
         match module {
             NeuronType::Icp(module) => match module {
                 IcpNeuronArgs::Create(args) => {
@@ -309,75 +626,155 @@ impl NeuronLogic {
                     Ok(ModuleResponse::Neuron(Box::new(result)))
                 }
                 IcpNeuronArgs::TopUp(args) => {
-                    let result =
-                        NeuronLogic::top_up_neuron_by_subaccount(args.subaccount, args.amount_e8s)
-                            .await?;
+                    let result = NeuronLogic::top_up_neuron_by_subaccount(
+                        args.neuron.resolve()?,
+                        args.amount_e8s,
+                    )
+                    .await?;
                     Ok(ModuleResponse::Boolean(result))
                 }
                 IcpNeuronArgs::AddDissolveDelay(args) => {
                     let result = NeuronLogic::add_dissolve_delay(
-                        args.subaccount,
+                        args.neuron.resolve()?,
                         args.dissolve_delay_seconds,
                     )
                     .await?;
                     Ok(ModuleResponse::Boolean(result))
                 }
                 IcpNeuronArgs::SetDissolveState(args) => {
-                    let result =
-                        NeuronLogic::set_dissolve_state(args.subaccount, args.start_dissolving)
-                            .await?;
+                    let result = NeuronLogic::set_dissolve_state(
+                        args.neuron.resolve()?,
+                        args.start_dissolving,
+                    )
+                    .await?;
                     Ok(ModuleResponse::Boolean(result))
                 }
                 IcpNeuronArgs::AutoStake(args) => {
                     let result =
-                        NeuronLogic::auto_stake_maturity(args.subaccount, args.auto_stake).await?;
+                        NeuronLogic::auto_stake_maturity(args.neuron.resolve()?, args.auto_stake)
+                            .await?;
                     Ok(ModuleResponse::Boolean(result))
                 }
                 IcpNeuronArgs::Spawn(args) => {
                     let result =
-                        NeuronLogic::spawn_neuron(args.parent_subaccount, args.start_dissolving)
+                        NeuronLogic::spawn_neuron(args.parent.resolve()?, args.start_dissolving)
                             .await?;
                     Ok(ModuleResponse::Boolean(result))
                 }
                 IcpNeuronArgs::CreateProposal(args) => {
                     let result =
-                        NeuronLogic::create_proposal(args.subaccount, args.proposal).await?;
+                        NeuronLogic::create_proposal(args.neuron.resolve()?, args.proposal).await?;
                     Ok(ModuleResponse::MakeProposalResponse(Box::new(result)))
                 }
                 IcpNeuronArgs::Vote(args) => {
                     let result =
-                        NeuronLogic::vote(args.subaccount, args.proposal_id, args.vote).await?;
+                        NeuronLogic::vote(args.neuron.resolve()?, args.proposal_id, args.vote)
+                            .await?;
                     Ok(ModuleResponse::Boolean(result))
                 }
                 IcpNeuronArgs::Disburse(args) => {
-                    let _ = NeuronLogic::disburse(args.subaccount).await?;
+                    let _ = NeuronLogic::disburse(
+                        args.neuron.resolve()?,
+                        args.amount_e8s,
+                        args.destination,
+                    )
+                    .await?;
                     Ok(ModuleResponse::Boolean(true))
                 }
-                IcpNeuronArgs::SetFollowing(set_following_args) => {
-                    for arg in set_following_args.following {
-                        NeuronLogic::set_following(
-                            set_following_args.subaccount,
-                            arg.topic,
-                            arg.followees,
-                        )
-                        .await?;
-                    }
-                    Ok(ModuleResponse::Boolean(true))
+                IcpNeuronArgs::SetFollowing(args) => {
+                    let result =
+                        NeuronLogic::set_following(args.neuron.resolve()?, args.following).await?;
+                    Ok(ModuleResponse::Following(result))
                 }
                 IcpNeuronArgs::Command(args) => {
-                    let result = NeuronLogic::command_neuron(args.subaccount, args.command).await?;
+                    let result =
+                        NeuronLogic::command_neuron(args.neuron.resolve()?, args.command).await?;
                     Ok(ModuleResponse::ManageNeuronResponse(Box::new(result)))
                 }
+                IcpNeuronArgs::Split(args) => {
+                    let result = NeuronLogic::split_neuron(
+                        args.neuron.resolve()?,
+                        args.amount_e8s,
+                        args.memo,
+                    )
+                    .await?;
+                    Ok(ModuleResponse::Neuron(Box::new(result)))
+                }
+                IcpNeuronArgs::Merge(args) => {
+                    let result =
+                        NeuronLogic::merge_neurons(args.target.resolve()?, args.source.resolve()?)
+                            .await?;
+                    Ok(ModuleResponse::Neuron(Box::new(result)))
+                }
+                IcpNeuronArgs::StakeMaturity(args) => {
+                    let result =
+                        NeuronLogic::stake_maturity(args.neuron.resolve()?, args.percentage)
+                            .await?;
+                    Ok(ModuleResponse::Boolean(result))
+                }
+                IcpNeuronArgs::DisburseMaturity(args) => {
+                    let result = NeuronLogic::disburse_maturity(
+                        args.neuron.resolve()?,
+                        args.percentage,
+                        args.to_account,
+                    )
+                    .await?;
+                    Ok(ModuleResponse::Boolean(result))
+                }
+                IcpNeuronArgs::AddHotKey(args) => {
+                    let result =
+                        NeuronLogic::add_hot_key(args.neuron.resolve()?, args.hot_key).await?;
+                    Ok(ModuleResponse::Boolean(result))
+                }
+                IcpNeuronArgs::RemoveHotKey(args) => {
+                    let result =
+                        NeuronLogic::remove_hot_key(args.neuron.resolve()?, args.hot_key).await?;
+                    Ok(ModuleResponse::Boolean(result))
+                }
+                IcpNeuronArgs::Reconcile => {
+                    let result = ReconcileLogic::reconcile().await?;
+                    Ok(ModuleResponse::Reconcile(Box::new(result)))
+                }
             },
+            NeuronType::Sns(module) => SnsNeuronLogic::tk_service_manage_neuron(module).await,
         }
     }
 
+    /// Simulates the command on the neuron of the subaccount, see `NeuronReference::simulate`.
+    pub async fn simulate_command(
+        subaccount: [u8; 32],
+        command: ManageNeuronCommandRequest,
+    ) -> CanisterResult<Command1> {
+        let (_, neuron) = NeuronReferenceStore::get_by_subaccount(subaccount)?;
+        neuron.simulate(command).await
+    }
+
+    fn configure_command(operation: Operation) -> ManageNeuronCommandRequest {
+        ManageNeuronCommandRequest::Configure(Configure {
+            operation: Some(operation),
+        })
+    }
+
+    /// Configure, follow, split and merge commands are simulated during validation.
+    fn is_simulated(command: &ManageNeuronCommandRequest) -> bool {
+        matches!(
+            command,
+            ManageNeuronCommandRequest::Configure(_)
+                | ManageNeuronCommandRequest::Follow(_)
+                | ManageNeuronCommandRequest::SetFollowing(_)
+                | ManageNeuronCommandRequest::Split(_)
+                | ManageNeuronCommandRequest::Merge(_)
+        )
+    }
+
     pub async fn tk_service_validate_manage_neuron(args: NeuronType) -> CanisterResult<String> {
+        PolicyLogic::check(&args).await?;
+        let summary = ValidationSummary::new(&args);
         match args {
             NeuronType::Icp(args) => match args {
                 IcpNeuronArgs::Create(args) => {
-                    let balance = get_icp_balance(canister_self()).await?;
-                    if balance.e8s() < args.amount_e8s {
+                    let balance = NeuronLogic::get_icp_balance().await?;
+                    if balance < args.amount_e8s {
                         return Err(ApiError::bad_request("Insufficient balance"));
                     }
 
@@ -386,13 +783,21 @@ impl NeuronLogic {
                             "Amount must be greater than 1 ICP + fee",
                         ));
                     }
-                    Ok(serde_json::to_string(&args).unwrap())
+                    Ok(summary
+                        .change(
+                            "Canister balance",
+                            format_icp(balance),
+                            format_icp(
+                                balance.saturating_sub(args.amount_e8s + TRANSACTION_FEE_E8S),
+                            ),
+                        )
+                        .render())
                 }
                 IcpNeuronArgs::TopUp(args) => {
-                    NeuronReferenceStore::get_by_subaccount(args.subaccount)?;
-                    NeuronLogic::get_full_neuron(args.subaccount).await?;
-                    let balance = get_icp_balance(canister_self()).await?;
-                    if balance.e8s() < args.amount_e8s {
+                    let subaccount = args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+                    let balance = NeuronLogic::get_icp_balance().await?;
+                    if balance < args.amount_e8s {
                         return Err(ApiError::bad_request("Insufficient balance"));
                     }
 
@@ -401,55 +806,467 @@ impl NeuronLogic {
                             "Amount must be greater than 1 ICP + fee",
                         ));
                     }
-                    Ok(serde_json::to_string(&args).unwrap())
+
+                    let stake = ValidationSummary::stake(&neuron);
+                    Ok(summary
+                        .with_neuron(&neuron, &ValidationSummary::known_neurons().await)
+                        .change(
+                            "Stake",
+                            format_icp(stake),
+                            format_icp(stake + args.amount_e8s),
+                        )
+                        .change(
+                            "Canister balance",
+                            format_icp(balance),
+                            format_icp(
+                                balance.saturating_sub(args.amount_e8s + TRANSACTION_FEE_E8S),
+                            ),
+                        )
+                        .render())
                 }
                 IcpNeuronArgs::AddDissolveDelay(args) => {
-                    NeuronReferenceStore::get_by_subaccount(args.subaccount)?;
-                    NeuronLogic::get_full_neuron(args.subaccount).await?;
-                    Ok(serde_json::to_string(&args).unwrap())
+                    let subaccount = args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+
+                    let command = NeuronLogic::configure_command(Operation::IncreaseDissolveDelay(
+                        IncreaseDissolveDelay {
+                            additional_dissolve_delay_seconds: args.dissolve_delay_seconds as u32,
+                        },
+                    ));
+                    let simulation =
+                        NeuronLogic::simulate_command(subaccount, command.clone()).await?;
+
+                    let known_neurons = ValidationSummary::known_neurons().await;
+                    let delay = ValidationSummary::dissolve_delay_seconds(&neuron);
+                    Ok(summary
+                        .with_neuron(&neuron, &known_neurons)
+                        .change(
+                            "Dissolve delay",
+                            format_days(delay),
+                            format_days(
+                                (delay + args.dissolve_delay_seconds)
+                                    .min(MAX_DISSOLVE_DELAY_SECONDS),
+                            ),
+                        )
+                        .with_simulation(&simulation, &neuron, &command, &known_neurons)
+                        .render())
                 }
                 IcpNeuronArgs::SetDissolveState(args) => {
-                    NeuronReferenceStore::get_by_subaccount(args.subaccount)?;
-                    NeuronLogic::get_full_neuron(args.subaccount).await?;
-                    Ok(serde_json::to_string(&args).unwrap())
+                    let subaccount = args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+
+                    let command = NeuronLogic::configure_command(if args.start_dissolving {
+                        Operation::StartDissolving {}
+                    } else {
+                        Operation::StopDissolving {}
+                    });
+                    let simulation =
+                        NeuronLogic::simulate_command(subaccount, command.clone()).await?;
+
+                    let known_neurons = ValidationSummary::known_neurons().await;
+                    let delay = format_days(ValidationSummary::dissolve_delay_seconds(&neuron));
+                    let after = if args.start_dissolving {
+                        format!("Dissolving, {} left", delay)
+                    } else {
+                        format!("Not dissolving, {} delay", delay)
+                    };
+                    Ok(summary
+                        .with_neuron(&neuron, &known_neurons)
+                        .change(
+                            "Dissolve state",
+                            ValidationSummary::dissolve_state(&neuron),
+                            after,
+                        )
+                        .with_simulation(&simulation, &neuron, &command, &known_neurons)
+                        .render())
                 }
                 IcpNeuronArgs::AutoStake(args) => {
-                    NeuronReferenceStore::get_by_subaccount(args.subaccount)?;
-                    NeuronLogic::get_full_neuron(args.subaccount).await?;
-                    Ok(serde_json::to_string(&args).unwrap())
+                    let subaccount = args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+                    let command = NeuronLogic::configure_command(
+                        Operation::ChangeAutoStakeMaturity(ChangeAutoStakeMaturity {
+                            requested_setting_for_auto_stake_maturity: args.auto_stake,
+                        }),
+                    );
+                    let simulation =
+                        NeuronLogic::simulate_command(subaccount, command.clone()).await?;
+
+                    let known_neurons = ValidationSummary::known_neurons().await;
+                    Ok(summary
+                        .with_neuron(&neuron, &known_neurons)
+                        .change(
+                            "Auto stake maturity",
+                            ValidationSummary::yes_no(
+                                neuron.auto_stake_maturity.unwrap_or_default(),
+                            ),
+                            ValidationSummary::yes_no(args.auto_stake),
+                        )
+                        .with_simulation(&simulation, &neuron, &command, &known_neurons)
+                        .render())
                 }
                 IcpNeuronArgs::Spawn(args) => {
-                    NeuronReferenceStore::get_by_subaccount(args.parent_subaccount)?;
-                    let neuron = NeuronLogic::get_full_neuron(args.parent_subaccount).await?;
+                    let parent_subaccount = args.parent.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(parent_subaccount).await?;
                     if neuron.maturity_e8s_equivalent < 100000000 {
                         return Err(ApiError::bad_request(
                             "neuron must have at least 1 ICP in maturity to spawn",
                         ));
                     }
-                    Ok(serde_json::to_string(&args).unwrap())
+                    Ok(summary
+                        .with_neuron(&neuron, &ValidationSummary::known_neurons().await)
+                        .change(
+                            "Maturity",
+                            format_icp(neuron.maturity_e8s_equivalent),
+                            format_icp(0),
+                        )
+                        .render())
                 }
                 IcpNeuronArgs::CreateProposal(create_proposal_args) => {
-                    NeuronReferenceStore::get_by_subaccount(create_proposal_args.subaccount)?;
-                    NeuronLogic::get_full_neuron(create_proposal_args.subaccount).await?;
-                    Ok(serde_json::to_string(&create_proposal_args).unwrap())
+                    let subaccount = create_proposal_args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+                    Ok(summary
+                        .with_neuron(&neuron, &ValidationSummary::known_neurons().await)
+                        .render())
                 }
                 IcpNeuronArgs::Vote(vote_args) => {
-                    NeuronReferenceStore::get_by_subaccount(vote_args.subaccount)?;
-                    NeuronLogic::get_full_neuron(vote_args.subaccount).await?;
-                    Ok(serde_json::to_string(&vote_args).unwrap())
+                    let subaccount = vote_args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+                    Ok(summary
+                        .with_neuron(&neuron, &ValidationSummary::known_neurons().await)
+                        .render())
                 }
                 IcpNeuronArgs::Disburse(disburse_args) => {
-                    NeuronReferenceStore::get_by_subaccount(disburse_args.subaccount)?;
-                    NeuronLogic::get_full_neuron(disburse_args.subaccount).await?;
-                    Ok(serde_json::to_string(&disburse_args).unwrap())
+                    let subaccount = disburse_args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+
+                    let now_seconds = time() / 1_000_000_000;
+                    let is_dissolved = match neuron.dissolve_state {
+                        Some(DissolveState::WhenDissolvedTimestampSeconds(timestamp)) => {
+                            timestamp <= now_seconds
+                        }
+                        Some(DissolveState::DissolveDelaySeconds(seconds)) => seconds == 0,
+                        None => true,
+                    };
+                    if !is_dissolved {
+                        return Err(ApiError::bad_request("Neuron is not fully dissolved"));
+                    }
+
+                    let stake = ValidationSummary::stake(&neuron);
+                    if let Some(amount_e8s) = disburse_args.amount_e8s {
+                        if amount_e8s <= TRANSACTION_FEE_E8S || amount_e8s > stake {
+                            return Err(ApiError::bad_request(&format!(
+                                "Amount must be between {} and {} e8s",
+                                TRANSACTION_FEE_E8S + 1,
+                                stake
+                            )));
+                        }
+                    }
+
+                    let destination = NeuronLogic::resolve_disburse_destination(
+                        disburse_args.destination.clone(),
+                    )?;
+                    let amount_e8s = disburse_args.amount_e8s.unwrap_or(stake);
+                    Ok(summary
+                        .with_neuron(&neuron, &ValidationSummary::known_neurons().await)
+                        .row("Destination", destination.to_hex())
+                        .change("Stake", format_icp(stake), format_icp(stake - amount_e8s))
+                        .render())
                 }
                 IcpNeuronArgs::SetFollowing(set_following_args) => {
-                    NeuronReferenceStore::get_by_subaccount(set_following_args.subaccount)?;
-                    NeuronLogic::get_full_neuron(set_following_args.subaccount).await?;
-                    Ok(serde_json::to_string(&set_following_args).unwrap())
+                    let subaccount = set_following_args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+                    let neuron_id = neuron.id.as_ref().map(|id| id.id);
+
+                    let mut topics = HashSet::new();
+                    let mut followees = HashSet::new();
+                    for arg in &set_following_args.following {
+                        if !topics.insert(i32::from(arg.topic.clone())) {
+                            return Err(ApiError::bad_request(&format!(
+                                "Topic {:?} is specified more than once",
+                                arg.topic
+                            )));
+                        }
+                        if arg.followees.len() > MAX_FOLLOWEES_PER_TOPIC {
+                            return Err(ApiError::bad_request(&format!(
+                                "A topic can have at most {} followees",
+                                MAX_FOLLOWEES_PER_TOPIC
+                            )));
+                        }
+                        if arg.followees.iter().any(|id| Some(*id) == neuron_id) {
+                            return Err(ApiError::bad_request("Neuron can not follow itself"));
+                        }
+                        followees.extend(arg.followees.iter().copied());
+                    }
+
+                    for followee in followees {
+                        NeuronLogic::get_neuron_info(followee).await?;
+                    }
+
+                    let command = NeuronReference::set_following_command(
+                        set_following_args.following.clone(),
+                    );
+                    let simulation =
+                        NeuronLogic::simulate_command(subaccount, command.clone()).await?;
+
+                    let known_neurons = ValidationSummary::known_neurons().await;
+                    let mut summary = summary.with_neuron(&neuron, &known_neurons);
+                    for arg in &set_following_args.following {
+                        let topic = i32::from(arg.topic.clone());
+                        summary = summary.change(
+                            &format!("Followees {}", ValidationSummary::topic_name(topic)),
+                            ValidationSummary::topic_followees(&neuron, topic, &known_neurons),
+                            ValidationSummary::neuron_names(
+                                arg.followees.iter().copied(),
+                                &known_neurons,
+                            ),
+                        );
+                    }
+                    Ok(summary
+                        .with_simulation(&simulation, &neuron, &command, &known_neurons)
+                        .render())
+                }
+                IcpNeuronArgs::Command(args) => {
+                    let subaccount = args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+                    let known_neurons = ValidationSummary::known_neurons().await;
+                    let summary = summary.with_neuron(&neuron, &known_neurons);
+
+                    if NeuronLogic::is_simulated(&args.command) {
+                        let simulation =
+                            NeuronLogic::simulate_command(subaccount, args.command.clone()).await?;
+                        Ok(summary
+                            .with_simulation(&simulation, &neuron, &args.command, &known_neurons)
+                            .render())
+                    } else {
+                        Ok(summary.render())
+                    }
+                }
+                IcpNeuronArgs::Split(args) => {
+                    let subaccount = args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+
+                    if args.amount_e8s < MINIMUM_STAKE_E8S + TRANSACTION_FEE_E8S {
+                        return Err(ApiError::bad_request(
+                            "Amount must be greater than 1 ICP + fee",
+                        ));
+                    }
+
+                    let stake = ValidationSummary::stake(&neuron);
+                    if stake.saturating_sub(args.amount_e8s) < MINIMUM_STAKE_E8S {
+                        return Err(ApiError::bad_request(
+                            "Remaining stake of the neuron must be at least 1 ICP",
+                        ));
+                    }
+
+                    let command = ManageNeuronCommandRequest::Split(Split {
+                        amount_e8s: args.amount_e8s,
+                        memo: Some(args.memo.unwrap_or_else(NeuronReferenceStore::peek_nonce)),
+                    });
+                    let simulation =
+                        NeuronLogic::simulate_command(subaccount, command.clone()).await?;
+
+                    let known_neurons = ValidationSummary::known_neurons().await;
+                    Ok(summary
+                        .with_neuron(&neuron, &known_neurons)
+                        .change(
+                            "Stake",
+                            format_icp(stake),
+                            format_icp(stake - args.amount_e8s),
+                        )
+                        .with_simulation(&simulation, &neuron, &command, &known_neurons)
+                        .render())
+                }
+                IcpNeuronArgs::Merge(args) => {
+                    let target_subaccount = args.target.resolve()?;
+                    let source_subaccount = args.source.resolve()?;
+                    if target_subaccount == source_subaccount {
+                        return Err(ApiError::bad_request("Can not merge a neuron into itself"));
+                    }
+
+                    let target = NeuronLogic::get_full_neuron(target_subaccount).await?;
+                    let source = NeuronLogic::get_full_neuron(source_subaccount).await?;
+
+                    for neuron in [&target, &source] {
+                        if neuron.controller != Some(canister_self()) {
+                            return Err(ApiError::bad_request(
+                                "Both neurons must be controlled by this canister",
+                            ));
+                        }
+                        if neuron.spawn_at_timestamp_seconds.is_some() {
+                            return Err(ApiError::bad_request("Can not merge a spawning neuron"));
+                        }
+                        if let Some(DissolveState::WhenDissolvedTimestampSeconds(_)) =
+                            neuron.dissolve_state
+                        {
+                            return Err(ApiError::bad_request(
+                                "Both neurons must be non-dissolving",
+                            ));
+                        }
+                    }
+
+                    let command = ManageNeuronCommandRequest::Merge(Merge {
+                        source_neuron_id: source.id.clone(),
+                    });
+                    let simulation =
+                        NeuronLogic::simulate_command(target_subaccount, command.clone()).await?;
+
+                    let known_neurons = ValidationSummary::known_neurons().await;
+                    let target_stake = ValidationSummary::stake(&target);
+                    let source_stake = ValidationSummary::stake(&source);
+                    Ok(summary
+                        .with_neuron(&target, &known_neurons)
+                        .change(
+                            "Target stake",
+                            format_icp(target_stake),
+                            format_icp(
+                                (target_stake + source_stake).saturating_sub(TRANSACTION_FEE_E8S),
+                            ),
+                        )
+                        .change("Source stake", format_icp(source_stake), format_icp(0))
+                        .with_simulation(&simulation, &target, &command, &known_neurons)
+                        .render())
+                }
+                IcpNeuronArgs::StakeMaturity(args) => {
+                    let subaccount = args.neuron.resolve()?;
+                    NeuronLogic::validate_percentage(args.percentage)?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+                    if neuron.maturity_e8s_equivalent == 0 {
+                        return Err(ApiError::bad_request("Neuron has no maturity to stake"));
+                    }
+
+                    let amount_e8s = neuron.maturity_e8s_equivalent * args.percentage as u64 / 100;
+                    let staked_maturity = neuron.staked_maturity_e8s_equivalent.unwrap_or_default();
+                    Ok(summary
+                        .with_neuron(&neuron, &ValidationSummary::known_neurons().await)
+                        .change(
+                            "Maturity",
+                            format_icp(neuron.maturity_e8s_equivalent),
+                            format_icp(neuron.maturity_e8s_equivalent - amount_e8s),
+                        )
+                        .change(
+                            "Staked maturity",
+                            format_icp(staked_maturity),
+                            format_icp(staked_maturity + amount_e8s),
+                        )
+                        .render())
+                }
+                IcpNeuronArgs::DisburseMaturity(args) => {
+                    let subaccount = args.neuron.resolve()?;
+                    NeuronLogic::validate_percentage(args.percentage)?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+
+                    let amount_e8s = neuron.maturity_e8s_equivalent * args.percentage as u64 / 100;
+                    if amount_e8s < MINIMUM_MATURITY_DISBURSEMENT_E8S {
+                        return Err(ApiError::bad_request(
+                            "Maturity to disburse must be at least 1 ICP",
+                        ));
+                    }
+
+                    let disbursements_in_progress = neuron
+                        .maturity_disbursements_in_progress
+                        .as_ref()
+                        .map(|disbursements| disbursements.len())
+                        .unwrap_or_default();
+                    if disbursements_in_progress >= MAX_MATURITY_DISBURSEMENTS_IN_PROGRESS {
+                        return Err(ApiError::bad_request(&format!(
+                            "Neuron already has {} maturity disbursements in progress",
+                            disbursements_in_progress
+                        )));
+                    }
+                    Ok(summary
+                        .with_neuron(&neuron, &ValidationSummary::known_neurons().await)
+                        .change(
+                            "Maturity",
+                            format_icp(neuron.maturity_e8s_equivalent),
+                            format_icp(neuron.maturity_e8s_equivalent - amount_e8s),
+                        )
+                        .render())
+                }
+                IcpNeuronArgs::AddHotKey(args) => {
+                    let subaccount = args.neuron.resolve()?;
+                    if args.hot_key == Principal::anonymous() || args.hot_key == canister_self() {
+                        return Err(ApiError::bad_request("Invalid hot key"));
+                    }
+
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+                    if neuron.hot_keys.contains(&args.hot_key) {
+                        return Err(ApiError::bad_request("Hot key already added"));
+                    }
+                    if neuron.hot_keys.len() >= MAX_HOT_KEYS {
+                        return Err(ApiError::bad_request(&format!(
+                            "Neuron can have at most {} hot keys",
+                            MAX_HOT_KEYS
+                        )));
+                    }
+
+                    let command = NeuronLogic::configure_command(Operation::AddHotKey(AddHotKey {
+                        new_hot_key: Some(args.hot_key),
+                    }));
+                    let simulation =
+                        NeuronLogic::simulate_command(subaccount, command.clone()).await?;
+
+                    let known_neurons = ValidationSummary::known_neurons().await;
+                    let mut after = neuron.clone();
+                    after.hot_keys.push(args.hot_key);
+                    Ok(summary
+                        .with_neuron(&neuron, &known_neurons)
+                        .change(
+                            "Hot keys",
+                            ValidationSummary::hot_keys(&neuron),
+                            ValidationSummary::hot_keys(&after),
+                        )
+                        .with_simulation(&simulation, &neuron, &command, &known_neurons)
+                        .render())
+                }
+                IcpNeuronArgs::RemoveHotKey(args) => {
+                    let subaccount = args.neuron.resolve()?;
+                    let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+                    if !neuron.hot_keys.contains(&args.hot_key) {
+                        return Err(ApiError::bad_request("Hot key not found"));
+                    }
+
+                    let command =
+                        NeuronLogic::configure_command(Operation::RemoveHotKey(RemoveHotKey {
+                            hot_key_to_remove: Some(args.hot_key),
+                        }));
+                    let simulation =
+                        NeuronLogic::simulate_command(subaccount, command.clone()).await?;
+
+                    let known_neurons = ValidationSummary::known_neurons().await;
+                    let mut after = neuron.clone();
+                    after.hot_keys.retain(|hot_key| hot_key != &args.hot_key);
+                    Ok(summary
+                        .with_neuron(&neuron, &known_neurons)
+                        .change(
+                            "Hot keys",
+                            ValidationSummary::hot_keys(&neuron),
+                            ValidationSummary::hot_keys(&after),
+                        )
+                        .with_simulation(&simulation, &neuron, &command, &known_neurons)
+                        .render())
+                }
+                IcpNeuronArgs::Reconcile => {
+                    let report = ReconcileLogic::get_report().await?;
+                    let list = |ids: Vec<u64>| {
+                        ValidationSummary::neuron_names(ids.into_iter(), &HashMap::new())
+                    };
+                    Ok(summary
+                        .row("Matched neurons", list(report.matched))
+                        .row("Unknown neurons", list(report.unknown))
+                        .row(
+                            "Missing neurons",
+                            list(
+                                report
+                                    .missing
+                                    .iter()
+                                    .filter_map(|reference| reference.neuron_id)
+                                    .collect(),
+                            ),
+                        )
+                        .render())
                 }
-                IcpNeuronArgs::Command(args) => Ok(serde_json::to_string(&args).unwrap()),
             },
+            NeuronType::Sns(args) => SnsNeuronLogic::tk_service_validate_manage_neuron(args).await,
         }
     }
 }
@@ -0,0 +1,406 @@
+use candid::Principal;
+use ic_cdk::api::{canister_self, time};
+use ic_ledger_types::{AccountIdentifier, Subaccount, DEFAULT_SUBACCOUNT};
+use toolkit_utils::{
+    api_error::ApiError,
+    cell::CellStorage,
+    result::CanisterResult,
+    storage::{StorageInsertable, StorageUpdateable},
+};
+
+use crate::{
+    api::{
+        icp_governance_api::{ManageNeuronCommandRequest, Operation},
+        sns_governance_api::DissolveState as SnsDissolveState,
+    },
+    helpers::format_helper::{format_days, format_icp},
+    logic::{
+        config_logic::ConfigLogic, consent_logic::ConsentLogic, log_logic::LogLogic,
+        neuron_logic::NeuronLogic, sns_neuron_logic::SnsNeuronLogic,
+        validation_summary_logic::ValidationSummary,
+    },
+    storage::policy_storage::{policy_store, DisbursementStore},
+    types::{
+        modules::{IcpNeuronArgs, NeuronType, SnsNeuronArgs},
+        policy::{Disbursement, DisbursementCap, Policy},
+    },
+};
+
+const COMMAND_NAMES: [&str; 15] = [
+    "Spawn",
+    "Split",
+    "Follow",
+    "DisburseMaturity",
+    "RefreshVotingPower",
+    "ClaimOrRefresh",
+    "Configure",
+    "RegisterVote",
+    "Merge",
+    "DisburseToNeuron",
+    "SetFollowing",
+    "MakeProposal",
+    "StakeMaturity",
+    "MergeMaturity",
+    "Disburse",
+];
+
+/// Ten years, long enough for any cap and short enough to convert to nanoseconds.
+const MAX_CAP_PERIOD_SECONDS: u64 = 10 * 365 * 24 * 60 * 60;
+
+pub struct PolicyLogic;
+
+impl PolicyLogic {
+    pub fn get_policy() -> Policy {
+        policy_store().get().unwrap_or_default()
+    }
+
+    pub fn set_policy(policy: Policy) -> CanisterResult<Policy> {
+        Self::validate_policy(&policy)?;
+        let policy = policy_store().set(policy)?;
+        LogLogic::info("set_policy", None, "Policy updated");
+        Ok(policy)
+    }
+
+    pub fn validate_set_policy(policy: Policy) -> CanisterResult<String> {
+        Self::validate_policy(&policy)?;
+        Ok(serde_json::to_string(&policy).unwrap())
+    }
+
+    /// Checks the args against the policy without counting a disbursement against the cap.
+    pub async fn check(args: &NeuronType) -> CanisterResult<()> {
+        Self::evaluate(args).await.map(|_| ())
+    }
+
+    /// Checks the args against the policy and counts the disbursement against the cap. The
+    /// returned key must be released when the operation fails.
+    pub async fn enforce(args: &NeuronType) -> CanisterResult<Option<u64>> {
+        match Self::evaluate(args).await? {
+            Some(amount_e8s) => {
+                Self::prune_disbursements();
+                let ledger = match args {
+                    NeuronType::Icp(_) => None,
+                    NeuronType::Sns(_) => Some(ConfigLogic::get_config()?.sns_ledger_canister_id),
+                };
+                let (key, _) = DisbursementStore::insert(Disbursement {
+                    timestamp: time(),
+                    amount_e8s,
+                    neuron_key: args.neuron_key(),
+                    ledger,
+                })?;
+                Ok(Some(key))
+            }
+            None => Ok(None),
+        }
+    }
+
+    pub fn release(key: u64) {
+        let _ = DisbursementStore::remove(key);
+    }
+
+    /// Disbursements older than the longest cap period no longer count against a cap.
+    fn prune_disbursements() {
+        let policy = Self::get_policy();
+        let period_seconds = [&policy.disbursement_cap, &policy.sns_disbursement_cap]
+            .into_iter()
+            .flatten()
+            .map(|cap| cap.period_seconds)
+            .max()
+            .unwrap_or_default();
+
+        DisbursementStore::remove_before(Self::period_start(period_seconds));
+    }
+
+    /// Returns the amount that counts against the disbursement cap.
+    async fn evaluate(args: &NeuronType) -> CanisterResult<Option<u64>> {
+        let policy = Self::get_policy();
+
+        match args {
+            NeuronType::Icp(args) => match args {
+                IcpNeuronArgs::Create(args) => {
+                    Self::check_dissolve_delay(&policy, args.dissolve_delay_seconds)?;
+                    Ok(None)
+                }
+                IcpNeuronArgs::SetDissolveState(args) if args.start_dissolving => {
+                    Self::check_icp_start_dissolving(&policy, args.neuron.resolve()?).await?;
+                    Ok(None)
+                }
+                // The spawned neuron has no dissolve delay of its own yet
+                IcpNeuronArgs::Spawn(args) if args.start_dissolving => {
+                    Self::check_start_dissolving(&policy, 0)?;
+                    Ok(None)
+                }
+                IcpNeuronArgs::Disburse(args) => {
+                    let destination =
+                        NeuronLogic::resolve_disburse_destination(args.destination.clone())?;
+                    Self::check_destination(&policy, destination)?;
+
+                    let amount_e8s = match args.amount_e8s {
+                        Some(amount_e8s) => amount_e8s,
+                        None => {
+                            let neuron =
+                                NeuronLogic::get_full_neuron(args.neuron.resolve()?).await?;
+                            neuron
+                                .cached_neuron_stake_e8s
+                                .saturating_sub(neuron.neuron_fees_e8s)
+                        }
+                    };
+                    Self::check_disbursement_cap(
+                        policy.disbursement_cap.as_ref(),
+                        None,
+                        amount_e8s,
+                    )?;
+                    Ok(Some(amount_e8s))
+                }
+                IcpNeuronArgs::DisburseMaturity(args) => {
+                    let destination = match &args.to_account {
+                        Some(account) => AccountIdentifier::new(
+                            &account.owner,
+                            &Subaccount(account.subaccount.unwrap_or(DEFAULT_SUBACCOUNT.0)),
+                        ),
+                        None => AccountIdentifier::new(&canister_self(), &DEFAULT_SUBACCOUNT),
+                    };
+                    Self::check_destination(&policy, destination)?;
+
+                    let neuron = NeuronLogic::get_full_neuron(args.neuron.resolve()?).await?;
+                    let amount_e8s = neuron.maturity_e8s_equivalent * args.percentage as u64 / 100;
+                    Self::check_disbursement_cap(
+                        policy.disbursement_cap.as_ref(),
+                        None,
+                        amount_e8s,
+                    )?;
+                    Ok(Some(amount_e8s))
+                }
+                IcpNeuronArgs::Command(args) => {
+                    Self::check_command(&policy, &args.command)?;
+                    if let ManageNeuronCommandRequest::Configure(configure) = &args.command {
+                        if let Some(Operation::StartDissolving {}) = configure.operation {
+                            Self::check_icp_start_dissolving(&policy, args.neuron.resolve()?)
+                                .await?;
+                        }
+                    }
+                    Ok(None)
+                }
+                _ => Ok(None),
+            },
+            NeuronType::Sns(args) => match args {
+                SnsNeuronArgs::Create(args) => {
+                    Self::check_dissolve_delay(&policy, args.dissolve_delay_seconds)?;
+                    Ok(None)
+                }
+                SnsNeuronArgs::SetDissolveState(args) if args.start_dissolving => {
+                    if policy.dissolve_delay_floor_seconds.is_some() {
+                        let neuron = SnsNeuronLogic::get_full_neuron(args.subaccount).await?;
+                        let dissolve_delay_seconds = match neuron.dissolve_state {
+                            Some(SnsDissolveState::DissolveDelaySeconds(seconds)) => seconds,
+                            Some(SnsDissolveState::WhenDissolvedTimestampSeconds(timestamp)) => {
+                                timestamp.saturating_sub(time() / 1_000_000_000)
+                            }
+                            None => 0,
+                        };
+                        Self::check_start_dissolving(&policy, dissolve_delay_seconds)?;
+                    }
+                    Ok(None)
+                }
+                // SNS neurons are always disbursed to the default account of this canister
+                SnsNeuronArgs::Disburse(args) => {
+                    Self::check_destination(
+                        &policy,
+                        AccountIdentifier::new(&canister_self(), &DEFAULT_SUBACCOUNT),
+                    )?;
+
+                    let neuron = SnsNeuronLogic::get_full_neuron(args.subaccount).await?;
+                    let amount_e8s = neuron
+                        .cached_neuron_stake_e8s
+                        .saturating_sub(neuron.neuron_fees_e8s);
+                    let ledger = ConfigLogic::get_config()?.sns_ledger_canister_id;
+                    Self::check_disbursement_cap(
+                        policy.sns_disbursement_cap.as_ref(),
+                        Some(ledger),
+                        amount_e8s,
+                    )?;
+                    Ok(Some(amount_e8s))
+                }
+                _ => Ok(None),
+            },
+        }
+    }
+
+    fn check_dissolve_delay(
+        policy: &Policy,
+        dissolve_delay_seconds: Option<u64>,
+    ) -> CanisterResult<()> {
+        if let Some(floor) = policy.dissolve_delay_floor_seconds {
+            if dissolve_delay_seconds.unwrap_or_default() < floor {
+                return Err(Self::violation(&format!(
+                    "the dissolve delay must be at least {}",
+                    format_days(floor)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    async fn check_icp_start_dissolving(
+        policy: &Policy,
+        subaccount: [u8; 32],
+    ) -> CanisterResult<()> {
+        if policy.dissolve_delay_floor_seconds.is_none() {
+            return Ok(());
+        }
+
+        let neuron = NeuronLogic::get_full_neuron(subaccount).await?;
+        Self::check_start_dissolving(policy, ValidationSummary::dissolve_delay_seconds(&neuron))
+    }
+
+    /// A neuron that starts dissolving is locked for its remaining dissolve delay, which has to
+    /// be at least the floor.
+    fn check_start_dissolving(policy: &Policy, dissolve_delay_seconds: u64) -> CanisterResult<()> {
+        if let Some(floor) = policy.dissolve_delay_floor_seconds {
+            if dissolve_delay_seconds < floor {
+                return Err(Self::violation(&format!(
+                    "neurons can only start dissolving with a dissolve delay of at least {}",
+                    format_days(floor)
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    fn check_destination(policy: &Policy, destination: AccountIdentifier) -> CanisterResult<()> {
+        let Some(allowed_destinations) = &policy.allowed_destinations else {
+            return Ok(());
+        };
+
+        let internal_accounts = [
+            AccountIdentifier::new(&canister_self(), &DEFAULT_SUBACCOUNT),
+            AccountIdentifier::new(
+                &ConfigLogic::get_config()?.governance_canister_id,
+                &DEFAULT_SUBACCOUNT,
+            ),
+        ];
+        let destination = destination.to_hex();
+
+        if internal_accounts
+            .iter()
+            .any(|account| account.to_hex() == destination)
+            || allowed_destinations
+                .iter()
+                .any(|allowed| allowed.eq_ignore_ascii_case(&destination))
+        {
+            return Ok(());
+        }
+
+        Err(Self::violation(&format!(
+            "account {} is not an allowed destination",
+            destination
+        )))
+    }
+
+    fn check_disbursement_cap(
+        cap: Option<&DisbursementCap>,
+        ledger: Option<Principal>,
+        amount_e8s: u64,
+    ) -> CanisterResult<()> {
+        let Some(cap) = cap else {
+            return Ok(());
+        };
+
+        let disbursed_e8s =
+            DisbursementStore::total_since(Self::period_start(cap.period_seconds), ledger);
+        if disbursed_e8s.saturating_add(amount_e8s) > cap.amount_e8s {
+            return Err(Self::violation(&format!(
+                "disbursing {} exceeds the cap of {} per {}, {} is already disbursed",
+                format_icp(amount_e8s),
+                format_icp(cap.amount_e8s),
+                format_days(cap.period_seconds),
+                format_icp(disbursed_e8s)
+            )));
+        }
+        Ok(())
+    }
+
+    fn period_start(period_seconds: u64) -> u64 {
+        time().saturating_sub(period_seconds.saturating_mul(1_000_000_000))
+    }
+
+    /// Raw commands can not be used to get around the other restrictions.
+    fn check_command(policy: &Policy, command: &ManageNeuronCommandRequest) -> CanisterResult<()> {
+        let name = ConsentLogic::command_name(command);
+        if policy.denied_commands.iter().any(|denied| denied == name) {
+            return Err(Self::violation(&format!(
+                "the {} command is not allowed",
+                name
+            )));
+        }
+
+        let restricted = policy.disbursement_cap.is_some() || policy.allowed_destinations.is_some();
+
+        match command {
+            // Spawning to another controller moves the maturity out of this canister
+            ManageNeuronCommandRequest::Spawn(spawn)
+                if restricted
+                    && spawn
+                        .new_controller
+                        .is_some_and(|controller| controller != canister_self()) =>
+            {
+                Err(Self::violation(
+                    "maturity can not be spawned to another controller while disbursements are restricted",
+                ))
+            }
+            ManageNeuronCommandRequest::Disburse(_)
+            | ManageNeuronCommandRequest::DisburseMaturity(_)
+            | ManageNeuronCommandRequest::DisburseToNeuron(_)
+                if restricted =>
+            {
+                Err(Self::violation(&format!(
+                    "the {} command can not be used while disbursements are restricted",
+                    name
+                )))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn validate_policy(policy: &Policy) -> CanisterResult<()> {
+        for cap in [&policy.disbursement_cap, &policy.sns_disbursement_cap]
+            .into_iter()
+            .flatten()
+        {
+            if cap.period_seconds == 0 || cap.period_seconds > MAX_CAP_PERIOD_SECONDS {
+                return Err(ApiError::bad_request(&format!(
+                    "Disbursement cap period must be between 1 second and {}",
+                    format_days(MAX_CAP_PERIOD_SECONDS)
+                )));
+            }
+        }
+
+        if let Some(allowed_destinations) = &policy.allowed_destinations {
+            if let Some(invalid) = allowed_destinations
+                .iter()
+                .find(|destination| AccountIdentifier::from_hex(destination).is_err())
+            {
+                return Err(ApiError::bad_request(&format!(
+                    "Invalid account identifier: {}",
+                    invalid
+                )));
+            }
+        }
+
+        if let Some(unknown) = policy
+            .denied_commands
+            .iter()
+            .find(|name| !COMMAND_NAMES.contains(&name.as_str()))
+        {
+            return Err(ApiError::bad_request(&format!(
+                "Unknown command: {}",
+                unknown
+            )));
+        }
+
+        Ok(())
+    }
+
+    fn violation(message: &str) -> ApiError {
+        ApiError::bad_request(&format!("Policy violation: {}", message))
+    }
+}
@@ -0,0 +1,216 @@
+use std::time::Duration;
+
+use candid::Principal;
+use ic_cdk::api::time;
+use toolkit_utils::{
+    api_error::ApiError,
+    result::CanisterResult,
+    storage::{StorageInsertable, StorageQueryable, StorageUpdateable},
+};
+
+use crate::{
+    helpers::format_helper::format_days,
+    logic::{
+        audit_logic::AuditLogic, block_logic::BlockLogic, consent_logic::ConsentLogic,
+        log_logic::LogLogic, neuron_logic::NeuronLogic, policy_logic::PolicyLogic,
+    },
+    storage::queued_operation_storage::QueuedOperationStore,
+    timers::storages::OperationTimers,
+    traits::timer_traits::TimerActions,
+    types::{
+        audit_record::ProposalMatch,
+        modules::{ModuleResponse, NeuronType},
+        queued_operation::{QueuedOperation, QueuedOperationResponse, QueuedOperationStatus},
+    },
+};
+
+/// Holds high-risk operations for the execution delay of the policy, which gives the community
+/// time to cancel an operation before it is executed.
+pub struct QueueLogic;
+
+impl QueueLogic {
+    pub fn get_queued_operations() -> Vec<QueuedOperationResponse> {
+        QueuedOperationStore::get_all()
+            .into_iter()
+            .map(|(id, operation)| operation.to_response(id))
+            .collect()
+    }
+
+    /// The delay in seconds before the args are executed, `None` when they execute right away.
+    pub fn get_delay(args: &NeuronType) -> Option<u64> {
+        PolicyLogic::get_policy()
+            .execution_delay_seconds
+            .filter(|delay| *delay > 0 && args.is_high_risk())
+    }
+
+    pub async fn enqueue(
+        caller: Principal,
+        args: NeuronType,
+        proposal_match: Option<ProposalMatch>,
+        delay_seconds: u64,
+    ) -> CanisterResult<ModuleResponse> {
+        PolicyLogic::check(&args).await?;
+
+        let (id, operation) = QueuedOperationStore::insert(QueuedOperation {
+            args,
+            caller,
+            proposal_id: proposal_match.as_ref().and_then(ProposalMatch::proposal_id),
+            status: QueuedOperationStatus::Queued,
+            queued_at: time(),
+            execute_at: time() + delay_seconds * 1_000_000_000,
+            updated_at: time(),
+            result: None,
+            proposal_match,
+        })?;
+        Self::schedule(id, &operation);
+
+        LogLogic::info(
+            operation.args.operation(),
+            operation.args.neuron_key(),
+            format!(
+                "Queued operation {} for execution in {}",
+                id,
+                format_days(delay_seconds)
+            ),
+        );
+        Ok(ModuleResponse::Queued(id))
+    }
+
+    pub fn cancel(id: u64) -> CanisterResult<QueuedOperationResponse> {
+        let (_, mut operation) = Self::get_queued(id)?;
+        OperationTimers::clear(&id);
+
+        operation.status = QueuedOperationStatus::Cancelled;
+        operation.updated_at = time();
+        let (id, operation) = QueuedOperationStore::update(id, operation)?;
+
+        LogLogic::info(
+            operation.args.operation(),
+            operation.args.neuron_key(),
+            format!("Cancelled queued operation {}", id),
+        );
+        Ok(operation.to_response(id))
+    }
+
+    pub fn validate_cancel(id: u64) -> CanisterResult<String> {
+        let (id, operation) = Self::get_queued(id)?;
+        Ok(format!(
+            "## Cancel queued operation\n\nCancel queued operation **{}**, which executes in {}.\n\n{}",
+            id,
+            format_days(operation.execute_at.saturating_sub(time()) / 1_000_000_000),
+            ConsentLogic::describe(&operation.args)
+        ))
+    }
+
+    /// Adds the time lock to the validation output of the args.
+    pub fn annotate(args: &NeuronType, summary: String) -> String {
+        match Self::get_delay(args) {
+            Some(delay) => format!(
+                "{}\n\n### Time lock\n\nThis operation is queued and executes {} after the proposal, it can be cancelled until then.",
+                summary,
+                format_days(delay)
+            ),
+            None => summary,
+        }
+    }
+
+    /// An upgrade drops the calls that were in flight, an operation that was still executing
+    /// is marked as interrupted instead of staying executing forever.
+    pub fn interrupt_executing() {
+        for (id, mut operation) in QueuedOperationStore::get_all() {
+            if operation.status != QueuedOperationStatus::Executing {
+                continue;
+            }
+
+            operation.status = QueuedOperationStatus::Interrupted;
+            operation.updated_at = time();
+            operation.result = Some(Err(
+                "Execution was interrupted, check the neuron before queueing the operation again"
+                    .to_string(),
+            ));
+            if QueuedOperationStore::update(id, operation.clone()).is_ok() {
+                LogLogic::warning(
+                    operation.args.operation(),
+                    operation.args.neuron_key(),
+                    format!("Queued operation {} was interrupted while executing", id),
+                );
+            }
+        }
+    }
+
+    /// Timers do not survive an upgrade, schedules all operations that are still queued.
+    pub fn schedule_all() {
+        for (id, operation) in QueuedOperationStore::get_all() {
+            if operation.status == QueuedOperationStatus::Queued {
+                Self::schedule(id, &operation);
+            }
+        }
+    }
+
+    fn schedule(id: u64, operation: &QueuedOperation) {
+        OperationTimers::create_once(
+            &id,
+            Duration::from_nanos(operation.execute_at.saturating_sub(time())),
+            move || {
+                ic_cdk::futures::spawn(async move {
+                    QueueLogic::execute(id).await;
+                });
+            },
+        );
+    }
+
+    async fn execute(id: u64) {
+        OperationTimers::clear(&id);
+        let Ok((_, mut operation)) = Self::get_queued(id) else {
+            return;
+        };
+
+        operation.status = QueuedOperationStatus::Executing;
+        operation.updated_at = time();
+        let Ok((_, mut operation)) = QueuedOperationStore::update(id, operation) else {
+            return;
+        };
+
+        let result = NeuronLogic::tk_service_manage_neuron(operation.args.clone()).await;
+        match &result {
+            Ok(response) => {
+                BlockLogic::append(operation.caller, &operation.args, response);
+                LogLogic::info(
+                    operation.args.operation(),
+                    operation.args.neuron_key(),
+                    format!("Executed queued operation {}", id),
+                );
+            }
+            Err(e) => LogLogic::error(
+                operation.args.operation(),
+                operation.args.neuron_key(),
+                format!("Error executing queued operation {}: {}", id, e),
+            ),
+        }
+        AuditLogic::insert(
+            operation.caller,
+            &operation.args,
+            &result,
+            operation.proposal_match(),
+        );
+
+        operation.status = match result {
+            Ok(_) => QueuedOperationStatus::Executed,
+            Err(_) => QueuedOperationStatus::Failed,
+        };
+        operation.updated_at = time();
+        operation.result = Some(result.map_err(|e| e.to_string()));
+        let _ = QueuedOperationStore::update(id, operation);
+    }
+
+    fn get_queued(id: u64) -> CanisterResult<(u64, QueuedOperation)> {
+        let (id, operation) = QueuedOperationStore::get(id)?;
+        if operation.status != QueuedOperationStatus::Queued {
+            return Err(ApiError::bad_request(&format!(
+                "Operation {} is not queued",
+                id
+            )));
+        }
+        Ok((id, operation))
+    }
+}
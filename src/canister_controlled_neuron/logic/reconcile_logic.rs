@@ -0,0 +1,151 @@
+use ic_cdk::api::canister_self;
+use toolkit_utils::{result::CanisterResult, storage::StorageQueryable};
+
+use crate::{
+    api::icp_governance_api::Neuron as GovNeuron,
+    logic::{log_logic::LogLogic, neuron_logic::NeuronLogic, voting_power_logic::VotingPowerLogic},
+    storage::neuron_reference_storage::NeuronReferenceStore,
+    types::{neuron_reference::NeuronReference, reconcile::ReconcileReport},
+};
+
+pub struct ReconcileLogic;
+
+impl ReconcileLogic {
+    /// Builds the report without changing any references.
+    pub async fn get_report() -> CanisterResult<ReconcileReport> {
+        let (report, _) = Self::diff(&Self::list_neurons().await?)?;
+        Ok(report)
+    }
+
+    /// Imports neurons without a reference, corrects the subaccount of references that are
+    /// matched by neuron id and logs references whose neuron is missing.
+    pub async fn reconcile() -> CanisterResult<ReconcileReport> {
+        let neurons = Self::list_neurons().await?;
+
+        for neuron in &neurons {
+            let (Some(neuron_id), Ok(subaccount)) = (
+                neuron.id.as_ref().map(|id| id.id),
+                <[u8; 32]>::try_from(neuron.account.as_slice()),
+            ) else {
+                continue;
+            };
+
+            if let Ok((id, mut reference)) = NeuronReferenceStore::get_by_neuron_id(neuron_id) {
+                if reference.subaccount != subaccount {
+                    VotingPowerLogic::unschedule(id, &reference);
+                    reference.subaccount = subaccount;
+                    let (id, reference) = NeuronReferenceStore::update(id, reference)?;
+                    VotingPowerLogic::schedule(id, &reference);
+                    LogLogic::info(
+                        "reconcile",
+                        Some(subaccount),
+                        format!("Filled in the subaccount of neuron {}", neuron_id),
+                    );
+                }
+            }
+        }
+
+        let (mut report, unknown_neurons) = Self::diff(&neurons)?;
+        for (subaccount, neuron) in unknown_neurons {
+            // The memo of a neuron that was not created here is unknown, it is refreshed by
+            // subaccount instead
+            let mut reference = NeuronReference::new(0);
+            reference.subaccount = subaccount;
+            reference.neuron_id = neuron.id.map(|id| id.id);
+            reference.hot_keys = neuron.hot_keys;
+
+            let (id, reference) = NeuronReferenceStore::insert(reference)?;
+            VotingPowerLogic::schedule(id, &reference);
+            report.imported.push(reference.to_response(id)?);
+        }
+
+        for missing in &report.missing {
+            LogLogic::warning(
+                "reconcile",
+                Some(missing.subaccount),
+                format!(
+                    "Neuron {:?} of reference {} no longer exists",
+                    missing.neuron_id, missing.storage_reference_id
+                ),
+            );
+        }
+
+        LogLogic::info(
+            "reconcile",
+            None,
+            format!(
+                "Reconciled neurons, {} matched, {} imported, {} missing",
+                report.matched.len(),
+                report.imported.len(),
+                report.missing.len()
+            ),
+        );
+
+        Ok(report)
+    }
+
+    async fn list_neurons() -> CanisterResult<Vec<GovNeuron>> {
+        Ok(NeuronLogic::list_controlled_neurons()
+            .await?
+            .into_iter()
+            .filter(|neuron| neuron.controller == Some(canister_self()))
+            .collect())
+    }
+
+    fn diff(
+        neurons: &[GovNeuron],
+    ) -> CanisterResult<(ReconcileReport, Vec<([u8; 32], GovNeuron)>)> {
+        let references = NeuronReferenceStore::get_all();
+
+        let mut report = ReconcileReport {
+            matched: vec![],
+            unknown: vec![],
+            imported: vec![],
+            missing: vec![],
+        };
+        let mut unknown_neurons = vec![];
+
+        for neuron in neurons {
+            let Some(neuron_id) = neuron.id.as_ref().map(|id| id.id) else {
+                continue;
+            };
+
+            let is_known = NeuronReferenceStore::get_by_neuron_id(neuron_id).is_ok()
+                || <[u8; 32]>::try_from(neuron.account.as_slice()).is_ok_and(|subaccount| {
+                    NeuronReferenceStore::get_by_subaccount(subaccount).is_ok()
+                });
+
+            if is_known {
+                report.matched.push(neuron_id);
+            } else if Self::is_empty(neuron) {
+                // Left behind by a merge or a full disbursement, there is nothing to manage
+                continue;
+            } else if let Ok(subaccount) = <[u8; 32]>::try_from(neuron.account.as_slice()) {
+                report.unknown.push(neuron_id);
+                unknown_neurons.push((subaccount, neuron.clone()));
+            }
+        }
+
+        for (id, reference) in &references {
+            let Some(neuron_id) = reference.neuron_id else {
+                continue;
+            };
+
+            let exists = neurons
+                .iter()
+                .any(|neuron| neuron.id.as_ref().map(|id| id.id) == Some(neuron_id));
+
+            if !exists {
+                report.missing.push(reference.to_response(*id)?);
+            }
+        }
+
+        Ok((report, unknown_neurons))
+    }
+
+    fn is_empty(neuron: &GovNeuron) -> bool {
+        neuron.cached_neuron_stake_e8s == 0
+            && neuron.maturity_e8s_equivalent == 0
+            && neuron.staked_maturity_e8s_equivalent.unwrap_or_default() == 0
+    }
+}
@@ -0,0 +1,102 @@
+use candid::Principal;
+use ic_cdk::api::{canister_self, msg_caller, time};
+use toolkit_utils::{api_error::ApiError, result::CanisterResult, storage::StorageQueryable};
+
+use crate::{
+    logic::{config_logic::ConfigLogic, log_logic::LogLogic},
+    storage::role_storage::RoleStore,
+    types::role::{Role, RoleAssignment, RoleAssignmentResponse, SetRoleArgs},
+};
+
+pub struct RoleLogic;
+
+impl RoleLogic {
+    pub fn get_roles() -> Vec<RoleAssignmentResponse> {
+        RoleStore::get_all()
+            .into_iter()
+            .map(|(principal, assignment)| assignment.to_response(principal))
+            .collect()
+    }
+
+    pub fn get_role(principal: Principal) -> CanisterResult<Option<Role>> {
+        if ConfigLogic::get_config()?.governance_canister_id == principal {
+            return Ok(Some(Role::Admin));
+        }
+        // The admin role is reserved for the governance canister, an admin role that was
+        // assigned before that only grants operator rights
+        Ok(
+            RoleStore::find(principal).map(|assignment| match assignment.role {
+                Role::Admin => Role::Operator,
+                role => role,
+            }),
+        )
+    }
+
+    pub fn authorize(principal: Principal, required: Role) -> CanisterResult<()> {
+        match Self::get_role(principal)? {
+            Some(role) if role.includes(required) => Ok(()),
+            _ => Err(ApiError::forbidden(&format!(
+                "Caller does not have the {:?} role",
+                required
+            ))),
+        }
+    }
+
+    pub fn set_role(args: SetRoleArgs) -> CanisterResult<Vec<RoleAssignmentResponse>> {
+        Self::validate_args(&args)?;
+
+        match args.role {
+            Some(role) => {
+                RoleStore::upsert(
+                    args.principal,
+                    RoleAssignment {
+                        role,
+                        assigned_at: time(),
+                        assigned_by: msg_caller(),
+                    },
+                );
+                LogLogic::info(
+                    "set_role",
+                    None,
+                    format!("Assigned the {:?} role to {}", role, args.principal),
+                );
+            }
+            None => {
+                RoleStore::delete(args.principal);
+                LogLogic::info(
+                    "set_role",
+                    None,
+                    format!("Revoked the role of {}", args.principal),
+                );
+            }
+        }
+
+        Ok(Self::get_roles())
+    }
+
+    pub fn validate_set_role(args: SetRoleArgs) -> CanisterResult<String> {
+        Self::validate_args(&args)?;
+        Ok(serde_json::to_string(&args).unwrap())
+    }
+
+    fn validate_args(args: &SetRoleArgs) -> CanisterResult<()> {
+        if args.principal == Principal::anonymous()
+            || args.principal == canister_self()
+            || args.principal == ConfigLogic::get_config()?.governance_canister_id
+        {
+            return Err(ApiError::bad_request("Invalid principal"));
+        }
+
+        if args.role == Some(Role::Admin) {
+            return Err(ApiError::bad_request(
+                "The admin role is reserved for the governance canister",
+            ));
+        }
+
+        if args.role.is_none() && RoleStore::find(args.principal).is_none() {
+            return Err(ApiError::bad_request("Principal has no role"));
+        }
+
+        Ok(())
+    }
+}
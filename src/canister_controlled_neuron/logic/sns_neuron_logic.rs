@@ -0,0 +1,314 @@
+use ic_cdk::api::canister_self;
+use toolkit_utils::{
+    api_error::ApiError,
+    icrc_ledger_types::icrc1::account::Account,
+    result::CanisterResult,
+    storage::{StorageQueryable, StorageUpdateable},
+};
+
+use crate::{
+    api::{api_clients::ApiClients, sns_governance_api::Neuron as SnsGovNeuron},
+    helpers::nat_helper::nat_to_u64,
+    logic::{
+        consent_logic::ConsentLogic, log_logic::LogLogic,
+        neuron_creation_logic::NeuronCreationLogic,
+    },
+    storage::sns_neuron_reference_storage::SnsNeuronReferenceStore,
+    types::{
+        modules::{ModuleResponse, NeuronType, SnsFollowingArgs, SnsNeuronArgs, Vote},
+        sns_neuron_reference::{SnsNeuronReference, SnsNeuronReferenceResponse},
+    },
+};
+
+pub struct SnsNeuronLogic;
+
+impl SnsNeuronLogic {
+    pub fn get_neurons() -> CanisterResult<Vec<SnsNeuronReferenceResponse>> {
+        SnsNeuronReferenceStore::get_all()
+            .into_iter()
+            .map(|(id, neuron)| neuron.to_response(id))
+            .collect()
+    }
+
+    pub async fn create_neuron(
+        amount_e8s: u64,
+        dissolve_delay: Option<u64>,
+    ) -> CanisterResult<SnsNeuronReferenceResponse> {
+        let (minimum_stake, _) = Self::get_staking_requirements().await?;
+        if amount_e8s < minimum_stake {
+            return Err(ApiError::bad_request(&format!(
+                "Amount is too small, minimum stake is {} e8s",
+                minimum_stake
+            )));
+        }
+
+        let id = NeuronCreationLogic::start_sns(amount_e8s, dissolve_delay)?;
+        NeuronCreationLogic::advance_sns(id).await
+    }
+
+    pub async fn claim_neuron(subaccount: [u8; 32]) -> CanisterResult<SnsNeuronReferenceResponse> {
+        let (id, neuron) = SnsNeuronReferenceStore::get_by_subaccount(subaccount)?;
+        let (id, neuron) = Self::claim_and_store(id, neuron).await?;
+        neuron.to_response(id)
+    }
+
+    async fn claim_and_store(
+        id: u64,
+        mut neuron: SnsNeuronReference,
+    ) -> CanisterResult<(u64, SnsNeuronReference)> {
+        let claimed_neuron = neuron.claim_or_refresh().await.map_err(|e| {
+            LogLogic::error(
+                "claim_neuron",
+                Some(neuron.subaccount),
+                format!("Error claiming or refreshing SNS neuron: {}", e),
+            );
+            e
+        })?;
+
+        if let Some(refreshed_neuron_id) = claimed_neuron.refreshed_neuron_id {
+            neuron.neuron_id = refreshed_neuron_id.id.try_into().ok();
+        }
+
+        SnsNeuronReferenceStore::update(id, neuron)
+    }
+
+    pub async fn top_up_neuron_by_subaccount(
+        subaccount: [u8; 32],
+        amount_e8s: u64,
+    ) -> CanisterResult<bool> {
+        let (_, mut neuron) = SnsNeuronReferenceStore::get_by_subaccount(subaccount)?;
+
+        let _ = neuron.top_up(amount_e8s).await.map_err(|e| {
+            LogLogic::error(
+                "top_up",
+                Some(subaccount),
+                format!("Error topping up SNS neuron: {}", e),
+            );
+            e
+        })?;
+
+        neuron.claim_or_refresh().await.map_err(|e| {
+            LogLogic::error(
+                "top_up",
+                Some(subaccount),
+                format!("Error claiming or refreshing SNS neuron: {}", e),
+            );
+            e
+        })?;
+
+        Ok(true)
+    }
+
+    pub async fn add_dissolve_delay(
+        subaccount: [u8; 32],
+        dissolve_delay: u64,
+    ) -> CanisterResult<bool> {
+        let (_, neuron) = SnsNeuronReferenceStore::get_by_subaccount(subaccount)?;
+        neuron.increase_dissolve_delay(dissolve_delay).await?;
+        Ok(true)
+    }
+
+    pub async fn set_dissolve_state(
+        subaccount: [u8; 32],
+        start_dissolving: bool,
+    ) -> CanisterResult<bool> {
+        let (_, neuron) = SnsNeuronReferenceStore::get_by_subaccount(subaccount)?;
+        neuron.set_dissolve_state(start_dissolving).await?;
+        Ok(true)
+    }
+
+    pub async fn vote(subaccount: [u8; 32], proposal_id: u64, vote: Vote) -> CanisterResult<bool> {
+        let (_, neuron) = SnsNeuronReferenceStore::get_by_subaccount(subaccount)?;
+        neuron.vote(proposal_id, vote).await.map_err(|e| {
+            LogLogic::error(
+                "vote",
+                Some(subaccount),
+                format!("Error voting with SNS neuron: {}", e),
+            );
+            e
+        })
+    }
+
+    /// SNS governance only sets the followees of one function per call, so when a call fails the
+    /// functions that were already updated get their previous followees back. This way the
+    /// following is either applied completely or not at all.
+    pub async fn set_following(
+        subaccount: [u8; 32],
+        following: Vec<SnsFollowingArgs>,
+    ) -> CanisterResult<()> {
+        let (_, neuron) = SnsNeuronReferenceStore::get_by_subaccount(subaccount)?;
+        let previous_followees = neuron.get_full_neuron().await?.followees;
+
+        let mut applied: Vec<u64> = vec![];
+        for arg in following {
+            if let Err(e) = neuron.set_following(arg.function_id, arg.followees).await {
+                for function_id in applied.into_iter().rev() {
+                    let followees = previous_followees
+                        .iter()
+                        .find(|(id, _)| *id == function_id)
+                        .map(|(_, followees)| {
+                            followees
+                                .followees
+                                .iter()
+                                .filter_map(|id| id.id.as_slice().try_into().ok())
+                                .collect()
+                        })
+                        .unwrap_or_default();
+
+                    if let Err(e) = neuron.set_following(function_id, followees).await {
+                        LogLogic::error(
+                            "set_following",
+                            Some(subaccount),
+                            format!(
+                                "Error restoring the followees of function {}: {}",
+                                function_id, e
+                            ),
+                        );
+                    }
+                }
+                return Err(e);
+            }
+            applied.push(arg.function_id);
+        }
+
+        Ok(())
+    }
+
+    pub async fn disburse(subaccount: [u8; 32]) -> CanisterResult<u64> {
+        let (_, neuron) = SnsNeuronReferenceStore::get_by_subaccount(subaccount)?;
+        let response = neuron.disburse().await?;
+        Ok(response.transfer_block_height)
+    }
+
+    pub async fn get_full_neuron(subaccount: [u8; 32]) -> CanisterResult<SnsGovNeuron> {
+        let (_, neuron) = SnsNeuronReferenceStore::get_by_subaccount(subaccount)?;
+        neuron.get_full_neuron().await
+    }
+
+    pub async fn get_sns_balance() -> CanisterResult<u64> {
+        let (balance,) = ApiClients::sns_ledger()?
+            .icrc1_balance_of(Account {
+                owner: canister_self(),
+                subaccount: None,
+            })
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        nat_to_u64(&balance)
+    }
+
+    /// Returns the minimum stake and the transaction fee of the SNS, both in e8s.
+    pub async fn get_staking_requirements() -> CanisterResult<(u64, u64)> {
+        let (parameters,) = ApiClients::sns_governance()?
+            .get_nervous_system_parameters()
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        Ok((
+            parameters.neuron_minimum_stake_e8s.unwrap_or_default(),
+            parameters.transaction_fee_e8s.unwrap_or_default(),
+        ))
+    }
+
+    pub async fn tk_service_manage_neuron(args: SnsNeuronArgs) -> CanisterResult<ModuleResponse> {
+        match args {
+            SnsNeuronArgs::Create(args) => {
+                let result =
+                    SnsNeuronLogic::create_neuron(args.amount_e8s, args.dissolve_delay_seconds)
+                        .await?;
+                Ok(ModuleResponse::SnsNeuron(Box::new(result)))
+            }
+            SnsNeuronArgs::TopUp(args) => {
+                let result =
+                    SnsNeuronLogic::top_up_neuron_by_subaccount(args.subaccount, args.amount_e8s)
+                        .await?;
+                Ok(ModuleResponse::Boolean(result))
+            }
+            SnsNeuronArgs::Claim(args) => {
+                let result = SnsNeuronLogic::claim_neuron(args.subaccount).await?;
+                Ok(ModuleResponse::SnsNeuron(Box::new(result)))
+            }
+            SnsNeuronArgs::AddDissolveDelay(args) => {
+                let result = SnsNeuronLogic::add_dissolve_delay(
+                    args.subaccount,
+                    args.dissolve_delay_seconds,
+                )
+                .await?;
+                Ok(ModuleResponse::Boolean(result))
+            }
+            SnsNeuronArgs::SetDissolveState(args) => {
+                let result =
+                    SnsNeuronLogic::set_dissolve_state(args.subaccount, args.start_dissolving)
+                        .await?;
+                Ok(ModuleResponse::Boolean(result))
+            }
+            SnsNeuronArgs::Vote(args) => {
+                let result =
+                    SnsNeuronLogic::vote(args.subaccount, args.proposal_id, args.vote).await?;
+                Ok(ModuleResponse::Boolean(result))
+            }
+            SnsNeuronArgs::SetFollowing(args) => {
+                SnsNeuronLogic::set_following(args.subaccount, args.following).await?;
+                Ok(ModuleResponse::Boolean(true))
+            }
+            SnsNeuronArgs::Disburse(args) => {
+                let result = SnsNeuronLogic::disburse(args.subaccount).await?;
+                Ok(ModuleResponse::BlockHeight(result))
+            }
+        }
+    }
+
+    pub async fn tk_service_validate_manage_neuron(args: SnsNeuronArgs) -> CanisterResult<String> {
+        let description = ConsentLogic::describe(&NeuronType::Sns(args.clone()));
+        match args {
+            SnsNeuronArgs::Create(args) => {
+                let (minimum_stake, fee) = SnsNeuronLogic::get_staking_requirements().await?;
+                if args.amount_e8s < minimum_stake {
+                    return Err(ApiError::bad_request(&format!(
+                        "Amount is too small, minimum stake is {} e8s",
+                        minimum_stake
+                    )));
+                }
+
+                let balance = SnsNeuronLogic::get_sns_balance().await?;
+                if balance < args.amount_e8s + fee {
+                    return Err(ApiError::bad_request("Insufficient balance"));
+                }
+                Ok(description)
+            }
+            SnsNeuronArgs::TopUp(args) => {
+                SnsNeuronLogic::get_full_neuron(args.subaccount).await?;
+                let (_, fee) = SnsNeuronLogic::get_staking_requirements().await?;
+                let balance = SnsNeuronLogic::get_sns_balance().await?;
+                if balance < args.amount_e8s + fee {
+                    return Err(ApiError::bad_request("Insufficient balance"));
+                }
+                Ok(description)
+            }
+            SnsNeuronArgs::Claim(args) => {
+                SnsNeuronReferenceStore::get_by_subaccount(args.subaccount)?;
+                Ok(description)
+            }
+            SnsNeuronArgs::AddDissolveDelay(args) => {
+                SnsNeuronLogic::get_full_neuron(args.subaccount).await?;
+                Ok(description)
+            }
+            SnsNeuronArgs::SetDissolveState(args) => {
+                SnsNeuronLogic::get_full_neuron(args.subaccount).await?;
+                Ok(description)
+            }
+            SnsNeuronArgs::Vote(args) => {
+                SnsNeuronLogic::get_full_neuron(args.subaccount).await?;
+                Ok(description)
+            }
+            SnsNeuronArgs::SetFollowing(args) => {
+                SnsNeuronLogic::get_full_neuron(args.subaccount).await?;
+                Ok(description)
+            }
+            SnsNeuronArgs::Disburse(args) => {
+                SnsNeuronLogic::get_full_neuron(args.subaccount).await?;
+                Ok(description)
+            }
+        }
+    }
+}
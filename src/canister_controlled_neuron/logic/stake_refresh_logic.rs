@@ -0,0 +1,68 @@
+use std::time::Duration;
+
+use toolkit_utils::{result::CanisterResult, storage::StorageQueryable};
+
+use crate::{
+    logic::log_logic::LogLogic, storage::neuron_reference_storage::NeuronReferenceStore,
+    timers::storages::JobTimers, traits::timer_traits::TimerActions,
+    types::neuron_reference::NeuronReference,
+};
+
+const STAKE_REFRESH_JOB: &str = "refresh_stakes";
+const STAKE_REFRESH_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Picks up ICP that was sent to the stake account of a neuron by someone other than this
+/// canister, the governance canister only accounts for it after a claim or refresh.
+pub struct StakeRefreshLogic;
+
+impl StakeRefreshLogic {
+    pub fn start_job() {
+        JobTimers::create_recurring(
+            &STAKE_REFRESH_JOB.to_string(),
+            STAKE_REFRESH_INTERVAL,
+            || {
+                ic_cdk::futures::spawn(async {
+                    StakeRefreshLogic::refresh_stakes().await;
+                });
+            },
+        );
+    }
+
+    pub async fn refresh_stakes() {
+        for (_, mut neuron) in NeuronReferenceStore::get_all() {
+            if neuron.neuron_id.is_none() {
+                continue;
+            }
+
+            if let Err(e) = Self::refresh_stake(&mut neuron).await {
+                LogLogic::error(
+                    "refresh_stake",
+                    Some(neuron.subaccount),
+                    format!(
+                        "Error refreshing stake of neuron {:?}: {}",
+                        neuron.neuron_id, e
+                    ),
+                );
+            }
+        }
+    }
+
+    async fn refresh_stake(neuron: &mut NeuronReference) -> CanisterResult<()> {
+        let balance = neuron.stake_balance().await?;
+        let cached_stake = neuron.get_full_neuron().await?.cached_neuron_stake_e8s;
+
+        if balance != cached_stake {
+            neuron.claim_or_refresh().await?;
+            LogLogic::info(
+                "refresh_stake",
+                Some(neuron.subaccount),
+                format!(
+                    "Refreshed stake of neuron {:?} from {} to {} e8s",
+                    neuron.neuron_id, cached_stake, balance
+                ),
+            );
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::collections::HashSet;
+
+use ic_stable_structures::Storable;
+use toolkit_utils::{
+    cell::CellStorage,
+    result::CanisterResult,
+    storage::{Storage, StorageQueryable, StorageUpdateable},
+    StaticStorageRef,
+};
+
+use crate::{
+    logic::{
+        block_logic::BlockLogic, config_logic::ConfigLogic, log_logic::LogLogic,
+        neuron_creation_logic::NeuronCreationLogic, policy_logic::PolicyLogic,
+        queue_logic::QueueLogic, stake_refresh_logic::StakeRefreshLogic,
+        voting_power_logic::VotingPowerLogic, voting_rule_logic::VotingRuleLogic,
+    },
+    storage::{
+        audit_record_storage::AuditRecordStore, block_storage::BlockStore,
+        config_change_storage::ConfigChangeStore, config_storage::config_store,
+        log_storage::LogStore, neuron_creation_storage::NeuronCreationStore,
+        neuron_reference_storage::NeuronReferenceStore, policy_storage::DisbursementStore,
+        queued_operation_storage::QueuedOperationStore, role_storage::RoleStore,
+        sns_neuron_reference_storage::SnsNeuronReferenceStore,
+        voting_power_refresh_storage::VotingPowerRefreshStore,
+        voting_rule_storage::VoteDecisionStore,
+    },
+};
+
+pub struct UpgradeLogic;
+
+impl UpgradeLogic {
+    pub fn pre_upgrade() {
+        LogLogic::info(
+            "pre_upgrade",
+            None,
+            format!("Upgrade started from version {}", env!("CARGO_PKG_VERSION")),
+        );
+    }
+
+    /// Decodes every stored entry, which migrates entries written with an older schema, and
+    /// writes it back in the current schema. An entry that can not be decoded traps, which
+    /// rolls back the upgrade and keeps the previous version of the canister running.
+    pub fn post_upgrade() -> CanisterResult<()> {
+        let config = config_store().get()?;
+        config_store().set(config)?;
+
+        let neuron_references = NeuronReferenceStore::get_all();
+        let neuron_reference_count = neuron_references.len();
+        let mut subaccounts = HashSet::new();
+        for (id, neuron) in neuron_references {
+            if !subaccounts.insert(neuron.subaccount) {
+                LogLogic::warning(
+                    "post_upgrade",
+                    Some(neuron.subaccount),
+                    format!("Duplicate subaccount found for neuron reference {}", id),
+                );
+            }
+            NeuronReferenceStore::update(id, neuron)?;
+        }
+        NeuronReferenceStore::rebuild_indexes();
+
+        let sns_neuron_references = SnsNeuronReferenceStore::get_all();
+        let sns_neuron_reference_count = sns_neuron_references.len();
+        for (id, neuron) in sns_neuron_references {
+            SnsNeuronReferenceStore::update(id, neuron)?;
+        }
+
+        LogLogic::info(
+            "post_upgrade",
+            None,
+            format!(
+                "Upgrade to version {} completed, migrated {} neuron references and {} SNS neuron references",
+                env!("CARGO_PKG_VERSION"),
+                neuron_reference_count,
+                sns_neuron_reference_count
+            ),
+        );
+
+        // The other stores are not rewritten, decoding them once makes an entry that can not be
+        // decoded fail the upgrade instead of every later call that reads it
+        let decoded_count = Self::decode_all(NeuronCreationStore::storage())
+            + Self::decode_all(ConfigChangeStore::storage())
+            + Self::decode_all(VotingPowerRefreshStore::storage())
+            + Self::decode_all(AuditRecordStore::storage())
+            + Self::decode_all(BlockStore::storage())
+            + Self::decode_all(RoleStore::storage())
+            + Self::decode_all(DisbursementStore::storage())
+            + Self::decode_all(QueuedOperationStore::storage())
+            + Self::decode_all(VoteDecisionStore::storage())
+            + Self::decode_all(LogStore::storage());
+        // Config changes hold encoded configs that are only decoded when they are read
+        ConfigLogic::get_config_changes();
+        PolicyLogic::get_policy();
+        VotingRuleLogic::get_voting_rules();
+        NeuronReferenceStore::peek_nonce();
+        VoteDecisionStore::rebuild_index();
+        LogLogic::info(
+            "post_upgrade",
+            None,
+            format!("Decoded {} entries of the other stores", decoded_count),
+        );
+
+        BlockLogic::certify_tip();
+
+        // Timers do not survive an upgrade
+        NeuronCreationLogic::schedule_retry();
+        VotingPowerLogic::schedule_all();
+        QueueLogic::interrupt_executing();
+        QueueLogic::schedule_all();
+        StakeRefreshLogic::start_job();
+        VotingRuleLogic::start_job();
+
+        Ok(())
+    }
+
+    fn decode_all<K: Storable + Ord + Clone, V: Storable>(storage: StaticStorageRef<K, V>) -> u64 {
+        storage.with(|data| data.borrow().iter().count() as u64)
+    }
+}
@@ -0,0 +1,435 @@
+use std::collections::HashMap;
+
+use ic_cdk::api::time;
+
+use crate::{
+    api::{
+        api_clients::ApiClients,
+        icp_governance_api::{
+            Command1, DissolveState, Followees, ManageNeuronCommandRequest, Neuron as GovNeuron,
+            NeuronId, Operation,
+        },
+    },
+    helpers::format_helper::{format_days, format_icp},
+    logic::consent_logic::ConsentLogic,
+    types::{modules::NeuronType, topic::Topic},
+};
+
+const TRANSACTION_FEE_E8S: u64 = 10_000;
+const MAX_DISSOLVE_DELAY_SECONDS: u64 = 252_460_800;
+
+/// The Markdown returned by `tk_service_validate_manage_neuron`: the description of the args,
+/// the current state of the neuron, the changes the args make to it and the outcome of
+/// simulating the command on governance.
+pub struct ValidationSummary {
+    description: String,
+    state: Vec<(String, String)>,
+    changes: Vec<(String, String, String)>,
+    simulation: Option<(String, Vec<(String, String)>)>,
+}
+
+impl ValidationSummary {
+    pub fn new(args: &NeuronType) -> Self {
+        Self {
+            description: ConsentLogic::describe(args),
+            state: vec![],
+            changes: vec![],
+            simulation: None,
+        }
+    }
+
+    /// Names of the known neurons by neuron id, empty when governance can not be reached.
+    pub async fn known_neurons() -> HashMap<u64, String> {
+        let Ok(governance) = ApiClients::icp_governance() else {
+            return HashMap::new();
+        };
+
+        match governance.list_known_neurons().await {
+            Ok((response,)) => response
+                .known_neurons
+                .into_iter()
+                .filter_map(|known_neuron| {
+                    Some((known_neuron.id?.id, known_neuron.known_neuron_data?.name))
+                })
+                .collect(),
+            Err(_) => HashMap::new(),
+        }
+    }
+
+    pub fn with_neuron(mut self, neuron: &GovNeuron, known_neurons: &HashMap<u64, String>) -> Self {
+        self.state.extend(Self::neuron_rows(neuron, known_neurons));
+        self
+    }
+
+    /// Adds the result of `simulate_manage_neuron` on the neuron and the state of the neuron
+    /// after the command. Governance only returns the resulting neuron of a merge, for the other
+    /// commands the after-state is derived from the simulated command.
+    pub fn with_simulation(
+        mut self,
+        result: &Command1,
+        neuron: &GovNeuron,
+        command: &ManageNeuronCommandRequest,
+        known_neurons: &HashMap<u64, String>,
+    ) -> Self {
+        let after = Self::after_command(neuron, command);
+        let (command_name, rows) = match result {
+            Command1::Configure {} => (
+                "Configure",
+                vec![
+                    ("Dissolve state".to_string(), Self::dissolve_state(&after)),
+                    (
+                        "Auto stake maturity".to_string(),
+                        Self::yes_no(after.auto_stake_maturity.unwrap_or_default()),
+                    ),
+                    ("Hot keys".to_string(), Self::hot_keys(&after)),
+                ],
+            ),
+            Command1::Follow {} => (
+                "Follow",
+                vec![(
+                    "Followees".to_string(),
+                    Self::followees(&after, known_neurons),
+                )],
+            ),
+            Command1::SetFollowing(_) => (
+                "SetFollowing",
+                vec![(
+                    "Followees".to_string(),
+                    Self::followees(&after, known_neurons),
+                )],
+            ),
+            Command1::Split(response) => {
+                let amount_e8s = match command {
+                    ManageNeuronCommandRequest::Split(split) => split.amount_e8s,
+                    _ => 0,
+                };
+                (
+                    "Split",
+                    vec![
+                        (
+                            "Created neuron id".to_string(),
+                            response
+                                .created_neuron_id
+                                .as_ref()
+                                .map(|id| id.id.to_string())
+                                .unwrap_or("unknown".to_string()),
+                        ),
+                        (
+                            "Parent stake".to_string(),
+                            format_icp(Self::stake(neuron).saturating_sub(amount_e8s)),
+                        ),
+                        (
+                            "Child stake".to_string(),
+                            format_icp(amount_e8s.saturating_sub(TRANSACTION_FEE_E8S)),
+                        ),
+                    ],
+                )
+            }
+            Command1::Merge(response) => (
+                "Merge",
+                response
+                    .target_neuron
+                    .as_ref()
+                    .map(|neuron| Self::neuron_rows(neuron, known_neurons))
+                    .unwrap_or_default(),
+            ),
+            _ => ("manage neuron", vec![]),
+        };
+
+        self.simulation = Some((
+            format!(
+                "The governance canister accepted the simulated **{}** command.",
+                command_name
+            ),
+            rows,
+        ));
+        self
+    }
+
+    /// The neuron with the configure and follow commands applied the way governance applies them.
+    fn after_command(neuron: &GovNeuron, command: &ManageNeuronCommandRequest) -> GovNeuron {
+        let mut after = neuron.clone();
+        match command {
+            ManageNeuronCommandRequest::Configure(configure) => {
+                if let Some(operation) = &configure.operation {
+                    Self::apply_operation(&mut after, operation);
+                }
+            }
+            ManageNeuronCommandRequest::Follow(follow) => {
+                Self::set_followees(&mut after, follow.topic, follow.followees.clone());
+            }
+            ManageNeuronCommandRequest::SetFollowing(set_following) => {
+                for topic_following in set_following.topic_following.iter().flatten() {
+                    Self::set_followees(
+                        &mut after,
+                        topic_following.topic.unwrap_or_default(),
+                        topic_following.followees.clone().unwrap_or_default(),
+                    );
+                }
+            }
+            _ => {}
+        }
+        after
+    }
+
+    fn apply_operation(neuron: &mut GovNeuron, operation: &Operation) {
+        let now = time() / 1_000_000_000;
+        let delay = Self::dissolve_delay_seconds(neuron);
+        let is_dissolving = matches!(
+            neuron.dissolve_state,
+            Some(DissolveState::WhenDissolvedTimestampSeconds(_))
+        ) && delay > 0;
+
+        match operation {
+            Operation::IncreaseDissolveDelay(increase) => {
+                let delay = (delay + increase.additional_dissolve_delay_seconds as u64)
+                    .min(MAX_DISSOLVE_DELAY_SECONDS);
+                neuron.dissolve_state = Some(if is_dissolving {
+                    DissolveState::WhenDissolvedTimestampSeconds(now + delay)
+                } else {
+                    DissolveState::DissolveDelaySeconds(delay)
+                });
+            }
+            Operation::StartDissolving {} if !is_dissolving && delay > 0 => {
+                neuron.dissolve_state =
+                    Some(DissolveState::WhenDissolvedTimestampSeconds(now + delay));
+            }
+            Operation::StopDissolving {} if is_dissolving => {
+                neuron.dissolve_state = Some(DissolveState::DissolveDelaySeconds(delay));
+            }
+            Operation::SetDissolveTimestamp(timestamp) => {
+                let delay = timestamp.dissolve_timestamp_seconds.saturating_sub(now);
+                neuron.dissolve_state = Some(if is_dissolving {
+                    DissolveState::WhenDissolvedTimestampSeconds(now + delay)
+                } else {
+                    DissolveState::DissolveDelaySeconds(delay)
+                });
+            }
+            Operation::ChangeAutoStakeMaturity(change) => {
+                neuron.auto_stake_maturity = Some(change.requested_setting_for_auto_stake_maturity);
+            }
+            Operation::AddHotKey(add) => {
+                if let Some(hot_key) = add.new_hot_key {
+                    neuron.hot_keys.push(hot_key);
+                }
+            }
+            Operation::RemoveHotKey(remove) => {
+                neuron
+                    .hot_keys
+                    .retain(|hot_key| Some(*hot_key) != remove.hot_key_to_remove);
+            }
+            _ => {}
+        }
+    }
+
+    /// Replaces the followees of the topic, no followees removes the topic.
+    fn set_followees(neuron: &mut GovNeuron, topic: i32, followees: Vec<NeuronId>) {
+        neuron
+            .followees
+            .retain(|(followee_topic, _)| *followee_topic != topic);
+        if !followees.is_empty() {
+            neuron.followees.push((topic, Followees { followees }));
+        }
+    }
+
+    fn neuron_rows(
+        neuron: &GovNeuron,
+        known_neurons: &HashMap<u64, String>,
+    ) -> Vec<(String, String)> {
+        vec![
+            (
+                "Neuron id".to_string(),
+                neuron
+                    .id
+                    .as_ref()
+                    .map(|id| id.id.to_string())
+                    .unwrap_or("unclaimed".to_string()),
+            ),
+            ("Stake".to_string(), format_icp(Self::stake(neuron))),
+            (
+                "Maturity".to_string(),
+                format_icp(neuron.maturity_e8s_equivalent),
+            ),
+            (
+                "Staked maturity".to_string(),
+                format_icp(neuron.staked_maturity_e8s_equivalent.unwrap_or_default()),
+            ),
+            ("Dissolve state".to_string(), Self::dissolve_state(neuron)),
+            (
+                "Auto stake maturity".to_string(),
+                Self::yes_no(neuron.auto_stake_maturity.unwrap_or_default()),
+            ),
+            ("Hot keys".to_string(), Self::hot_keys(neuron)),
+            (
+                "Followees".to_string(),
+                Self::followees(neuron, known_neurons),
+            ),
+        ]
+    }
+
+    pub fn row(mut self, field: &str, value: impl Into<String>) -> Self {
+        self.state.push((field.to_string(), value.into()));
+        self
+    }
+
+    pub fn change(
+        mut self,
+        field: &str,
+        before: impl Into<String>,
+        after: impl Into<String>,
+    ) -> Self {
+        self.changes
+            .push((field.to_string(), before.into(), after.into()));
+        self
+    }
+
+    pub fn render(self) -> String {
+        let mut markdown = self.description;
+
+        if !self.state.is_empty() {
+            markdown.push_str("\n\n### Current state\n\n| | |\n|---|---|");
+            for (field, value) in self.state {
+                markdown.push_str(&format!("\n| {} | {} |", field, Self::escape(&value)));
+            }
+        }
+
+        if !self.changes.is_empty() {
+            markdown.push_str("\n\n### Changes\n\n| | Before | After |\n|---|---|---|");
+            for (field, before, after) in self.changes {
+                markdown.push_str(&format!(
+                    "\n| {} | {} | {} |",
+                    field,
+                    Self::escape(&before),
+                    Self::escape(&after)
+                ));
+            }
+        }
+
+        if let Some((outcome, rows)) = self.simulation {
+            markdown.push_str(&format!("\n\n### Simulation\n\n{}", outcome));
+            if !rows.is_empty() {
+                markdown.push_str("\n\n| | |\n|---|---|");
+                for (field, value) in rows {
+                    markdown.push_str(&format!("\n| {} | {} |", field, Self::escape(&value)));
+                }
+            }
+        }
+
+        markdown
+    }
+
+    /// The stake of the neuron without the fees of rejected proposals.
+    pub fn stake(neuron: &GovNeuron) -> u64 {
+        neuron
+            .cached_neuron_stake_e8s
+            .saturating_sub(neuron.neuron_fees_e8s)
+    }
+
+    /// The remaining dissolve delay, counting down when the neuron is dissolving.
+    pub fn dissolve_delay_seconds(neuron: &GovNeuron) -> u64 {
+        match neuron.dissolve_state {
+            Some(DissolveState::DissolveDelaySeconds(seconds)) => seconds,
+            Some(DissolveState::WhenDissolvedTimestampSeconds(timestamp)) => {
+                timestamp.saturating_sub(time() / 1_000_000_000)
+            }
+            None => 0,
+        }
+    }
+
+    pub fn dissolve_state(neuron: &GovNeuron) -> String {
+        let delay = format_days(Self::dissolve_delay_seconds(neuron));
+        match neuron.dissolve_state {
+            Some(DissolveState::WhenDissolvedTimestampSeconds(_))
+                if Self::dissolve_delay_seconds(neuron) > 0 =>
+            {
+                format!("Dissolving, {} left", delay)
+            }
+            Some(DissolveState::DissolveDelaySeconds(seconds)) if seconds > 0 => {
+                format!("Not dissolving, {} delay", delay)
+            }
+            _ => "Dissolved".to_string(),
+        }
+    }
+
+    pub fn hot_keys(neuron: &GovNeuron) -> String {
+        Self::list(neuron.hot_keys.iter().map(|key| key.to_string()).collect())
+    }
+
+    pub fn followees(neuron: &GovNeuron, known_neurons: &HashMap<u64, String>) -> String {
+        Self::list(
+            neuron
+                .followees
+                .iter()
+                .map(|(topic, followees)| {
+                    format!(
+                        "{}: {}",
+                        Self::topic_name(*topic),
+                        Self::neuron_names(
+                            followees.followees.iter().map(|id| id.id),
+                            known_neurons
+                        )
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn topic_followees(
+        neuron: &GovNeuron,
+        topic: i32,
+        known_neurons: &HashMap<u64, String>,
+    ) -> String {
+        neuron
+            .followees
+            .iter()
+            .find(|(followee_topic, _)| *followee_topic == topic)
+            .map(|(_, followees)| {
+                Self::neuron_names(followees.followees.iter().map(|id| id.id), known_neurons)
+            })
+            .unwrap_or("none".to_string())
+    }
+
+    pub fn neuron_names(
+        neuron_ids: impl Iterator<Item = u64>,
+        known_neurons: &HashMap<u64, String>,
+    ) -> String {
+        let names: Vec<String> = neuron_ids
+            .map(|id| match known_neurons.get(&id) {
+                Some(name) => format!("{} ({})", name, id),
+                None => id.to_string(),
+            })
+            .collect();
+        if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.join(", ")
+        }
+    }
+
+    pub fn topic_name(topic: i32) -> String {
+        match Topic::try_from(topic) {
+            Ok(topic) => format!("{:?}", topic),
+            Err(topic) => format!("Topic {}", topic),
+        }
+    }
+
+    pub fn yes_no(value: bool) -> String {
+        match value {
+            true => "yes",
+            false => "no",
+        }
+        .to_string()
+    }
+
+    fn list(items: Vec<String>) -> String {
+        if items.is_empty() {
+            "none".to_string()
+        } else {
+            items.join("<br>")
+        }
+    }
+
+    fn escape(value: &str) -> String {
+        value.replace('|', "\\|")
+    }
+}
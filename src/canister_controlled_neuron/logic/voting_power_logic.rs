@@ -0,0 +1,128 @@
+use std::time::Duration;
+
+use ic_cdk::api::time;
+use toolkit_utils::{result::CanisterResult, storage::StorageQueryable};
+
+use crate::{
+    logic::log_logic::LogLogic,
+    storage::{
+        neuron_reference_storage::NeuronReferenceStore,
+        voting_power_refresh_storage::VotingPowerRefreshStore,
+    },
+    timers::storages::NeuronTimers,
+    traits::timer_traits::TimerActions,
+    types::{
+        neuron_reference::NeuronReference,
+        voting_power_refresh::{VotingPowerRefresh, VotingPowerRefreshResponse},
+    },
+};
+
+/// Voting power starts to decrease six months after the last refresh, refresh well before that.
+const REFRESH_INTERVAL_NANOS: u64 = 150 * 24 * 60 * 60 * 1_000_000_000;
+const RETRY_INTERVAL_NANOS: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+pub struct VotingPowerLogic;
+
+impl VotingPowerLogic {
+    pub fn get_refreshes() -> Vec<VotingPowerRefreshResponse> {
+        VotingPowerRefreshStore::get_all()
+            .into_iter()
+            .map(|(id, refresh)| refresh.to_response(id))
+            .collect()
+    }
+
+    /// Schedules the timers of all neurons from their stored schedule. Neurons without a
+    /// schedule are refreshed right away, which also determines their next run.
+    pub fn schedule_all() {
+        for (id, neuron) in NeuronReferenceStore::get_all() {
+            Self::schedule(id, &neuron);
+        }
+    }
+
+    pub fn schedule(neuron_reference_id: u64, neuron: &NeuronReference) {
+        if neuron.neuron_id.is_none() {
+            return;
+        }
+
+        let next_run_at = VotingPowerRefreshStore::find(neuron_reference_id)
+            .map(|refresh| refresh.next_run_at)
+            .unwrap_or_else(time);
+
+        NeuronTimers::create_once(
+            &neuron.subaccount,
+            Duration::from_nanos(next_run_at.saturating_sub(time())),
+            move || {
+                ic_cdk::futures::spawn(async move {
+                    VotingPowerLogic::refresh(neuron_reference_id).await;
+                });
+            },
+        );
+    }
+
+    pub fn unschedule(neuron_reference_id: u64, neuron: &NeuronReference) {
+        NeuronTimers::clear(&neuron.subaccount);
+        VotingPowerRefreshStore::delete(neuron_reference_id);
+    }
+
+    async fn refresh(neuron_reference_id: u64) {
+        let Ok((_, mut neuron)) = NeuronReferenceStore::get(neuron_reference_id) else {
+            VotingPowerRefreshStore::delete(neuron_reference_id);
+            return;
+        };
+
+        let mut refresh =
+            VotingPowerRefreshStore::find(neuron_reference_id).unwrap_or(VotingPowerRefresh {
+                next_run_at: time(),
+                last_run_at: None,
+                last_error: None,
+                voting_power_refreshed_timestamp_seconds: None,
+            });
+        refresh.last_run_at = Some(time());
+
+        match Self::refresh_neuron(neuron_reference_id, &mut neuron).await {
+            Ok(refreshed_timestamp_seconds) => {
+                refresh.last_error = None;
+                refresh.voting_power_refreshed_timestamp_seconds = refreshed_timestamp_seconds;
+                refresh.next_run_at = (refreshed_timestamp_seconds
+                    .map(|seconds| seconds * 1_000_000_000)
+                    .unwrap_or(time())
+                    + REFRESH_INTERVAL_NANOS)
+                    .max(time() + RETRY_INTERVAL_NANOS);
+            }
+            Err(e) => {
+                LogLogic::error(
+                    "refresh_voting_power",
+                    Some(neuron.subaccount),
+                    format!(
+                        "Error refreshing voting power of neuron {:?}: {}",
+                        neuron.neuron_id, e
+                    ),
+                );
+                refresh.last_error = Some(e.to_string());
+                refresh.next_run_at = time() + RETRY_INTERVAL_NANOS;
+            }
+        }
+
+        VotingPowerRefreshStore::upsert(neuron_reference_id, refresh);
+        Self::schedule(neuron_reference_id, &neuron);
+    }
+
+    async fn refresh_neuron(
+        neuron_reference_id: u64,
+        neuron: &mut NeuronReference,
+    ) -> CanisterResult<Option<u64>> {
+        neuron.refresh_voting_power().await?;
+        let full_neuron = neuron.get_full_neuron().await?;
+
+        // A split neuron whose account could not be read back is stored with the subaccount
+        // derived from its nonce
+        if let Ok(subaccount) = <[u8; 32]>::try_from(full_neuron.account.as_slice()) {
+            if subaccount != neuron.subaccount {
+                neuron.subaccount = subaccount;
+                NeuronReferenceStore::update(neuron_reference_id, neuron.clone())?;
+            }
+        }
+
+        Ok(full_neuron.voting_power_refreshed_timestamp_seconds)
+    }
+}
@@ -0,0 +1,339 @@
+use std::{collections::HashMap, time::Duration};
+
+use ic_cdk::api::{canister_self, time};
+use toolkit_utils::{
+    api_error::ApiError, cell::CellStorage, result::CanisterResult, storage::StorageQueryable,
+};
+
+use crate::{
+    api::{
+        api_clients::ApiClients,
+        icp_governance_api::{BallotInfo, ProposalInfo, Result5},
+    },
+    logic::{
+        audit_logic::AuditLogic, block_logic::BlockLogic, log_logic::LogLogic,
+        neuron_logic::NeuronLogic, validation_summary_logic::ValidationSummary,
+    },
+    storage::{
+        neuron_reference_storage::NeuronReferenceStore,
+        voting_rule_storage::{voting_rules_store, VoteDecisionStore},
+    },
+    timers::storages::JobTimers,
+    traits::timer_traits::TimerActions,
+    types::{
+        modules::{IcpNeuronArgs, NeuronSelector, NeuronType, Vote, VoteArgs},
+        neuron_reference::NeuronReference,
+        voting_rule::{
+            GetVoteDecisionsArgs, VoteDecision, VoteDecisionsResponse, VotingAction, VotingRule,
+            VotingRules,
+        },
+    },
+};
+
+const VOTING_JOB: &str = "vote_on_proposals";
+const VOTING_INTERVAL: Duration = Duration::from_secs(15 * 60);
+const MAX_RULES: usize = 100;
+const DEFAULT_PAGE_SIZE: u64 = 100;
+const MAX_PAGE_SIZE: u64 = 500;
+/// `Vote::Unspecified`, `Vote::Yes` and `Vote::No` of the governance canister.
+const UNSPECIFIED_VOTE: i32 = 0;
+const YES_VOTE: i32 = 1;
+const NO_VOTE: i32 = 2;
+
+/// Votes with the controlled neurons on pending proposals according to the voting rules, so the
+/// neurons vote consistently without a proposal for every vote.
+pub struct VotingRuleLogic;
+
+impl VotingRuleLogic {
+    pub fn get_voting_rules() -> Vec<VotingRule> {
+        voting_rules_store().get().unwrap_or_default().rules
+    }
+
+    pub fn set_voting_rules(rules: Vec<VotingRule>) -> CanisterResult<Vec<VotingRule>> {
+        Self::validate_rules(&rules)?;
+        let rules = voting_rules_store().set(VotingRules { rules })?.rules;
+        LogLogic::info(
+            "set_voting_rules",
+            None,
+            format!("Voting rules updated to {} rules", rules.len()),
+        );
+        Ok(rules)
+    }
+
+    pub fn validate_set_voting_rules(rules: Vec<VotingRule>) -> CanisterResult<String> {
+        Self::validate_rules(&rules)?;
+        Ok(serde_json::to_string(&rules).unwrap())
+    }
+
+    pub fn get_decisions(args: GetVoteDecisionsArgs) -> VoteDecisionsResponse {
+        let limit = args
+            .limit
+            .unwrap_or(DEFAULT_PAGE_SIZE)
+            .clamp(1, MAX_PAGE_SIZE) as usize;
+
+        let (decisions, next) =
+            VoteDecisionStore::get_page(args.start.unwrap_or(0), limit, |decision| {
+                args.proposal_id
+                    .is_none_or(|proposal_id| decision.proposal_id == proposal_id)
+                    && args
+                        .neuron_id
+                        .is_none_or(|neuron_id| decision.neuron_id == neuron_id)
+            });
+
+        VoteDecisionsResponse {
+            decisions: decisions
+                .into_iter()
+                .map(|(id, decision)| decision.to_response(id))
+                .collect(),
+            next,
+        }
+    }
+
+    pub fn start_job() {
+        JobTimers::create_recurring(&VOTING_JOB.to_string(), VOTING_INTERVAL, || {
+            ic_cdk::futures::spawn(async {
+                VotingRuleLogic::vote_on_pending_proposals().await;
+            });
+        });
+    }
+
+    /// Applies the first matching rule of every pending proposal to the controlled neurons that
+    /// did not vote on it yet. A proposal that follows a neuron which did not vote yet is picked
+    /// up again on the next run.
+    pub async fn vote_on_pending_proposals() {
+        let rules = Self::get_voting_rules();
+        if rules.is_empty() {
+            return;
+        }
+
+        let proposals = match Self::get_pending_proposals().await {
+            Ok(proposals) => proposals,
+            Err(e) => {
+                LogLogic::error(
+                    "vote_on_proposals",
+                    None,
+                    format!("Error getting pending proposals: {}", e),
+                );
+                return;
+            }
+        };
+
+        let proposal_ids: Vec<u64> = proposals
+            .iter()
+            .filter_map(|proposal| proposal.id.as_ref().map(|id| id.id))
+            .collect();
+        let pruned = VoteDecisionStore::prune(&proposal_ids);
+        if pruned > 0 {
+            LogLogic::info(
+                "vote_on_proposals",
+                None,
+                format!("Removed {} decisions on decided proposals", pruned),
+            );
+        }
+
+        let decided = VoteDecisionStore::decided(&proposal_ids);
+        let neurons: Vec<NeuronReference> = NeuronReferenceStore::get_all()
+            .into_iter()
+            .map(|(_, neuron)| neuron)
+            .filter(|neuron| neuron.neuron_id.is_some())
+            .collect();
+        let mut followee_ballots: HashMap<u64, Vec<BallotInfo>> = HashMap::new();
+
+        for proposal in proposals {
+            let Some(proposal_id) = proposal.id.as_ref().map(|id| id.id) else {
+                continue;
+            };
+            let proposer = proposal.proposer.as_ref().map(|proposer| proposer.id);
+            let Some((index, rule)) = rules
+                .iter()
+                .enumerate()
+                .find(|(_, rule)| rule.matches(proposal.topic, proposer))
+            else {
+                continue;
+            };
+
+            // Only neurons with a ballot can vote, and only when they did not vote yet
+            let voters: Vec<&NeuronReference> = neurons
+                .iter()
+                .filter(|neuron| {
+                    let neuron_id = neuron.neuron_id.unwrap_or_default();
+                    !decided.contains(&(proposal_id, neuron_id))
+                        && proposal
+                            .ballots
+                            .iter()
+                            .any(|(id, ballot)| *id == neuron_id && ballot.vote == UNSPECIFIED_VOTE)
+                })
+                .collect();
+            if voters.is_empty() {
+                continue;
+            }
+
+            let scope = format!(
+                "Rule {} on {} proposals",
+                index + 1,
+                ValidationSummary::topic_name(proposal.topic)
+            );
+            let (vote, reason) = match &rule.action {
+                VotingAction::Reject => (Some(Vote::Reject), format!("{} rejects", scope)),
+                VotingAction::Follow(followee) => {
+                    match Self::followee_vote(*followee, proposal_id, &mut followee_ballots).await {
+                        Some(vote) => (
+                            Some(vote.clone()),
+                            format!(
+                                "{} follows neuron {}, which voted {:?}",
+                                scope, followee, vote
+                            ),
+                        ),
+                        None => continue,
+                    }
+                }
+                VotingAction::Abstain => (None, format!("{} abstains", scope)),
+                VotingAction::Manual => (None, format!("{} requires a manual vote", scope)),
+            };
+
+            for neuron in voters {
+                if let Some(vote) = &vote {
+                    if let Err(e) = Self::vote(neuron.subaccount, proposal_id, vote.clone()).await {
+                        LogLogic::error(
+                            "vote_on_proposals",
+                            Some(neuron.subaccount),
+                            format!("Error voting on proposal {}: {}", proposal_id, e),
+                        );
+                        continue;
+                    }
+                }
+
+                Self::record(VoteDecision {
+                    proposal_id,
+                    neuron_id: neuron.neuron_id.unwrap_or_default(),
+                    topic: proposal.topic,
+                    proposer,
+                    vote: vote.clone(),
+                    reason: reason.clone(),
+                    timestamp: time(),
+                });
+            }
+        }
+    }
+
+    /// Votes the same way a `Vote` operation does, so the vote is checked against the policy and
+    /// recorded in the block log and the audit trail with this canister as the caller.
+    async fn vote(subaccount: [u8; 32], proposal_id: u64, vote: Vote) -> CanisterResult<()> {
+        let args = NeuronType::Icp(IcpNeuronArgs::Vote(VoteArgs {
+            neuron: NeuronSelector::Subaccount(subaccount),
+            proposal_id,
+            vote,
+        }));
+
+        let result = NeuronLogic::tk_service_manage_neuron(args.clone()).await;
+        if let Ok(response) = &result {
+            BlockLogic::append(canister_self(), &args, response);
+        }
+        AuditLogic::insert(canister_self(), &args, &result, None);
+        result.map(|_| ())
+    }
+
+    async fn get_pending_proposals() -> CanisterResult<Vec<ProposalInfo>> {
+        ApiClients::icp_governance()?
+            .get_pending_proposals()
+            .await
+            .map(|(proposals,)| proposals)
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))
+    }
+
+    /// The vote of the followee on the proposal, `None` when it did not vote yet. The recent
+    /// ballots of a followee are fetched once per run.
+    async fn followee_vote(
+        followee: u64,
+        proposal_id: u64,
+        followee_ballots: &mut HashMap<u64, Vec<BallotInfo>>,
+    ) -> Option<Vote> {
+        if !followee_ballots.contains_key(&followee) {
+            let ballots = match Self::get_recent_ballots(followee).await {
+                Ok(ballots) => ballots,
+                Err(e) => {
+                    LogLogic::error(
+                        "vote_on_proposals",
+                        None,
+                        format!("Error getting ballots of neuron {}: {}", followee, e),
+                    );
+                    vec![]
+                }
+            };
+            followee_ballots.insert(followee, ballots);
+        }
+
+        followee_ballots
+            .get(&followee)?
+            .iter()
+            .find(|ballot| {
+                ballot
+                    .proposal_id
+                    .as_ref()
+                    .is_some_and(|id| id.id == proposal_id)
+            })
+            .and_then(|ballot| match ballot.vote {
+                YES_VOTE => Some(Vote::Approve),
+                NO_VOTE => Some(Vote::Reject),
+                _ => None,
+            })
+    }
+
+    async fn get_recent_ballots(neuron_id: u64) -> CanisterResult<Vec<BallotInfo>> {
+        let (result,) = ApiClients::icp_governance()?
+            .get_neuron_info(neuron_id)
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        match result {
+            Result5::Ok(info) => Ok(info.recent_ballots),
+            Result5::Err(e) => Err(ApiError::external_service_error(&e.error_message)),
+        }
+    }
+
+    fn record(decision: VoteDecision) {
+        LogLogic::info(
+            "vote_on_proposals",
+            None,
+            format!(
+                "Neuron {} on proposal {}: {}",
+                decision.neuron_id, decision.proposal_id, decision.reason
+            ),
+        );
+
+        if let Err(e) = VoteDecisionStore::insert(decision) {
+            LogLogic::error(
+                "vote_on_proposals",
+                None,
+                format!("Error storing vote decision: {}", e),
+            );
+        }
+    }
+
+    fn validate_rules(rules: &[VotingRule]) -> CanisterResult<()> {
+        if rules.len() > MAX_RULES {
+            return Err(ApiError::bad_request(&format!(
+                "At most {} voting rules are allowed",
+                MAX_RULES
+            )));
+        }
+
+        let controlled_neuron_ids: Vec<u64> = NeuronReferenceStore::get_all()
+            .into_iter()
+            .filter_map(|(_, neuron)| neuron.neuron_id)
+            .collect();
+
+        for (index, rule) in rules.iter().enumerate() {
+            if let VotingAction::Follow(followee) = rule.action {
+                if followee == 0 || controlled_neuron_ids.contains(&followee) {
+                    return Err(ApiError::bad_request(&format!(
+                        "Rule {} follows an invalid neuron",
+                        index + 1
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,18 +1,45 @@
-use ic_cdk::{init, query};
-use toolkit_utils::{cell::CellStorage, result::CanisterResult, storage::StorageQueryable};
+use ic_cdk::{init, post_upgrade, pre_upgrade, query, trap, update};
+use toolkit_utils::{cell::CellStorage, result::CanisterResult};
 
 use crate::{
-    logic::config_logic::ConfigLogic,
-    storage::{config_storage::config_store, log_storage::LogStore},
-    types::config::Config,
+    logic::{
+        config_logic::ConfigLogic, log_logic::LogLogic, policy_logic::PolicyLogic,
+        role_logic::RoleLogic, stake_refresh_logic::StakeRefreshLogic, upgrade_logic::UpgradeLogic,
+        voting_rule_logic::VotingRuleLogic,
+    },
+    misc::guards::is_governance_canister,
+    storage::config_storage::config_store,
+    types::{
+        config::{Config, InitArgs, UpdateConfigArgs},
+        config_change::ConfigChangeResponse,
+        log_entry::{GetLogsArgs, LogsResponse},
+        policy::Policy,
+        role::{RoleAssignmentResponse, SetRoleArgs},
+    },
 };
 
 #[init]
-pub fn init(canisters: Config) {
+pub fn init(args: InitArgs) {
     let _ = ConfigLogic::init(
-        canisters.governance_canister_id,
-        canisters.sns_ledger_canister_id,
+        args.governance_canister_id,
+        args.sns_ledger_canister_id,
+        args.icp_governance_canister_id,
+        args.icp_ledger_canister_id,
     );
+    StakeRefreshLogic::start_job();
+    VotingRuleLogic::start_job();
+}
+
+#[pre_upgrade]
+pub fn pre_upgrade() {
+    UpgradeLogic::pre_upgrade();
+}
+
+#[post_upgrade]
+pub fn post_upgrade() {
+    if let Err(e) = UpgradeLogic::post_upgrade() {
+        trap(&e.to_string());
+    }
 }
 
 #[query]
@@ -21,9 +48,57 @@ pub fn get_config() -> CanisterResult<Config> {
 }
 
 #[query]
-pub fn get_logs() -> Vec<String> {
-    LogStore::get_all()
-        .into_iter()
-        .map(|(_, log)| log)
-        .collect()
+pub fn get_config_changes() -> Vec<ConfigChangeResponse> {
+    ConfigLogic::get_config_changes()
+}
+
+#[update]
+pub fn tk_service_update_config(args: UpdateConfigArgs) -> CanisterResult<Config> {
+    is_governance_canister()?;
+    ConfigLogic::update_config(args)
+}
+
+#[update]
+pub fn tk_service_validate_update_config(args: UpdateConfigArgs) -> Result<String, String> {
+    is_governance_canister().map_err(|e| e.to_string())?;
+    ConfigLogic::validate_update_config(args).map_err(|e| e.to_string())
+}
+
+#[query]
+pub fn get_logs(args: GetLogsArgs) -> LogsResponse {
+    LogLogic::get_logs(args)
+}
+
+#[query]
+pub fn get_roles() -> Vec<RoleAssignmentResponse> {
+    RoleLogic::get_roles()
+}
+
+#[update]
+pub fn tk_service_set_role(args: SetRoleArgs) -> CanisterResult<Vec<RoleAssignmentResponse>> {
+    is_governance_canister()?;
+    RoleLogic::set_role(args)
+}
+
+#[update]
+pub fn tk_service_validate_set_role(args: SetRoleArgs) -> Result<String, String> {
+    is_governance_canister().map_err(|e| e.to_string())?;
+    RoleLogic::validate_set_role(args).map_err(|e| e.to_string())
+}
+
+#[query]
+pub fn get_policy() -> Policy {
+    PolicyLogic::get_policy()
+}
+
+#[update]
+pub fn tk_service_set_policy(policy: Policy) -> CanisterResult<Policy> {
+    is_governance_canister()?;
+    PolicyLogic::set_policy(policy)
+}
+
+#[update]
+pub fn tk_service_validate_set_policy(policy: Policy) -> Result<String, String> {
+    is_governance_canister().map_err(|e| e.to_string())?;
+    PolicyLogic::validate_set_policy(policy).map_err(|e| e.to_string())
 }
@@ -1,6 +1,17 @@
-use ic_cdk::query;
+use ic_cdk::{query, update};
 use toolkit_utils::icrc_types::{Icrc28TrustedOriginsResponse, SupportedStandard};
 
+use crate::{
+    logic::{block_logic::BlockLogic, consent_logic::ConsentLogic},
+    types::{
+        icrc21::{Icrc21ConsentMessageRequest, Icrc21ConsentMessageResponse},
+        icrc3::{
+            GetArchivesArgs, GetBlocksArgs, GetBlocksResult, Icrc3ArchiveInfo,
+            Icrc3DataCertificate, SupportedBlockType,
+        },
+    },
+};
+
 #[query]
 pub fn icrc28_trusted_origins() -> Icrc28TrustedOriginsResponse {
     let trusted_origins = vec![
@@ -26,5 +37,44 @@ pub fn icrc10_supported_standards() -> Vec<SupportedStandard> {
             url: "https://github.com/dfinity/wg-identity-authentication/blob/main/topics/icrc_28_trusted_origins.md".to_string(),
             name: "ICRC-28".to_string(),
         },
+        SupportedStandard {
+            url: "https://github.com/dfinity/wg-identity-authentication/blob/main/topics/ICRC-21/icrc_21_consent_msg.md".to_string(),
+            name: "ICRC-21".to_string(),
+        },
+        SupportedStandard {
+            url: "https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-3/README.md"
+                .to_string(),
+            name: "ICRC-3".to_string(),
+        },
     ]
 }
+
+#[query]
+pub fn icrc3_get_blocks(args: Vec<GetBlocksArgs>) -> GetBlocksResult {
+    BlockLogic::get_blocks(args)
+}
+
+#[query]
+pub fn icrc3_get_archives(args: GetArchivesArgs) -> Vec<Icrc3ArchiveInfo> {
+    BlockLogic::get_archives(args)
+}
+
+#[query]
+pub fn icrc3_get_tip_certificate() -> Option<Icrc3DataCertificate> {
+    BlockLogic::get_tip_certificate()
+}
+
+#[query]
+pub fn icrc3_supported_block_types() -> Vec<SupportedBlockType> {
+    BlockLogic::supported_block_types()
+}
+
+#[update]
+pub fn icrc21_canister_call_consent_message(
+    request: Icrc21ConsentMessageRequest,
+) -> Icrc21ConsentMessageResponse {
+    match ConsentLogic::consent_message(request) {
+        Ok(info) => Icrc21ConsentMessageResponse::Ok(info),
+        Err(error) => Icrc21ConsentMessageResponse::Err(error),
+    }
+}
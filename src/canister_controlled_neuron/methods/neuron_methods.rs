@@ -1,13 +1,27 @@
-use ic_cdk::{query, update};
+use candid::Principal;
+use ic_cdk::{api::msg_caller, query, update};
 use toolkit_utils::result::CanisterResult;
 
 use crate::{
-    api::icp_governance_api::Neuron as GovNeuron,
-    logic::neuron_logic::NeuronLogic,
-    misc::guards::is_governance_canister,
+    api::{icp_governance_api::Neuron as GovNeuron, sns_governance_api::Neuron as SnsGovNeuron},
+    logic::{
+        audit_logic::AuditLogic, block_logic::BlockLogic,
+        neuron_creation_logic::NeuronCreationLogic, neuron_logic::NeuronLogic,
+        queue_logic::QueueLogic, reconcile_logic::ReconcileLogic, sns_neuron_logic::SnsNeuronLogic,
+        voting_power_logic::VotingPowerLogic, voting_rule_logic::VotingRuleLogic,
+    },
+    misc::guards::{has_role, is_governance_canister},
     types::{
+        audit_record::{AuditRecordsResponse, GetAuditRecordsArgs},
         modules::{ModuleResponse, NeuronType},
+        neuron_creation::NeuronCreationOperationResponse,
         neuron_reference::NeuronReferenceResponse,
+        queued_operation::QueuedOperationResponse,
+        reconcile::ReconcileReport,
+        role::Role,
+        sns_neuron_reference::SnsNeuronReferenceResponse,
+        voting_power_refresh::VotingPowerRefreshResponse,
+        voting_rule::{GetVoteDecisionsArgs, VoteDecisionsResponse, VotingRule},
     },
 };
 
@@ -16,21 +30,127 @@ pub fn get_neuron_references() -> CanisterResult<Vec<NeuronReferenceResponse>> {
     NeuronLogic::get_neurons()
 }
 
+#[query]
+pub fn get_neuron_reference_by_neuron_id(
+    neuron_id: u64,
+) -> CanisterResult<NeuronReferenceResponse> {
+    NeuronLogic::get_neuron_by_neuron_id(neuron_id)
+}
+
+#[query]
+pub fn get_audit_records(args: GetAuditRecordsArgs) -> AuditRecordsResponse {
+    AuditLogic::get_records(args)
+}
+
+#[query]
+pub fn get_voting_power_refreshes() -> Vec<VotingPowerRefreshResponse> {
+    VotingPowerLogic::get_refreshes()
+}
+
+#[query]
+pub fn get_hot_keys(subaccount: [u8; 32]) -> CanisterResult<Vec<Principal>> {
+    NeuronLogic::get_hot_keys(subaccount)
+}
+
+#[query]
+pub fn get_sns_neuron_references() -> CanisterResult<Vec<SnsNeuronReferenceResponse>> {
+    SnsNeuronLogic::get_neurons()
+}
+
+#[query]
+pub fn get_pending_operations() -> Vec<NeuronCreationOperationResponse> {
+    NeuronCreationLogic::get_pending_operations()
+}
+
+#[update]
+pub async fn resume_pending_operations() -> CanisterResult<Vec<NeuronCreationOperationResponse>> {
+    has_role(Role::Operator)?;
+    Ok(NeuronCreationLogic::resume_pending_operations().await)
+}
+
 #[update]
 pub async fn get_full_neuron(subaccount: [u8; 32]) -> CanisterResult<GovNeuron> {
+    has_role(Role::Viewer)?;
     NeuronLogic::get_full_neuron(subaccount).await
 }
 
+#[update]
+pub async fn get_reconcile_report() -> CanisterResult<ReconcileReport> {
+    has_role(Role::Viewer)?;
+    ReconcileLogic::get_report().await
+}
+
+#[update]
+pub async fn get_full_sns_neuron(subaccount: [u8; 32]) -> CanisterResult<SnsGovNeuron> {
+    has_role(Role::Viewer)?;
+    SnsNeuronLogic::get_full_neuron(subaccount).await
+}
+
 #[update]
 pub async fn tk_service_manage_neuron(args: NeuronType) -> CanisterResult<ModuleResponse> {
-    is_governance_canister()?;
-    NeuronLogic::tk_service_manage_neuron(args).await
+    has_role(args.required_role())?;
+    let caller = msg_caller();
+
+    if let Some(delay_seconds) = QueueLogic::get_delay(&args) {
+        let proposal_match = AuditLogic::get_proposal_match(caller, &args).await;
+        let result =
+            QueueLogic::enqueue(caller, args.clone(), proposal_match.clone(), delay_seconds).await;
+        AuditLogic::insert(caller, &args, &result, proposal_match);
+        return result;
+    }
+
+    let result = NeuronLogic::tk_service_manage_neuron(args.clone()).await;
+    if let Ok(response) = &result {
+        BlockLogic::append(caller, &args, response);
+    }
+    AuditLogic::record(caller, &args, &result).await;
+    result
 }
 
 #[update]
 pub async fn tk_service_validate_manage_neuron(args: NeuronType) -> Result<String, String> {
     is_governance_canister().map_err(|e| e.to_string())?;
-    NeuronLogic::tk_service_validate_manage_neuron(args)
+    NeuronLogic::tk_service_validate_manage_neuron(args.clone())
         .await
+        .map(|summary| QueueLogic::annotate(&args, summary))
         .map_err(|e| e.to_string())
 }
+
+#[query]
+pub fn get_queued_operations() -> Vec<QueuedOperationResponse> {
+    QueueLogic::get_queued_operations()
+}
+
+#[update]
+pub fn tk_service_cancel_queued_operation(id: u64) -> CanisterResult<QueuedOperationResponse> {
+    is_governance_canister()?;
+    QueueLogic::cancel(id)
+}
+
+#[update]
+pub fn tk_service_validate_cancel_queued_operation(id: u64) -> Result<String, String> {
+    is_governance_canister().map_err(|e| e.to_string())?;
+    QueueLogic::validate_cancel(id).map_err(|e| e.to_string())
+}
+
+#[query]
+pub fn get_voting_rules() -> Vec<VotingRule> {
+    VotingRuleLogic::get_voting_rules()
+}
+
+#[query]
+pub fn get_vote_decisions(args: GetVoteDecisionsArgs) -> VoteDecisionsResponse {
+    VotingRuleLogic::get_decisions(args)
+}
+
+#[update]
+pub fn tk_service_set_voting_rules(rules: Vec<VotingRule>) -> CanisterResult<Vec<VotingRule>> {
+    is_governance_canister()?;
+    VotingRuleLogic::set_voting_rules(rules)
+}
+
+#[update]
+pub fn tk_service_validate_set_voting_rules(rules: Vec<VotingRule>) -> Result<String, String> {
+    is_governance_canister().map_err(|e| e.to_string())?;
+    VotingRuleLogic::validate_set_voting_rules(rules).map_err(|e| e.to_string())
+}
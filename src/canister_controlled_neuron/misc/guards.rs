@@ -1,7 +1,10 @@
 use ic_cdk::api::msg_caller;
 use toolkit_utils::{api_error::ApiError, result::CanisterResult};
 
-use crate::logic::config_logic::ConfigLogic;
+use crate::{
+    logic::{config_logic::ConfigLogic, role_logic::RoleLogic},
+    types::role::Role,
+};
 
 pub fn is_governance_canister() -> CanisterResult<()> {
     if ConfigLogic::get_config()?.governance_canister_id != msg_caller() {
@@ -9,3 +12,7 @@ pub fn is_governance_canister() -> CanisterResult<()> {
     }
     Ok(())
 }
+
+pub fn has_role(role: Role) -> CanisterResult<()> {
+    RoleLogic::authorize(msg_caller(), role)
+}
@@ -0,0 +1,44 @@
+use toolkit_utils::{
+    storage::{Storage, StorageInsertable, StorageQueryable},
+    StaticStorageRef,
+};
+
+use crate::types::audit_record::AuditRecord;
+
+use super::storages::AUDIT_RECORDS;
+
+pub struct AuditRecordStore;
+
+impl Storage<u64, AuditRecord> for AuditRecordStore {
+    const NAME: &'static str = "audit_record";
+
+    fn storage() -> StaticStorageRef<u64, AuditRecord> {
+        &AUDIT_RECORDS
+    }
+}
+
+impl StorageQueryable<u64, AuditRecord> for AuditRecordStore {}
+impl StorageInsertable<AuditRecord> for AuditRecordStore {}
+
+impl AuditRecordStore {
+    /// Returns up to `limit` records matching `filter` with a key of at least `start`, and the key
+    /// to continue from when more records may match.
+    pub fn get_page(
+        start: u64,
+        limit: usize,
+        filter: impl Fn(&AuditRecord) -> bool,
+    ) -> (Vec<(u64, AuditRecord)>, Option<u64>) {
+        Self::storage().with(|data| {
+            let mut records = vec![];
+            for (key, record) in data.borrow().range(start..) {
+                if records.len() == limit {
+                    return (records, Some(key));
+                }
+                if filter(&record) {
+                    records.push((key, record));
+                }
+            }
+            (records, None)
+        })
+    }
+}
@@ -0,0 +1,48 @@
+use toolkit_utils::{
+    storage::{Storage, StorageQueryable},
+    StaticStorageRef,
+};
+
+use crate::types::icrc3::Block;
+
+use super::storages::BLOCKS;
+
+pub struct BlockStore;
+
+impl Storage<u64, Block> for BlockStore {
+    const NAME: &'static str = "block";
+
+    fn storage() -> StaticStorageRef<u64, Block> {
+        &BLOCKS
+    }
+}
+
+impl StorageQueryable<u64, Block> for BlockStore {}
+
+/// Blocks are keyed by their zero based block index and are never updated or removed.
+impl BlockStore {
+    pub fn append(block: Block) -> u64 {
+        Self::storage().with(|data| {
+            let mut data = data.borrow_mut();
+            let index = data.len();
+            data.insert(index, block);
+            index
+        })
+    }
+
+    pub fn len() -> u64 {
+        Self::storage().with(|data| data.borrow().len())
+    }
+
+    pub fn last() -> Option<(u64, Block)> {
+        Self::storage().with(|data| data.borrow().last_key_value())
+    }
+
+    pub fn get_range(start: u64, length: u64) -> Vec<(u64, Block)> {
+        Self::storage().with(|data| {
+            data.borrow()
+                .range(start..start.saturating_add(length))
+                .collect()
+        })
+    }
+}
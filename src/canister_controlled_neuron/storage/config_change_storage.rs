@@ -0,0 +1,21 @@
+use toolkit_utils::{
+    storage::{Storage, StorageInsertable, StorageQueryable},
+    StaticStorageRef,
+};
+
+use crate::types::config_change::ConfigChange;
+
+use super::storages::CONFIG_CHANGES;
+
+pub struct ConfigChangeStore;
+
+impl Storage<u64, ConfigChange> for ConfigChangeStore {
+    const NAME: &'static str = "config_change";
+
+    fn storage() -> StaticStorageRef<u64, ConfigChange> {
+        &CONFIG_CHANGES
+    }
+}
+
+impl StorageQueryable<u64, ConfigChange> for ConfigChangeStore {}
+impl StorageInsertable<ConfigChange> for ConfigChangeStore {}
@@ -3,20 +3,64 @@ use toolkit_utils::{
     StaticStorageRef,
 };
 
+use crate::types::log_entry::LogEntry;
+
 use super::storages::LOG;
 
+/// The oldest entries are dropped once the log grows beyond this size.
+const MAX_LOG_ENTRIES: u64 = 10_000;
+
 pub struct LogStore;
 
-impl Storage<u64, String> for LogStore {
+impl Storage<u64, LogEntry> for LogStore {
     const NAME: &'static str = "log";
 
-    fn storage() -> StaticStorageRef<u64, String> {
+    fn storage() -> StaticStorageRef<u64, LogEntry> {
         &LOG
     }
 }
 
-impl StorageQueryable<u64, String> for LogStore {}
-impl StorageUpdateable<u64, String> for LogStore {}
-impl StorageInsertable<String> for LogStore {}
+impl StorageQueryable<u64, LogEntry> for LogStore {}
+impl StorageUpdateable<u64, LogEntry> for LogStore {}
+impl StorageInsertable<LogEntry> for LogStore {}
 
-impl LogStore {}
+/// `insert` shadows the trait method to apply the retention policy. Keys keep increasing after
+/// old entries are dropped, so they double as a pagination cursor.
+impl LogStore {
+    pub fn insert(entry: LogEntry) -> (u64, LogEntry) {
+        Self::storage().with(|data| {
+            let mut data = data.borrow_mut();
+            let key = data.last_key_value().map(|(k, _)| k + 1).unwrap_or(1);
+            data.insert(key, entry.clone());
+
+            while data.len() > MAX_LOG_ENTRIES {
+                let Some((oldest, _)) = data.first_key_value() else {
+                    break;
+                };
+                data.remove(&oldest);
+            }
+            (key, entry)
+        })
+    }
+
+    /// Returns up to `limit` entries matching `filter` with a key of at least `start`, and the key
+    /// to continue from when more entries may match.
+    pub fn get_page(
+        start: u64,
+        limit: usize,
+        filter: impl Fn(&LogEntry) -> bool,
+    ) -> (Vec<(u64, LogEntry)>, Option<u64>) {
+        Self::storage().with(|data| {
+            let mut entries = vec![];
+            for (key, entry) in data.borrow().range(start..) {
+                if entries.len() == limit {
+                    return (entries, Some(key));
+                }
+                if filter(&entry) {
+                    entries.push((key, entry));
+                }
+            }
+            (entries, None)
+        })
+    }
+}
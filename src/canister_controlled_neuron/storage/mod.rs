@@ -1,4 +1,14 @@
+pub mod audit_record_storage;
+pub mod block_storage;
+pub mod config_change_storage;
 pub mod config_storage;
 pub mod log_storage;
+pub mod neuron_creation_storage;
 pub mod neuron_reference_storage;
+pub mod policy_storage;
+pub mod queued_operation_storage;
+pub mod role_storage;
+pub mod sns_neuron_reference_storage;
 pub mod storages;
+pub mod voting_power_refresh_storage;
+pub mod voting_rule_storage;
@@ -0,0 +1,33 @@
+use toolkit_utils::{
+    storage::{Storage, StorageInsertable, StorageQueryable, StorageUpdateable},
+    StaticStorageRef,
+};
+
+use crate::types::neuron_creation::NeuronCreationOperation;
+
+use super::storages::NEURON_CREATIONS;
+
+pub struct NeuronCreationStore;
+
+impl Storage<u64, NeuronCreationOperation> for NeuronCreationStore {
+    const NAME: &'static str = "neuron_creation";
+
+    fn storage() -> StaticStorageRef<u64, NeuronCreationOperation> {
+        &NEURON_CREATIONS
+    }
+}
+
+impl StorageQueryable<u64, NeuronCreationOperation> for NeuronCreationStore {}
+impl StorageUpdateable<u64, NeuronCreationOperation> for NeuronCreationStore {}
+impl StorageInsertable<NeuronCreationOperation> for NeuronCreationStore {}
+
+impl NeuronCreationStore {
+    pub fn get_pending() -> Vec<(u64, NeuronCreationOperation)> {
+        Self::storage().with(|data| {
+            data.borrow()
+                .iter()
+                .filter(|(_, operation)| operation.is_pending())
+                .collect()
+        })
+    }
+}
@@ -1,13 +1,21 @@
 use toolkit_utils::{
     api_error::ApiError,
+    cell::CellStorage,
     result::CanisterResult,
     storage::{Storage, StorageInsertable, StorageQueryable, StorageUpdateable},
-    StaticStorageRef,
+    GenericCellStorage, StaticStorageRef,
 };
 
-use crate::types::neuron_reference::NeuronReference;
+use crate::types::{
+    modules::NeuronSelector,
+    neuron_reference::{NeuronReference, NonceCounter},
+};
+
+use super::storages::{NEURON_ID_INDEX, NEURON_REFERENCES, NEURON_SUBACCOUNT_INDEX, NONCE_COUNTER};
 
-use super::storages::NEURON_REFERENCES;
+pub fn nonce_counter_store() -> impl CellStorage<NonceCounter> {
+    GenericCellStorage::new("nonce_counter", &NONCE_COUNTER)
+}
 
 pub struct NeuronReferenceStore;
 
@@ -23,20 +31,101 @@ impl StorageQueryable<u64, NeuronReference> for NeuronReferenceStore {}
 impl StorageUpdateable<u64, NeuronReference> for NeuronReferenceStore {}
 impl StorageInsertable<NeuronReference> for NeuronReferenceStore {}
 
+/// `insert`, `update` and `remove` shadow the trait methods to keep the subaccount and neuron id
+/// indexes in sync, the trait methods must not be called directly.
 impl NeuronReferenceStore {
+    pub fn insert(value: NeuronReference) -> CanisterResult<(u64, NeuronReference)> {
+        let (key, value) = <Self as StorageInsertable<NeuronReference>>::insert(value)?;
+        Self::index(key, &value);
+        Ok((key, value))
+    }
+
+    pub fn update(key: u64, value: NeuronReference) -> CanisterResult<(u64, NeuronReference)> {
+        let previous = Self::get(key).ok();
+        let (key, value) = <Self as StorageUpdateable<u64, NeuronReference>>::update(key, value)?;
+
+        if let Some((_, previous)) = previous {
+            Self::unindex(&previous);
+        }
+        Self::index(key, &value);
+        Ok((key, value))
+    }
+
+    pub fn remove(key: u64) {
+        if let Ok((_, previous)) = Self::get(key) {
+            Self::unindex(&previous);
+        }
+        let _ = <Self as StorageUpdateable<u64, NeuronReference>>::remove(key);
+    }
+
     pub fn get_latest_key() -> u64 {
         Self::storage().with(|data| data.borrow().last_key_value().map(|(k, _)| k).unwrap_or(0))
     }
 
+    /// Hands out the next nonce for a stake subaccount.
+    pub fn next_nonce() -> CanisterResult<u64> {
+        let nonce = Self::peek_nonce();
+        nonce_counter_store().set(NonceCounter { next: nonce + 1 })?;
+        Ok(nonce)
+    }
+
+    /// The nonce `next_nonce` hands out next. Before the counter is used for the first time it
+    /// starts after the highest key and nonce in use, where `get_latest_key() + 1` left off.
+    pub fn peek_nonce() -> u64 {
+        match nonce_counter_store().get() {
+            Ok(counter) if counter.next > 0 => counter.next,
+            _ => {
+                Self::get_all()
+                    .into_iter()
+                    .map(|(_, neuron)| neuron.nonce)
+                    .fold(Self::get_latest_key(), u64::max)
+                    + 1
+            }
+        }
+    }
+
     pub fn get_by_subaccount(subaccount: [u8; 32]) -> CanisterResult<(u64, NeuronReference)> {
-        Self::storage().with(|data| {
-            let (id, neuron) = data
-                .borrow()
-                .iter()
-                .find(|(_, neuron)| neuron.subaccount == subaccount)
-                .ok_or(ApiError::not_found("Neuron not found"))?;
-
-            Ok((id, neuron.clone()))
-        })
+        let key = NEURON_SUBACCOUNT_INDEX
+            .with(|index| index.borrow().get(&subaccount))
+            .ok_or(ApiError::not_found("Neuron not found"))?;
+        Self::get(key)
+    }
+
+    pub fn get_by_neuron_id(neuron_id: u64) -> CanisterResult<(u64, NeuronReference)> {
+        let key = NEURON_ID_INDEX
+            .with(|index| index.borrow().get(&neuron_id))
+            .ok_or(ApiError::not_found("Neuron not found"))?;
+        Self::get(key)
+    }
+
+    pub fn get_by_selector(selector: NeuronSelector) -> CanisterResult<(u64, NeuronReference)> {
+        match selector {
+            NeuronSelector::Subaccount(subaccount) => Self::get_by_subaccount(subaccount),
+            NeuronSelector::NeuronId(neuron_id) => Self::get_by_neuron_id(neuron_id),
+        }
+    }
+
+    /// Rebuilds both indexes from the stored references.
+    pub fn rebuild_indexes() {
+        NEURON_SUBACCOUNT_INDEX.with(|index| index.borrow_mut().clear_new());
+        NEURON_ID_INDEX.with(|index| index.borrow_mut().clear_new());
+
+        for (key, value) in Self::get_all() {
+            Self::index(key, &value);
+        }
+    }
+
+    fn index(key: u64, value: &NeuronReference) {
+        NEURON_SUBACCOUNT_INDEX.with(|index| index.borrow_mut().insert(value.subaccount, key));
+        if let Some(neuron_id) = value.neuron_id {
+            NEURON_ID_INDEX.with(|index| index.borrow_mut().insert(neuron_id, key));
+        }
+    }
+
+    fn unindex(value: &NeuronReference) {
+        NEURON_SUBACCOUNT_INDEX.with(|index| index.borrow_mut().remove(&value.subaccount));
+        if let Some(neuron_id) = value.neuron_id {
+            NEURON_ID_INDEX.with(|index| index.borrow_mut().remove(&neuron_id));
+        }
     }
 }
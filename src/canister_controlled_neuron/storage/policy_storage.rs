@@ -0,0 +1,61 @@
+use candid::Principal;
+use toolkit_utils::{
+    cell::CellStorage,
+    storage::{Storage, StorageInsertable, StorageQueryable, StorageUpdateable},
+    GenericCellStorage, StaticStorageRef,
+};
+
+use crate::types::policy::{Disbursement, Policy};
+
+use super::storages::{DISBURSEMENTS, POLICY};
+
+pub fn policy_store() -> impl CellStorage<Policy> {
+    GenericCellStorage::new("policy", &POLICY)
+}
+
+pub struct DisbursementStore;
+
+impl Storage<u64, Disbursement> for DisbursementStore {
+    const NAME: &'static str = "disbursement";
+
+    fn storage() -> StaticStorageRef<u64, Disbursement> {
+        &DISBURSEMENTS
+    }
+}
+
+impl StorageQueryable<u64, Disbursement> for DisbursementStore {}
+impl StorageUpdateable<u64, Disbursement> for DisbursementStore {}
+impl StorageInsertable<Disbursement> for DisbursementStore {}
+
+impl DisbursementStore {
+    /// The total amount disbursed from the `ledger` at or after `timestamp`.
+    pub fn total_since(timestamp: u64, ledger: Option<Principal>) -> u64 {
+        Self::storage().with(|data| {
+            data.borrow()
+                .iter()
+                .filter(|(_, disbursement)| {
+                    disbursement.timestamp >= timestamp && disbursement.ledger == ledger
+                })
+                .map(|(_, disbursement)| disbursement.amount_e8s)
+                .sum()
+        })
+    }
+
+    /// Removes the disbursements before `timestamp`, keys are inserted in time order so only
+    /// the oldest entries are visited.
+    pub fn remove_before(timestamp: u64) {
+        Self::storage().with(|data| {
+            let expired: Vec<u64> = data
+                .borrow()
+                .iter()
+                .take_while(|(_, disbursement)| disbursement.timestamp < timestamp)
+                .map(|(key, _)| key)
+                .collect();
+
+            let mut data = data.borrow_mut();
+            for key in expired {
+                data.remove(&key);
+            }
+        });
+    }
+}
@@ -0,0 +1,22 @@
+use toolkit_utils::{
+    storage::{Storage, StorageInsertable, StorageQueryable, StorageUpdateable},
+    StaticStorageRef,
+};
+
+use crate::types::queued_operation::QueuedOperation;
+
+use super::storages::QUEUED_OPERATIONS;
+
+pub struct QueuedOperationStore;
+
+impl Storage<u64, QueuedOperation> for QueuedOperationStore {
+    const NAME: &'static str = "queued_operation";
+
+    fn storage() -> StaticStorageRef<u64, QueuedOperation> {
+        &QUEUED_OPERATIONS
+    }
+}
+
+impl StorageQueryable<u64, QueuedOperation> for QueuedOperationStore {}
+impl StorageUpdateable<u64, QueuedOperation> for QueuedOperationStore {}
+impl StorageInsertable<QueuedOperation> for QueuedOperationStore {}
@@ -0,0 +1,40 @@
+use candid::Principal;
+use toolkit_utils::{
+    storage::{Storage, StorageQueryable},
+    StaticStorageRef,
+};
+
+use crate::types::role::RoleAssignment;
+
+use super::storages::ROLES;
+
+pub struct RoleStore;
+
+impl Storage<Principal, RoleAssignment> for RoleStore {
+    const NAME: &'static str = "role";
+
+    fn storage() -> StaticStorageRef<Principal, RoleAssignment> {
+        &ROLES
+    }
+}
+
+impl StorageQueryable<Principal, RoleAssignment> for RoleStore {}
+
+/// Entries are keyed by the principal the role is assigned to.
+impl RoleStore {
+    pub fn find(principal: Principal) -> Option<RoleAssignment> {
+        Self::storage().with(|data| data.borrow().get(&principal))
+    }
+
+    pub fn upsert(principal: Principal, assignment: RoleAssignment) {
+        Self::storage().with(|data| {
+            data.borrow_mut().insert(principal, assignment);
+        });
+    }
+
+    pub fn delete(principal: Principal) {
+        Self::storage().with(|data| {
+            data.borrow_mut().remove(&principal);
+        });
+    }
+}
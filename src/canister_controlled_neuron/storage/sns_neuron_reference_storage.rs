@@ -0,0 +1,69 @@
+use toolkit_utils::{
+    api_error::ApiError,
+    cell::CellStorage,
+    result::CanisterResult,
+    storage::{Storage, StorageInsertable, StorageQueryable, StorageUpdateable},
+    GenericCellStorage, StaticStorageRef,
+};
+
+use crate::types::{neuron_reference::NonceCounter, sns_neuron_reference::SnsNeuronReference};
+
+use super::storages::{SNS_NEURON_REFERENCES, SNS_NONCE_COUNTER};
+
+pub fn sns_nonce_counter_store() -> impl CellStorage<NonceCounter> {
+    GenericCellStorage::new("sns_nonce_counter", &SNS_NONCE_COUNTER)
+}
+
+pub struct SnsNeuronReferenceStore;
+
+impl Storage<u64, SnsNeuronReference> for SnsNeuronReferenceStore {
+    const NAME: &'static str = "sns_neuron_reference";
+
+    fn storage() -> StaticStorageRef<u64, SnsNeuronReference> {
+        &SNS_NEURON_REFERENCES
+    }
+}
+
+impl StorageQueryable<u64, SnsNeuronReference> for SnsNeuronReferenceStore {}
+impl StorageUpdateable<u64, SnsNeuronReference> for SnsNeuronReferenceStore {}
+impl StorageInsertable<SnsNeuronReference> for SnsNeuronReferenceStore {}
+
+impl SnsNeuronReferenceStore {
+    pub fn get_latest_key() -> u64 {
+        Self::storage().with(|data| data.borrow().last_key_value().map(|(k, _)| k).unwrap_or(0))
+    }
+
+    /// Hands out the next nonce for an SNS stake subaccount.
+    pub fn next_nonce() -> CanisterResult<u64> {
+        let nonce = Self::peek_nonce();
+        sns_nonce_counter_store().set(NonceCounter { next: nonce + 1 })?;
+        Ok(nonce)
+    }
+
+    /// The nonce `next_nonce` hands out next. Before the counter is used for the first time it
+    /// starts after the highest key and nonce in use, where `get_latest_key() + 1` left off.
+    pub fn peek_nonce() -> u64 {
+        match sns_nonce_counter_store().get() {
+            Ok(counter) if counter.next > 0 => counter.next,
+            _ => {
+                Self::get_all()
+                    .into_iter()
+                    .map(|(_, neuron)| neuron.nonce)
+                    .fold(Self::get_latest_key(), u64::max)
+                    + 1
+            }
+        }
+    }
+
+    pub fn get_by_subaccount(subaccount: [u8; 32]) -> CanisterResult<(u64, SnsNeuronReference)> {
+        Self::storage().with(|data| {
+            let (id, neuron) = data
+                .borrow()
+                .iter()
+                .find(|(_, neuron)| neuron.subaccount == subaccount)
+                .ok_or(ApiError::not_found("SNS neuron not found"))?;
+
+            Ok((id, neuron.clone()))
+        })
+    }
+}
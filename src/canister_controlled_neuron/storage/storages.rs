@@ -1,3 +1,4 @@
+use candid::Principal;
 use ic_stable_structures::memory_manager::MemoryId;
 use toolkit_utils::{
     cell::CellStorageRef,
@@ -5,10 +6,41 @@ use toolkit_utils::{
     MemoryManagerStorage, StorageRef,
 };
 
-use crate::types::{config::Config, neuron_reference::NeuronReference};
+use crate::types::{
+    audit_record::AuditRecord,
+    config::Config,
+    config_change::ConfigChange,
+    icrc3::Block,
+    log_entry::LogEntry,
+    neuron_creation::NeuronCreationOperation,
+    neuron_reference::{NeuronReference, NonceCounter},
+    policy::{Disbursement, Policy},
+    queued_operation::QueuedOperation,
+    role::RoleAssignment,
+    sns_neuron_reference::SnsNeuronReference,
+    voting_power_refresh::VotingPowerRefresh,
+    voting_rule::{VoteDecision, VotingRules},
+};
 
 pub static CONFIG_MEMORY_ID: MemoryId = MemoryId::new(0);
 pub static NEURON_REFERENCES_MEMORY_ID: MemoryId = MemoryId::new(2);
+pub static SNS_NEURON_REFERENCES_MEMORY_ID: MemoryId = MemoryId::new(3);
+pub static NEURON_CREATIONS_MEMORY_ID: MemoryId = MemoryId::new(4);
+pub static CONFIG_CHANGES_MEMORY_ID: MemoryId = MemoryId::new(5);
+pub static VOTING_POWER_REFRESHES_MEMORY_ID: MemoryId = MemoryId::new(6);
+pub static NEURON_SUBACCOUNT_INDEX_MEMORY_ID: MemoryId = MemoryId::new(7);
+pub static NEURON_ID_INDEX_MEMORY_ID: MemoryId = MemoryId::new(8);
+pub static AUDIT_RECORDS_MEMORY_ID: MemoryId = MemoryId::new(9);
+pub static BLOCKS_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub static ROLES_MEMORY_ID: MemoryId = MemoryId::new(11);
+pub static POLICY_MEMORY_ID: MemoryId = MemoryId::new(12);
+pub static DISBURSEMENTS_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub static QUEUED_OPERATIONS_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub static VOTING_RULES_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub static VOTE_DECISIONS_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub static NONCE_COUNTER_MEMORY_ID: MemoryId = MemoryId::new(17);
+pub static VOTE_DECISION_INDEX_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub static SNS_NONCE_COUNTER_MEMORY_ID: MemoryId = MemoryId::new(19);
 
 pub static LOG_MEMORY_ID: MemoryId = MemoryId::new(254);
 
@@ -17,5 +49,37 @@ thread_local! {
     pub static CONFIG: CellStorageRef<Config> = init_cell(&MEMORY_MANAGER, "config", CONFIG_MEMORY_ID);
     pub static NEURON_REFERENCES: StorageRef<u64, NeuronReference> =
         init_btree(&MEMORY_MANAGER, NEURON_REFERENCES_MEMORY_ID);
-    pub static LOG: StorageRef<u64, String> = init_btree(&MEMORY_MANAGER, LOG_MEMORY_ID);
+    pub static SNS_NEURON_REFERENCES: StorageRef<u64, SnsNeuronReference> =
+        init_btree(&MEMORY_MANAGER, SNS_NEURON_REFERENCES_MEMORY_ID);
+    pub static NEURON_CREATIONS: StorageRef<u64, NeuronCreationOperation> =
+        init_btree(&MEMORY_MANAGER, NEURON_CREATIONS_MEMORY_ID);
+    pub static CONFIG_CHANGES: StorageRef<u64, ConfigChange> =
+        init_btree(&MEMORY_MANAGER, CONFIG_CHANGES_MEMORY_ID);
+    pub static VOTING_POWER_REFRESHES: StorageRef<u64, VotingPowerRefresh> =
+        init_btree(&MEMORY_MANAGER, VOTING_POWER_REFRESHES_MEMORY_ID);
+    pub static NEURON_SUBACCOUNT_INDEX: StorageRef<[u8; 32], u64> =
+        init_btree(&MEMORY_MANAGER, NEURON_SUBACCOUNT_INDEX_MEMORY_ID);
+    pub static NEURON_ID_INDEX: StorageRef<u64, u64> =
+        init_btree(&MEMORY_MANAGER, NEURON_ID_INDEX_MEMORY_ID);
+    pub static AUDIT_RECORDS: StorageRef<u64, AuditRecord> =
+        init_btree(&MEMORY_MANAGER, AUDIT_RECORDS_MEMORY_ID);
+    pub static BLOCKS: StorageRef<u64, Block> = init_btree(&MEMORY_MANAGER, BLOCKS_MEMORY_ID);
+    pub static ROLES: StorageRef<Principal, RoleAssignment> =
+        init_btree(&MEMORY_MANAGER, ROLES_MEMORY_ID);
+    pub static POLICY: CellStorageRef<Policy> = init_cell(&MEMORY_MANAGER, "policy", POLICY_MEMORY_ID);
+    pub static DISBURSEMENTS: StorageRef<u64, Disbursement> =
+        init_btree(&MEMORY_MANAGER, DISBURSEMENTS_MEMORY_ID);
+    pub static QUEUED_OPERATIONS: StorageRef<u64, QueuedOperation> =
+        init_btree(&MEMORY_MANAGER, QUEUED_OPERATIONS_MEMORY_ID);
+    pub static VOTING_RULES: CellStorageRef<VotingRules> =
+        init_cell(&MEMORY_MANAGER, "voting_rules", VOTING_RULES_MEMORY_ID);
+    pub static VOTE_DECISIONS: StorageRef<u64, VoteDecision> =
+        init_btree(&MEMORY_MANAGER, VOTE_DECISIONS_MEMORY_ID);
+    pub static NONCE_COUNTER: CellStorageRef<NonceCounter> =
+        init_cell(&MEMORY_MANAGER, "nonce_counter", NONCE_COUNTER_MEMORY_ID);
+    pub static VOTE_DECISION_INDEX: StorageRef<(u64, u64), u64> =
+        init_btree(&MEMORY_MANAGER, VOTE_DECISION_INDEX_MEMORY_ID);
+    pub static SNS_NONCE_COUNTER: CellStorageRef<NonceCounter> =
+        init_cell(&MEMORY_MANAGER, "sns_nonce_counter", SNS_NONCE_COUNTER_MEMORY_ID);
+    pub static LOG: StorageRef<u64, LogEntry> = init_btree(&MEMORY_MANAGER, LOG_MEMORY_ID);
 }
@@ -0,0 +1,39 @@
+use toolkit_utils::{
+    storage::{Storage, StorageQueryable},
+    StaticStorageRef,
+};
+
+use crate::types::voting_power_refresh::VotingPowerRefresh;
+
+use super::storages::VOTING_POWER_REFRESHES;
+
+pub struct VotingPowerRefreshStore;
+
+impl Storage<u64, VotingPowerRefresh> for VotingPowerRefreshStore {
+    const NAME: &'static str = "voting_power_refresh";
+
+    fn storage() -> StaticStorageRef<u64, VotingPowerRefresh> {
+        &VOTING_POWER_REFRESHES
+    }
+}
+
+impl StorageQueryable<u64, VotingPowerRefresh> for VotingPowerRefreshStore {}
+
+/// Entries are keyed by neuron reference id instead of an incrementing key.
+impl VotingPowerRefreshStore {
+    pub fn find(neuron_reference_id: u64) -> Option<VotingPowerRefresh> {
+        Self::storage().with(|data| data.borrow().get(&neuron_reference_id))
+    }
+
+    pub fn upsert(neuron_reference_id: u64, refresh: VotingPowerRefresh) {
+        Self::storage().with(|data| {
+            data.borrow_mut().insert(neuron_reference_id, refresh);
+        });
+    }
+
+    pub fn delete(neuron_reference_id: u64) {
+        Self::storage().with(|data| {
+            data.borrow_mut().remove(&neuron_reference_id);
+        });
+    }
+}
@@ -0,0 +1,111 @@
+use std::collections::HashSet;
+
+use toolkit_utils::{
+    cell::CellStorage,
+    result::CanisterResult,
+    storage::{Storage, StorageInsertable, StorageQueryable, StorageUpdateable},
+    GenericCellStorage, StaticStorageRef,
+};
+
+use crate::types::voting_rule::{VoteDecision, VotingRules};
+
+use super::storages::{VOTE_DECISIONS, VOTE_DECISION_INDEX, VOTING_RULES};
+
+pub fn voting_rules_store() -> impl CellStorage<VotingRules> {
+    GenericCellStorage::new("voting_rules", &VOTING_RULES)
+}
+
+pub struct VoteDecisionStore;
+
+impl Storage<u64, VoteDecision> for VoteDecisionStore {
+    const NAME: &'static str = "vote_decision";
+
+    fn storage() -> StaticStorageRef<u64, VoteDecision> {
+        &VOTE_DECISIONS
+    }
+}
+
+impl StorageQueryable<u64, VoteDecision> for VoteDecisionStore {}
+impl StorageUpdateable<u64, VoteDecision> for VoteDecisionStore {}
+impl StorageInsertable<VoteDecision> for VoteDecisionStore {}
+
+/// `insert` shadows the trait method to keep the `(proposal_id, neuron_id)` index in sync, the
+/// trait methods must not be called directly.
+impl VoteDecisionStore {
+    pub fn insert(value: VoteDecision) -> CanisterResult<(u64, VoteDecision)> {
+        let (key, value) = <Self as StorageInsertable<VoteDecision>>::insert(value)?;
+        VOTE_DECISION_INDEX.with(|index| {
+            index
+                .borrow_mut()
+                .insert((value.proposal_id, value.neuron_id), key)
+        });
+        Ok((key, value))
+    }
+    /// Returns up to `limit` decisions matching `filter` with a key of at least `start`, and the
+    /// key to continue from when more decisions may match.
+    pub fn get_page(
+        start: u64,
+        limit: usize,
+        filter: impl Fn(&VoteDecision) -> bool,
+    ) -> (Vec<(u64, VoteDecision)>, Option<u64>) {
+        Self::storage().with(|data| {
+            let mut decisions = vec![];
+            for (key, decision) in data.borrow().range(start..) {
+                if decisions.len() == limit {
+                    return (decisions, Some(key));
+                }
+                if filter(&decision) {
+                    decisions.push((key, decision));
+                }
+            }
+            (decisions, None)
+        })
+    }
+
+    /// The `(proposal_id, neuron_id)` pairs that have a decision for one of the given proposals.
+    pub fn decided(proposal_ids: &[u64]) -> HashSet<(u64, u64)> {
+        VOTE_DECISION_INDEX.with(|index| {
+            let index = index.borrow();
+            proposal_ids
+                .iter()
+                .flat_map(|proposal_id| {
+                    index
+                        .range((*proposal_id, 0)..=(*proposal_id, u64::MAX))
+                        .map(|(pair, _)| pair)
+                        .collect::<Vec<_>>()
+                })
+                .collect()
+        })
+    }
+
+    /// Removes the decisions on proposals that are no longer pending, votes that were cast stay
+    /// in the audit trail and the block log. Returns the number of removed decisions.
+    pub fn prune(pending_proposal_ids: &[u64]) -> usize {
+        let expired: Vec<((u64, u64), u64)> = VOTE_DECISION_INDEX.with(|index| {
+            index
+                .borrow()
+                .iter()
+                .filter(|((proposal_id, _), _)| !pending_proposal_ids.contains(proposal_id))
+                .collect()
+        });
+
+        for (pair, key) in &expired {
+            VOTE_DECISION_INDEX.with(|index| index.borrow_mut().remove(pair));
+            let _ = <Self as StorageUpdateable<u64, VoteDecision>>::remove(*key);
+        }
+        expired.len()
+    }
+
+    /// Rebuilds the index from the stored decisions.
+    pub fn rebuild_index() {
+        VOTE_DECISION_INDEX.with(|index| index.borrow_mut().clear_new());
+
+        for (key, decision) in Self::get_all() {
+            VOTE_DECISION_INDEX.with(|index| {
+                index
+                    .borrow_mut()
+                    .insert((decision.proposal_id, decision.neuron_id), key)
+            });
+        }
+    }
+}
@@ -1,19 +1,154 @@
 use std::time::Duration;
 
-use candid::encode_args;
+use candid::{decode_one, encode_args, encode_one, Nat, Principal};
 use canister_controlled_neuron::{
-    api::icp_governance_api::{MakeProposalRequest, Motion, Neuron, ProposalActionRequest},
+    api::{
+        icp_governance_api::{
+            MakeProposalRequest, ManageNeuronCommandRequest, Motion, Neuron, ProposalActionRequest,
+            Spawn,
+        },
+        sns_governance_api::Neuron as SnsNeuron,
+    },
     types::{
-        config::Config,
+        audit_record::{AuditRecordsResponse, GetAuditRecordsArgs, ProposalMatch},
+        config::{Config, UpdateConfigArgs},
+        config_change::ConfigChangeResponse,
+        icrc21::{
+            Icrc21ConsentMessage, Icrc21ConsentMessageMetadata, Icrc21ConsentMessageRequest,
+            Icrc21ConsentMessageResponse, Icrc21ConsentMessageSpec,
+        },
+        icrc3::{GetBlocksArgs, GetBlocksResult},
+        log_entry::{GetLogsArgs, LogLevel, LogsResponse},
         modules::{
-            CreateNeuronArgs, CreateProposalArgs, DisburseArgs, IcpNeuronArgs, ModuleResponse,
-            NeuronType, SpawnArgs,
+            AddDissolveDelayArgs, AutoStakeArgs, ClaimSnsNeuronArgs, CommandNeuronArgs,
+            CreateNeuronArgs, CreateProposalArgs, CreateSnsNeuronArgs, DisburseArgs,
+            DisburseDestination, DisburseMaturityArgs, FollowingArgs, HotKeyArgs, IcpNeuronArgs,
+            MergeArgs, ModuleResponse, NeuronSelector, NeuronType, SetDissolveStateArgs,
+            SetFollowingArgs, SetSnsFollowingArgs, SnsFollowingArgs, SnsNeuronArgs, SpawnArgs,
+            SplitArgs, StakeMaturityArgs, Vote,
         },
         neuron_reference::NeuronReferenceResponse,
+        policy::{DisbursementCap, Policy},
+        queued_operation::{QueuedOperationResponse, QueuedOperationStatus},
+        reconcile::ReconcileReport,
+        role::{Role, RoleAssignmentResponse, SetRoleArgs},
+        sns_neuron_reference::SnsNeuronReferenceResponse,
+        topic::Topic,
+        voting_power_refresh::VotingPowerRefreshResponse,
+        voting_rule::{GetVoteDecisionsArgs, VoteDecisionsResponse, VotingAction, VotingRule},
+    },
+};
+use test_helper::{context::Context, sender::Sender, utils::generate_principal};
+use toolkit_utils::{
+    ic_ledger_types::{
+        AccountIdentifier, Subaccount, MAINNET_GOVERNANCE_CANISTER_ID, MAINNET_LEDGER_CANISTER_ID,
     },
+    icrc_ledger_types::icrc1::account::Account,
+    result::CanisterResult,
 };
-use test_helper::{context::Context, sender::Sender};
-use toolkit_utils::{icrc_ledger_types::icrc1::account::Account, result::CanisterResult};
+
+fn create_icp_neuron(
+    context: &Context,
+    amount_e8s: u64,
+    dissolve_delay_seconds: Option<u64>,
+) -> Result<NeuronReferenceResponse, String> {
+    context.transfer_icp(
+        amount_e8s + 10_000,
+        Account {
+            owner: context.owner_account.owner,
+            subaccount: None,
+        },
+        Account {
+            owner: context.neuron_controller_canister,
+            subaccount: None,
+        },
+    );
+
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Create(CreateNeuronArgs {
+        amount_e8s,
+        auto_stake: None,
+        dissolve_delay_seconds,
+    }));
+    match manage_neuron(context, args)? {
+        ModuleResponse::Neuron(neuron) => Ok(*neuron),
+        created => Err(format!("Unexpected response: {:?}", created)),
+    }
+}
+
+fn manage_neuron(context: &Context, args: NeuronType) -> Result<ModuleResponse, String> {
+    context
+        .update::<CanisterResult<ModuleResponse>>(
+            Sender::Other(context.config.governance_canister_id),
+            "tk_service_manage_neuron",
+            Some(encode_args((args,)).unwrap()),
+        )?
+        .map_err(|e| e.to_string())
+}
+
+fn validate_neuron(context: &Context, args: NeuronType) -> Result<String, String> {
+    context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_manage_neuron",
+        Some(encode_args((args,)).unwrap()),
+    )?
+}
+
+fn get_full_neuron(context: &Context, subaccount: [u8; 32]) -> Result<Neuron, String> {
+    context
+        .update::<CanisterResult<Neuron>>(
+            Sender::Other(context.config.governance_canister_id),
+            "get_full_neuron",
+            Some(encode_args((subaccount,)).unwrap()),
+        )?
+        .map_err(|e| e.to_string())
+}
+
+fn get_voting_power_refreshes(
+    context: &Context,
+) -> Result<Vec<VotingPowerRefreshResponse>, String> {
+    context.query::<Vec<VotingPowerRefreshResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "get_voting_power_refreshes",
+        None,
+    )
+}
+
+fn tick(context: &Context, times: usize) {
+    for _ in 0..times {
+        context.pic.tick();
+    }
+}
+
+fn get_reconcile_report(context: &Context) -> Result<ReconcileReport, String> {
+    context
+        .update::<CanisterResult<ReconcileReport>>(
+            Sender::Other(context.config.governance_canister_id),
+            "get_reconcile_report",
+            None,
+        )?
+        .map_err(|e| e.to_string())
+}
+
+fn create_sns_neuron(context: &Context) -> Result<SnsNeuronReferenceResponse, String> {
+    let args: NeuronType = NeuronType::Sns(SnsNeuronArgs::Create(CreateSnsNeuronArgs {
+        amount_e8s: 1_000_000_000,
+        dissolve_delay_seconds: Some(2_629_800),
+    }));
+    match manage_neuron(context, args)? {
+        ModuleResponse::SnsNeuron(neuron) => Ok(*neuron),
+        created => Err(format!("Unexpected response: {:?}", created)),
+    }
+}
+
+fn get_full_sns_neuron(context: &Context, subaccount: [u8; 32]) -> Result<SnsNeuron, String> {
+    context
+        .update::<CanisterResult<SnsNeuron>>(
+            Sender::Other(context.config.governance_canister_id),
+            "get_full_sns_neuron",
+            Some(encode_args((subaccount,)).unwrap()),
+        )?
+        .map_err(|e| e.to_string())
+}
 
 #[test]
 fn test_get_config() -> Result<(), String> {
@@ -25,6 +160,13 @@ fn test_get_config() -> Result<(), String> {
     println!("config_result: {:?}", config_result);
     assert!(config_result.is_ok());
 
+    let config = config_result.unwrap();
+    assert_eq!(
+        config.icp_governance_canister_id,
+        MAINNET_GOVERNANCE_CANISTER_ID
+    );
+    assert_eq!(config.icp_ledger_canister_id, MAINNET_LEDGER_CANISTER_ID);
+
     Ok(())
 }
 
@@ -63,13 +205,46 @@ fn test_create_neuron_blanco() -> Result<(), String> {
     println!("result: {:?}", create_neuron);
     assert!(create_neuron.is_ok());
 
-    let logs = context.query::<Vec<String>>(
+    let logs = context.query::<LogsResponse>(
         Sender::Other(context.config.governance_canister_id),
         "get_logs",
-        None,
+        Some(
+            encode_args((GetLogsArgs {
+                level: Some(LogLevel::Error),
+                ..Default::default()
+            },))
+            .unwrap(),
+        ),
     )?;
     println!("logs: {:?}", logs);
-    assert!(logs.is_empty());
+    assert!(logs.entries.is_empty());
+
+    let audit_records = context.query::<AuditRecordsResponse>(
+        Sender::Other(context.config.governance_canister_id),
+        "get_audit_records",
+        Some(encode_args((GetAuditRecordsArgs::default(),)).unwrap()),
+    )?;
+    assert!(audit_records.records.len() == 1);
+    assert!(audit_records.records[0].operation == "create");
+    assert!(audit_records.records[0].caller == context.config.governance_canister_id);
+    assert!(audit_records.records[0].result.is_ok());
+    // The call did not come from an adopted proposal
+    assert!(audit_records.records[0].proposal_match == Some(ProposalMatch::NotFound));
+    assert!(audit_records.records[0].proposal_id.is_none());
+
+    let blocks = context.query::<GetBlocksResult>(
+        Sender::Owner,
+        "icrc3_get_blocks",
+        Some(
+            encode_args((vec![GetBlocksArgs {
+                start: Nat::from(0u64),
+                length: Nat::from(10u64),
+            }],))
+            .unwrap(),
+        ),
+    )?;
+    assert!(blocks.log_length == Nat::from(1u64));
+    assert!(blocks.blocks.len() == 1);
 
     let neuron_references = context.query::<CanisterResult<Vec<NeuronReferenceResponse>>>(
         Sender::Other(context.config.governance_canister_id),
@@ -129,13 +304,19 @@ fn test_create_neuron_with_dissolve_delay() -> Result<(), String> {
     println!("result: {:?}", create_neuron);
     assert!(create_neuron.is_ok());
 
-    let logs = context.query::<Vec<String>>(
+    let logs = context.query::<LogsResponse>(
         Sender::Other(context.config.governance_canister_id),
         "get_logs",
-        None,
+        Some(
+            encode_args((GetLogsArgs {
+                level: Some(LogLevel::Error),
+                ..Default::default()
+            },))
+            .unwrap(),
+        ),
     )?;
     println!("logs: {:?}", logs);
-    assert!(logs.is_empty());
+    assert!(logs.entries.is_empty());
 
     let neuron_references = context.query::<CanisterResult<Vec<NeuronReferenceResponse>>>(
         Sender::Other(context.config.governance_canister_id),
@@ -195,13 +376,19 @@ fn test_create_neuron_with_auto_stake() -> Result<(), String> {
     println!("result: {:?}", create_neuron);
     assert!(create_neuron.is_ok());
 
-    let logs = context.query::<Vec<String>>(
+    let logs = context.query::<LogsResponse>(
         Sender::Other(context.config.governance_canister_id),
         "get_logs",
-        None,
+        Some(
+            encode_args((GetLogsArgs {
+                level: Some(LogLevel::Error),
+                ..Default::default()
+            },))
+            .unwrap(),
+        ),
     )?;
     println!("logs: {:?}", logs);
-    assert!(logs.is_empty());
+    assert!(logs.entries.is_empty());
 
     let neuron_references = context.query::<CanisterResult<Vec<NeuronReferenceResponse>>>(
         Sender::Other(context.config.governance_canister_id),
@@ -268,7 +455,7 @@ fn test_create_proposal() -> Result<(), String> {
     let subaccount = &neuron_references_unwrapped[0].subaccount.clone();
 
     let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::CreateProposal(CreateProposalArgs {
-        subaccount: *subaccount,
+        neuron: NeuronSelector::Subaccount(*subaccount),
         proposal: MakeProposalRequest {
             title: Some("Test proposal".to_string()),
             summary: "Simulate governance vote".to_string(),
@@ -371,7 +558,7 @@ fn test_spawn_neuron_manual_disburse() -> Result<(), String> {
     let subaccount = &neuron_references_unwrapped[0].subaccount.clone();
 
     let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::CreateProposal(CreateProposalArgs {
-        subaccount: *subaccount,
+        neuron: NeuronSelector::Subaccount(*subaccount),
         proposal: MakeProposalRequest {
             title: Some("Test proposal".to_string()),
             summary: "Simulate governance vote".to_string(),
@@ -412,7 +599,7 @@ fn test_spawn_neuron_manual_disburse() -> Result<(), String> {
     assert!(neuron_info_unwrapped.maturity_e8s_equivalent > 0);
 
     let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Spawn(SpawnArgs {
-        parent_subaccount: *subaccount,
+        parent: NeuronSelector::Subaccount(*subaccount),
         start_dissolving: true,
     }));
     let x = context.update::<CanisterResult<ModuleResponse>>(
@@ -457,7 +644,9 @@ fn test_spawn_neuron_manual_disburse() -> Result<(), String> {
     assert!(neuron_info_unwrapped.maturity_e8s_equivalent == 0);
 
     let disburse_args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Disburse(DisburseArgs {
-        subaccount: neuron_references_unwrapped[1].subaccount,
+        neuron: NeuronSelector::Subaccount(neuron_references_unwrapped[1].subaccount),
+        amount_e8s: None,
+        destination: None,
     }));
 
     let disburse_result = context.update::<CanisterResult<ModuleResponse>>(
@@ -492,3 +681,1388 @@ fn test_spawn_neuron_manual_disburse() -> Result<(), String> {
     );
     Ok(())
 }
+
+#[test]
+fn test_upgrade_keeps_neuron_references() -> Result<(), String> {
+    let context = Context::new();
+
+    context.transfer_icp(
+        10_000_000_000,
+        Account {
+            owner: context.owner_account.owner,
+            subaccount: None,
+        },
+        Account {
+            owner: context.neuron_controller_canister,
+            subaccount: None,
+        },
+    );
+
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Create(CreateNeuronArgs {
+        amount_e8s: 1_000_000_000,
+        auto_stake: None,
+        dissolve_delay_seconds: None,
+    }));
+    let create_neuron = context.update::<CanisterResult<ModuleResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_manage_neuron",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    assert!(create_neuron.is_ok());
+
+    let neuron_references_before = context
+        .query::<CanisterResult<Vec<NeuronReferenceResponse>>>(
+            Sender::Other(context.config.governance_canister_id),
+            "get_neuron_references",
+            None,
+        )?
+        .unwrap();
+    assert!(neuron_references_before.len() == 1);
+
+    context.upgrade()?;
+
+    let config_result =
+        context.query::<CanisterResult<Config>>(Sender::Owner, "get_config", None)?;
+    assert!(config_result.is_ok());
+    assert!(config_result.unwrap().governance_canister_id == context.config.governance_canister_id);
+
+    let neuron_references_after = context
+        .query::<CanisterResult<Vec<NeuronReferenceResponse>>>(
+            Sender::Other(context.config.governance_canister_id),
+            "get_neuron_references",
+            None,
+        )?
+        .unwrap();
+    println!("neuron_references_after: {:?}", neuron_references_after);
+    assert!(neuron_references_after.len() == 1);
+    assert!(neuron_references_after[0].subaccount == neuron_references_before[0].subaccount);
+    assert!(neuron_references_after[0].neuron_id == neuron_references_before[0].neuron_id);
+
+    let neuron_reference_by_id = context
+        .query::<CanisterResult<NeuronReferenceResponse>>(
+            Sender::Other(context.config.governance_canister_id),
+            "get_neuron_reference_by_neuron_id",
+            Some(encode_args((neuron_references_after[0].neuron_id.unwrap(),)).unwrap()),
+        )?
+        .unwrap();
+    assert!(neuron_reference_by_id.subaccount == neuron_references_after[0].subaccount);
+
+    let neuron_info = context.update::<CanisterResult<Neuron>>(
+        Sender::Other(context.config.governance_canister_id),
+        "get_full_neuron",
+        Some(encode_args((neuron_references_after[0].subaccount,)).unwrap()),
+    )?;
+    assert!(neuron_info.is_ok());
+    Ok(())
+}
+
+#[test]
+fn test_update_config() -> Result<(), String> {
+    let context = Context::new();
+    let sns_ledger_canister_id = generate_principal();
+    let args = UpdateConfigArgs {
+        governance_canister_id: None,
+        sns_ledger_canister_id: Some(sns_ledger_canister_id),
+        icp_governance_canister_id: None,
+        icp_ledger_canister_id: None,
+    };
+
+    let unauthorized = context.update::<CanisterResult<Config>>(
+        Sender::Unauthorized,
+        "tk_service_update_config",
+        Some(encode_args((args.clone(),)).unwrap()),
+    )?;
+    assert!(unauthorized.is_err());
+
+    let validation = context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_update_config",
+        Some(encode_args((args.clone(),)).unwrap()),
+    )?;
+    assert!(validation.is_ok());
+
+    let config = context.update::<CanisterResult<Config>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_update_config",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    assert!(config.is_ok());
+    assert!(config.unwrap().sns_ledger_canister_id == sns_ledger_canister_id);
+
+    let changes =
+        context.query::<Vec<ConfigChangeResponse>>(Sender::Owner, "get_config_changes", None)?;
+    assert!(changes.len() == 1);
+    assert!(changes[0].previous.sns_ledger_canister_id == context.config.sns_ledger_canister_id);
+    assert!(changes[0].updated.sns_ledger_canister_id == sns_ledger_canister_id);
+
+    // The stored configs are decoded again after an upgrade
+    context.upgrade()?;
+    let changes =
+        context.query::<Vec<ConfigChangeResponse>>(Sender::Owner, "get_config_changes", None)?;
+    assert!(changes[0].updated.sns_ledger_canister_id == sns_ledger_canister_id);
+
+    let logs = context.query::<LogsResponse>(
+        Sender::Owner,
+        "get_logs",
+        Some(
+            encode_args((GetLogsArgs {
+                level: Some(LogLevel::Info),
+                ..Default::default()
+            },))
+            .unwrap(),
+        ),
+    )?;
+    assert!(logs
+        .entries
+        .iter()
+        .any(|entry| entry.operation == "update_config"
+            && entry.caller == context.config.governance_canister_id));
+    Ok(())
+}
+
+#[test]
+fn test_policy() -> Result<(), String> {
+    let context = Context::new();
+    let policy = Policy {
+        dissolve_delay_floor_seconds: Some(31_557_600),
+        disbursement_cap: None,
+        allowed_destinations: None,
+        denied_commands: vec!["Unknown".to_string()],
+        execution_delay_seconds: None,
+        sns_disbursement_cap: None,
+    };
+
+    let validation = context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_set_policy",
+        Some(encode_args((policy.clone(),)).unwrap()),
+    )?;
+    assert!(validation.is_err());
+
+    let policy = Policy {
+        denied_commands: vec!["Disburse".to_string()],
+        ..policy
+    };
+    let updated = context.update::<CanisterResult<Policy>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_policy",
+        Some(encode_args((policy,)).unwrap()),
+    )?;
+    assert!(updated.is_ok());
+
+    let policy = context.query::<Policy>(Sender::Owner, "get_policy", None)?;
+    assert!(policy.dissolve_delay_floor_seconds == Some(31_557_600));
+
+    // New neurons need at least the dissolve delay floor
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Create(CreateNeuronArgs {
+        amount_e8s: 1_000_000_000,
+        auto_stake: None,
+        dissolve_delay_seconds: Some(15_778_800),
+    }));
+    let validation = context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_manage_neuron",
+        Some(encode_args((args.clone(),)).unwrap()),
+    )?;
+    assert!(validation.is_err_and(|e| e.contains("Policy violation")));
+
+    let result = context.update::<CanisterResult<ModuleResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_manage_neuron",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_policy_restricts_foreign_spawns() -> Result<(), String> {
+    let context = Context::new();
+    let policy = Policy {
+        dissolve_delay_floor_seconds: None,
+        disbursement_cap: Some(DisbursementCap {
+            amount_e8s: 1_000_000_000,
+            period_seconds: u64::MAX,
+        }),
+        allowed_destinations: None,
+        denied_commands: vec![],
+        execution_delay_seconds: None,
+        sns_disbursement_cap: None,
+    };
+
+    // The period has to fit in nanoseconds
+    let updated = context.update::<CanisterResult<Policy>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_policy",
+        Some(encode_args((policy.clone(),)).unwrap()),
+    )?;
+    assert!(updated.is_err());
+
+    let policy = Policy {
+        disbursement_cap: Some(DisbursementCap {
+            amount_e8s: 1_000_000_000,
+            period_seconds: 86_400,
+        }),
+        ..policy
+    };
+    let updated = context.update::<CanisterResult<Policy>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_policy",
+        Some(encode_args((policy,)).unwrap()),
+    )?;
+    assert!(updated.is_ok());
+
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Command(CommandNeuronArgs {
+        neuron: NeuronSelector::Subaccount([1; 32]),
+        command: ManageNeuronCommandRequest::Spawn(Spawn {
+            percentage_to_spawn: Some(50),
+            new_controller: Some(generate_principal()),
+            nonce: None,
+        }),
+    }));
+    let validation = context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_manage_neuron",
+        Some(encode_args((args.clone(),)).unwrap()),
+    )?;
+    assert!(validation.is_err_and(|e| e.contains("Policy violation")));
+
+    let result = context.update::<CanisterResult<ModuleResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_manage_neuron",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    assert!(result.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_queued_operations() -> Result<(), String> {
+    let context = Context::new();
+    let policy = Policy {
+        dissolve_delay_floor_seconds: None,
+        disbursement_cap: None,
+        allowed_destinations: None,
+        denied_commands: vec![],
+        execution_delay_seconds: Some(86_400),
+        sns_disbursement_cap: None,
+    };
+    let updated = context.update::<CanisterResult<Policy>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_policy",
+        Some(encode_args((policy,)).unwrap()),
+    )?;
+    assert!(updated.is_ok());
+
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::SetDissolveState(SetDissolveStateArgs {
+        neuron: NeuronSelector::Subaccount([0; 32]),
+        start_dissolving: true,
+    }));
+    let validation = context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_manage_neuron",
+        Some(encode_args((args.clone(),)).unwrap()),
+    )?;
+    assert!(validation.is_err());
+
+    let queued = context.update::<CanisterResult<ModuleResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_manage_neuron",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    let Ok(ModuleResponse::Queued(id)) = queued else {
+        return Err("Operation was not queued".to_string());
+    };
+
+    let operations = context.query::<Vec<QueuedOperationResponse>>(
+        Sender::Owner,
+        "get_queued_operations",
+        None,
+    )?;
+    assert!(operations.len() == 1);
+    assert!(operations[0].id == id && operations[0].status == QueuedOperationStatus::Queued);
+
+    let unauthorized = context.update::<CanisterResult<QueuedOperationResponse>>(
+        Sender::Unauthorized,
+        "tk_service_cancel_queued_operation",
+        Some(encode_one(id).unwrap()),
+    )?;
+    assert!(unauthorized.is_err());
+
+    let cancelled = context.update::<CanisterResult<QueuedOperationResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_cancel_queued_operation",
+        Some(encode_one(id).unwrap()),
+    )?;
+    assert!(cancelled.is_ok_and(|operation| operation.status == QueuedOperationStatus::Cancelled));
+
+    let cancelled = context.update::<CanisterResult<QueuedOperationResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_cancel_queued_operation",
+        Some(encode_one(id).unwrap()),
+    )?;
+    assert!(cancelled.is_err());
+    Ok(())
+}
+
+#[test]
+fn test_failed_queued_operation() -> Result<(), String> {
+    let context = Context::new();
+    let policy = Policy {
+        dissolve_delay_floor_seconds: None,
+        disbursement_cap: None,
+        allowed_destinations: None,
+        denied_commands: vec![],
+        execution_delay_seconds: Some(86_400),
+        sns_disbursement_cap: None,
+    };
+    let updated = context.update::<CanisterResult<Policy>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_policy",
+        Some(encode_args((policy,)).unwrap()),
+    )?;
+    assert!(updated.is_ok());
+
+    // There is no neuron with this subaccount, so the operation fails once it executes
+    let queued = manage_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::SetDissolveState(SetDissolveStateArgs {
+            neuron: NeuronSelector::Subaccount([0; 32]),
+            start_dissolving: true,
+        })),
+    )?;
+    let ModuleResponse::Queued(id) = queued else {
+        return Err(format!("Unexpected response: {:?}", queued));
+    };
+
+    context.pic.advance_time(Duration::from_secs(86_400));
+    tick(&context, 5);
+
+    let operations = context.query::<Vec<QueuedOperationResponse>>(
+        Sender::Owner,
+        "get_queued_operations",
+        None,
+    )?;
+    assert!(operations.len() == 1);
+    assert!(operations[0].id == id && operations[0].status == QueuedOperationStatus::Failed);
+    assert!(operations[0]
+        .result
+        .as_ref()
+        .is_some_and(|result| result.is_err()));
+    Ok(())
+}
+
+#[test]
+fn test_roles() -> Result<(), String> {
+    let context = Context::new();
+    let operator = generate_principal();
+    let args = SetRoleArgs {
+        principal: operator,
+        role: Some(Role::Operator),
+    };
+
+    let unauthorized = context.update::<CanisterResult<Vec<RoleAssignmentResponse>>>(
+        Sender::Other(operator),
+        "tk_service_set_role",
+        Some(encode_args((args.clone(),)).unwrap()),
+    )?;
+    assert!(unauthorized.is_err());
+
+    let no_role = context.update::<CanisterResult<ReconcileReport>>(
+        Sender::Other(operator),
+        "get_reconcile_report",
+        None,
+    )?;
+    assert!(no_role.is_err());
+
+    let validation = context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_set_role",
+        Some(encode_args((args.clone(),)).unwrap()),
+    )?;
+    assert!(validation.is_ok());
+
+    let roles = context.update::<CanisterResult<Vec<RoleAssignmentResponse>>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_role",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    assert!(roles.is_ok());
+
+    let roles = context.query::<Vec<RoleAssignmentResponse>>(Sender::Owner, "get_roles", None)?;
+    assert!(roles.len() == 1);
+    assert!(roles[0].principal == operator && roles[0].role == Role::Operator);
+
+    // Only the governance canister is an admin
+    let admin = context.update::<CanisterResult<Vec<RoleAssignmentResponse>>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_role",
+        Some(
+            encode_args((SetRoleArgs {
+                principal: operator,
+                role: Some(Role::Admin),
+            },))
+            .unwrap(),
+        ),
+    )?;
+    assert!(admin.is_err());
+
+    // Operators can not execute operations that require an admin
+    let create_args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Create(CreateNeuronArgs {
+        amount_e8s: 100_010_000,
+        auto_stake: None,
+        dissolve_delay_seconds: None,
+    }));
+    let forbidden = context.update::<CanisterResult<ModuleResponse>>(
+        Sender::Other(operator),
+        "tk_service_manage_neuron",
+        Some(encode_args((create_args,)).unwrap()),
+    )?;
+    assert!(forbidden.is_err());
+
+    let roles = context.update::<CanisterResult<Vec<RoleAssignmentResponse>>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_role",
+        Some(
+            encode_args((SetRoleArgs {
+                principal: operator,
+                role: None,
+            },))
+            .unwrap(),
+        ),
+    )?;
+    assert!(roles.is_ok_and(|roles| roles.is_empty()));
+    Ok(())
+}
+
+#[test]
+fn test_split_neuron() -> Result<(), String> {
+    let context = Context::new();
+
+    context.transfer_icp(
+        10_000_000_000,
+        Account {
+            owner: context.owner_account.owner,
+            subaccount: None,
+        },
+        Account {
+            owner: context.neuron_controller_canister,
+            subaccount: None,
+        },
+    );
+
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Create(CreateNeuronArgs {
+        amount_e8s: 1_000_000_000,
+        auto_stake: None,
+        dissolve_delay_seconds: None,
+    }));
+    let create_neuron = context.update::<CanisterResult<ModuleResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_manage_neuron",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    assert!(create_neuron.is_ok());
+
+    let neuron_references = context
+        .query::<CanisterResult<Vec<NeuronReferenceResponse>>>(
+            Sender::Other(context.config.governance_canister_id),
+            "get_neuron_references",
+            None,
+        )?
+        .unwrap();
+    let parent_subaccount = neuron_references[0].subaccount;
+
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Split(SplitArgs {
+        neuron: NeuronSelector::Subaccount(parent_subaccount),
+        amount_e8s: 950_000_000,
+        memo: None,
+    }));
+    let validation = context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_manage_neuron",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    assert!(validation.is_err());
+
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Split(SplitArgs {
+        neuron: NeuronSelector::Subaccount(parent_subaccount),
+        amount_e8s: 300_000_000,
+        memo: None,
+    }));
+    let validation = context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_manage_neuron",
+        Some(encode_args((args.clone(),)).unwrap()),
+    )?;
+    let summary = validation.unwrap();
+    assert!(summary.starts_with("## Split neuron"));
+    assert!(summary.contains("### Current state"));
+    assert!(summary.contains("| Stake | "));
+    assert!(summary.contains("### Changes"));
+    assert!(summary.contains("### Simulation"));
+    assert!(summary.contains("| Parent stake | 7 ICP |"));
+    assert!(summary.contains("| Child stake | 2.9999 ICP |"));
+
+    let split_neuron = context.update::<CanisterResult<ModuleResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_manage_neuron",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    println!("split_neuron: {:?}", split_neuron);
+    assert!(split_neuron.is_ok());
+
+    let neuron_references = context
+        .query::<CanisterResult<Vec<NeuronReferenceResponse>>>(
+            Sender::Other(context.config.governance_canister_id),
+            "get_neuron_references",
+            None,
+        )?
+        .unwrap();
+    assert!(neuron_references.len() == 2);
+    assert!(neuron_references[1].parent_subaccount == Some(parent_subaccount));
+    assert!(neuron_references[1].neuron_id.is_some());
+
+    let neuron_info = context.update::<CanisterResult<Neuron>>(
+        Sender::Other(context.config.governance_canister_id),
+        "get_full_neuron",
+        Some(encode_args((neuron_references[1].subaccount,)).unwrap()),
+    )?;
+    assert_eq!(
+        neuron_info.unwrap().account,
+        neuron_references[1].subaccount.to_vec()
+    );
+    assert!(neuron_references[1].nonce > neuron_references[0].nonce);
+    Ok(())
+}
+
+#[test]
+fn test_consent_message() -> Result<(), String> {
+    let context = Context::new();
+
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Create(CreateNeuronArgs {
+        amount_e8s: 150_000_000,
+        auto_stake: None,
+        dissolve_delay_seconds: Some(30 * 24 * 60 * 60),
+    }));
+    let request = Icrc21ConsentMessageRequest {
+        method: "tk_service_manage_neuron".to_string(),
+        arg: encode_one(args).unwrap(),
+        user_preferences: Icrc21ConsentMessageSpec {
+            metadata: Icrc21ConsentMessageMetadata {
+                language: "en".to_string(),
+                utc_offset_minutes: None,
+            },
+            device_spec: None,
+        },
+    };
+
+    let response = context.update::<Icrc21ConsentMessageResponse>(
+        Sender::Owner,
+        "icrc21_canister_call_consent_message",
+        Some(encode_args((request,)).unwrap()),
+    )?;
+    let Icrc21ConsentMessageResponse::Ok(info) = response else {
+        return Err(format!("Unexpected response: {:?}", response));
+    };
+    let Icrc21ConsentMessage::GenericDisplayMessage(message) = info.consent_message else {
+        return Err("Expected a generic display message".to_string());
+    };
+    assert!(message.contains("1.5 ICP"));
+    assert!(message.contains("30 days"));
+
+    // Raw commands that move funds show their payload
+    let new_controller = generate_principal();
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::Command(CommandNeuronArgs {
+        neuron: NeuronSelector::Subaccount([1; 32]),
+        command: ManageNeuronCommandRequest::Spawn(Spawn {
+            percentage_to_spawn: Some(50),
+            new_controller: Some(new_controller),
+            nonce: None,
+        }),
+    }));
+    let request = Icrc21ConsentMessageRequest {
+        method: "tk_service_manage_neuron".to_string(),
+        arg: encode_one(args).unwrap(),
+        user_preferences: Icrc21ConsentMessageSpec {
+            metadata: Icrc21ConsentMessageMetadata {
+                language: "en".to_string(),
+                utc_offset_minutes: None,
+            },
+            device_spec: None,
+        },
+    };
+    let response = context.update::<Icrc21ConsentMessageResponse>(
+        Sender::Owner,
+        "icrc21_canister_call_consent_message",
+        Some(encode_args((request,)).unwrap()),
+    )?;
+    let Icrc21ConsentMessageResponse::Ok(info) = response else {
+        return Err(format!("Unexpected response: {:?}", response));
+    };
+    let Icrc21ConsentMessage::GenericDisplayMessage(message) = info.consent_message else {
+        return Err("Expected a generic display message".to_string());
+    };
+    assert!(message.contains("**Spawn**"));
+    assert!(message.contains("50%"));
+    assert!(message.contains(&new_controller.to_string()));
+    Ok(())
+}
+
+#[test]
+fn test_voting_rules() -> Result<(), String> {
+    let context = Context::new();
+    let rules = vec![
+        VotingRule {
+            topic: Some(Topic::Governance),
+            proposer: None,
+            action: VotingAction::Follow(0),
+        },
+        VotingRule {
+            topic: None,
+            proposer: None,
+            action: VotingAction::Manual,
+        },
+    ];
+
+    let validation = context.update::<Result<String, String>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_validate_set_voting_rules",
+        Some(encode_args((rules.clone(),)).unwrap()),
+    )?;
+    assert!(validation.is_err());
+
+    let mut rules = rules;
+    rules[0].action = VotingAction::Reject;
+
+    // Only the governance canister can set the rules
+    let updated = context.update::<CanisterResult<Vec<VotingRule>>>(
+        Sender::Owner,
+        "tk_service_set_voting_rules",
+        Some(encode_args((rules.clone(),)).unwrap()),
+    )?;
+    assert!(updated.is_err());
+
+    let updated = context.update::<CanisterResult<Vec<VotingRule>>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_voting_rules",
+        Some(encode_args((rules,)).unwrap()),
+    )?;
+    assert!(updated.is_ok());
+
+    let rules = context.query::<Vec<VotingRule>>(Sender::Owner, "get_voting_rules", None)?;
+    assert!(rules.len() == 2);
+    assert!(matches!(rules[0].action, VotingAction::Reject));
+
+    let decisions = context.query::<VoteDecisionsResponse>(
+        Sender::Owner,
+        "get_vote_decisions",
+        Some(encode_one(GetVoteDecisionsArgs::default()).unwrap()),
+    )?;
+    assert!(decisions.decisions.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_voting_rules_cast_and_record_votes() -> Result<(), String> {
+    let context = Context::new();
+    let proposer = create_icp_neuron(&context, 10_000_000_000, Some(255_000_000))?;
+    let voter = create_icp_neuron(&context, 1_000_000_000, Some(255_000_000))?;
+    let voter_id = voter.neuron_id.ok_or("Voter has no neuron id")?;
+
+    let rules = vec![VotingRule {
+        topic: Some(Topic::Governance),
+        proposer: None,
+        action: VotingAction::Reject,
+    }];
+    let updated = context.update::<CanisterResult<Vec<VotingRule>>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_set_voting_rules",
+        Some(encode_args((rules,)).unwrap()),
+    )?;
+    assert!(updated.is_ok());
+
+    // The proposer votes yes when it makes the proposal, only the other neuron is left to vote
+    let args: NeuronType = NeuronType::Icp(IcpNeuronArgs::CreateProposal(CreateProposalArgs {
+        neuron: NeuronSelector::Subaccount(proposer.subaccount),
+        proposal: MakeProposalRequest {
+            title: Some("Test proposal".to_string()),
+            summary: "Rejected by the voting rules".to_string(),
+            action: Some(ProposalActionRequest::Motion(Motion {
+                motion_text: "Reject something".to_string(),
+            })),
+            url: "".to_string(),
+        },
+    }));
+    let proposal = context.update::<CanisterResult<ModuleResponse>>(
+        Sender::Other(context.config.governance_canister_id),
+        "tk_service_manage_neuron",
+        Some(encode_args((args,)).unwrap()),
+    )?;
+    assert!(proposal.is_ok());
+
+    context.pic.advance_time(Duration::from_secs(15 * 60));
+    tick(&context, 10);
+
+    let decisions = context.query::<VoteDecisionsResponse>(
+        Sender::Owner,
+        "get_vote_decisions",
+        Some(
+            encode_one(GetVoteDecisionsArgs {
+                neuron_id: Some(voter_id),
+                ..Default::default()
+            })
+            .unwrap(),
+        ),
+    )?;
+    assert!(decisions.decisions.len() == 1);
+    assert!(matches!(decisions.decisions[0].vote, Some(Vote::Reject)));
+
+    // The vote went through the same recording path as a vote operation
+    let audit_records = context.query::<AuditRecordsResponse>(
+        Sender::Other(context.config.governance_canister_id),
+        "get_audit_records",
+        Some(encode_args((GetAuditRecordsArgs::default(),)).unwrap()),
+    )?;
+    assert!(audit_records
+        .records
+        .iter()
+        .any(|record| record.operation == "vote"
+            && record.caller == context.neuron_controller_canister
+            && record.neuron_key == Some(voter.subaccount)
+            && record.result.is_ok()));
+
+    let blocks = context.query::<GetBlocksResult>(
+        Sender::Owner,
+        "icrc3_get_blocks",
+        Some(
+            encode_args((vec![GetBlocksArgs {
+                start: Nat::from(0u64),
+                length: Nat::from(10u64),
+            }],))
+            .unwrap(),
+        ),
+    )?;
+    // The two created neurons and the vote, proposals are not part of the block log
+    assert!(blocks.log_length == Nat::from(3u64));
+    Ok(())
+}
+
+#[test]
+fn test_merge_neurons_does_not_reuse_nonce() -> Result<(), String> {
+    let context = Context::new();
+
+    let target = create_icp_neuron(&context, 200_000_000, None)?;
+    let source = create_icp_neuron(&context, 200_000_000, None)?;
+
+    manage_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::Merge(MergeArgs {
+            target: NeuronSelector::Subaccount(target.subaccount),
+            source: NeuronSelector::Subaccount(source.subaccount),
+        })),
+    )?;
+    let merged = get_full_neuron(&context, target.subaccount)?;
+    assert!(merged.cached_neuron_stake_e8s > 200_000_000);
+
+    // The source is no longer managed and can not be found by its subaccount or neuron id
+    let references = context
+        .query::<CanisterResult<Vec<NeuronReferenceResponse>>>(
+            Sender::Owner,
+            "get_neuron_references",
+            None,
+        )?
+        .unwrap();
+    assert!(references
+        .iter()
+        .all(|reference| reference.subaccount != source.subaccount));
+    assert!(get_full_neuron(&context, source.subaccount).is_err());
+    let by_neuron_id = context.query::<CanisterResult<NeuronReferenceResponse>>(
+        Sender::Owner,
+        "get_neuron_reference_by_neuron_id",
+        Some(encode_args((source.neuron_id.unwrap(),)).unwrap()),
+    )?;
+    assert!(by_neuron_id.is_err());
+
+    // The source held the highest key, a new neuron must not get its nonce
+    let created = create_icp_neuron(&context, 200_000_000, None)?;
+    assert!(created.nonce > source.nonce);
+    assert_ne!(created.subaccount, source.subaccount);
+    Ok(())
+}
+
+#[test]
+fn test_merge_validation() -> Result<(), String> {
+    let context = Context::new();
+
+    let target = create_icp_neuron(&context, 200_000_000, Some(15_778_800))?;
+    let source = create_icp_neuron(&context, 200_000_000, Some(15_778_800))?;
+    assert!(validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::Merge(MergeArgs {
+            target: NeuronSelector::Subaccount(target.subaccount),
+            source: NeuronSelector::Subaccount(source.subaccount),
+        })),
+    )
+    .is_ok());
+
+    // A neuron that is not controlled by this canister can not be merged
+    let (_, foreign_subaccount) =
+        context.stake_icp_neuron(context.owner_account.owner, 1_000_000, 200_000_000)?;
+    assert!(validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::Merge(MergeArgs {
+            target: NeuronSelector::Subaccount(target.subaccount),
+            source: NeuronSelector::Subaccount(foreign_subaccount),
+        })),
+    )
+    .is_err());
+
+    // A dissolving neuron can not be merged into a non-dissolving one
+    manage_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::SetDissolveState(SetDissolveStateArgs {
+            neuron: NeuronSelector::Subaccount(source.subaccount),
+            start_dissolving: true,
+        })),
+    )?;
+    let validation = validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::Merge(MergeArgs {
+            target: NeuronSelector::Subaccount(target.subaccount),
+            source: NeuronSelector::Subaccount(source.subaccount),
+        })),
+    );
+    assert!(validation.is_err_and(|e| e.contains("non-dissolving")));
+    Ok(())
+}
+
+#[test]
+fn test_disburse_to_account() -> Result<(), String> {
+    let context = Context::new();
+    let recipient = Account {
+        owner: generate_principal(),
+        subaccount: Some([7; 32]),
+    };
+    let args = NeuronType::Icp(IcpNeuronArgs::Disburse(DisburseArgs {
+        neuron: NeuronSelector::Subaccount(
+            create_icp_neuron(&context, 300_000_000, None)?.subaccount,
+        ),
+        amount_e8s: Some(100_000_000),
+        destination: Some(DisburseDestination::Account(recipient)),
+    }));
+
+    // The summary shows the account the ICP is sent to
+    let summary = validate_neuron(&context, args.clone())?;
+    let destination = AccountIdentifier::new(&recipient.owner, &Subaccount([7; 32]));
+    assert!(summary.contains(&destination.to_hex()));
+
+    // A neuron without a dissolve delay is dissolved, governance takes the fee from the amount
+    manage_neuron(&context, args)?;
+    let balance = context.get_icp_account_balance(recipient)?;
+    assert_eq!(balance, Nat::from(100_000_000 - 10_000u64));
+    Ok(())
+}
+
+#[test]
+fn test_disburse_validation_rejects_a_neuron_that_is_not_dissolved() -> Result<(), String> {
+    let context = Context::new();
+    let neuron = create_icp_neuron(&context, 300_000_000, Some(15_778_800))?;
+
+    let validation = validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::Disburse(DisburseArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            amount_e8s: None,
+            destination: None,
+        })),
+    );
+    assert!(validation.is_err_and(|e| e.contains("not fully dissolved")));
+    Ok(())
+}
+
+#[test]
+fn test_neuron_selector_by_neuron_id() -> Result<(), String> {
+    let context = Context::new();
+    let auto_stake = |neuron: NeuronSelector, auto_stake: bool| {
+        NeuronType::Icp(IcpNeuronArgs::AutoStake(AutoStakeArgs {
+            neuron,
+            auto_stake,
+        }))
+    };
+
+    // The neuron id is indexed by the update that stores it after the claim
+    let neuron = create_icp_neuron(&context, 200_000_000, None)?;
+    let by_id = NeuronSelector::NeuronId(neuron.neuron_id.unwrap());
+    manage_neuron(&context, auto_stake(by_id, true))?;
+    let full_neuron = get_full_neuron(&context, neuron.subaccount)?;
+    assert_eq!(full_neuron.auto_stake_maturity, Some(true));
+
+    // The indexes are rebuilt on upgrade
+    context.upgrade()?;
+    assert!(validate_neuron(&context, auto_stake(by_id, false)).is_ok());
+
+    // A removed reference is no longer found by either selector
+    let target = create_icp_neuron(&context, 200_000_000, None)?;
+    manage_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::Merge(MergeArgs {
+            target: NeuronSelector::Subaccount(target.subaccount),
+            source: by_id,
+        })),
+    )?;
+    for selector in [by_id, NeuronSelector::Subaccount(neuron.subaccount)] {
+        assert!(validate_neuron(&context, auto_stake(selector, false)).is_err());
+        assert!(manage_neuron(&context, auto_stake(selector, false)).is_err());
+    }
+    Ok(())
+}
+
+#[test]
+fn test_maturity_validation() -> Result<(), String> {
+    let context = Context::new();
+    let neuron = create_icp_neuron(&context, 200_000_000, None)?;
+
+    for percentage in [0, 101] {
+        let stake = validate_neuron(
+            &context,
+            NeuronType::Icp(IcpNeuronArgs::StakeMaturity(StakeMaturityArgs {
+                neuron: NeuronSelector::Subaccount(neuron.subaccount),
+                percentage,
+            })),
+        );
+        assert!(stake.is_err());
+
+        let disburse = validate_neuron(
+            &context,
+            NeuronType::Icp(IcpNeuronArgs::DisburseMaturity(DisburseMaturityArgs {
+                neuron: NeuronSelector::Subaccount(neuron.subaccount),
+                percentage,
+                to_account: None,
+            })),
+        );
+        assert!(disburse.is_err());
+    }
+
+    // A new neuron has no maturity yet
+    let stake = validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::StakeMaturity(StakeMaturityArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            percentage: 50,
+        })),
+    );
+    assert!(stake.unwrap_err().contains("no maturity"));
+
+    let disburse = validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::DisburseMaturity(DisburseMaturityArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            percentage: 100,
+            to_account: None,
+        })),
+    );
+    assert!(disburse.unwrap_err().contains("at least 1 ICP"));
+    Ok(())
+}
+
+#[test]
+fn test_hot_keys() -> Result<(), String> {
+    let context = Context::new();
+    let neuron = create_icp_neuron(&context, 200_000_000, None)?;
+    let hot_key = generate_principal();
+
+    let invalid = validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::AddHotKey(HotKeyArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            hot_key: Principal::anonymous(),
+        })),
+    );
+    assert!(invalid.is_err());
+
+    manage_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::AddHotKey(HotKeyArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            hot_key,
+        })),
+    )?;
+    let hot_keys = context.query::<CanisterResult<Vec<Principal>>>(
+        Sender::Other(context.config.governance_canister_id),
+        "get_hot_keys",
+        Some(encode_args((neuron.subaccount,)).unwrap()),
+    )?;
+    assert_eq!(hot_keys.unwrap(), vec![hot_key]);
+    assert_eq!(
+        get_full_neuron(&context, neuron.subaccount)?.hot_keys,
+        vec![hot_key]
+    );
+
+    manage_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::RemoveHotKey(HotKeyArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            hot_key,
+        })),
+    )?;
+    let hot_keys = context.query::<CanisterResult<Vec<Principal>>>(
+        Sender::Other(context.config.governance_canister_id),
+        "get_hot_keys",
+        Some(encode_args((neuron.subaccount,)).unwrap()),
+    )?;
+    assert!(hot_keys.unwrap().is_empty());
+    assert!(get_full_neuron(&context, neuron.subaccount)?
+        .hot_keys
+        .is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_set_following_is_all_or_nothing() -> Result<(), String> {
+    let context = Context::new();
+    let neuron = create_icp_neuron(&context, 200_000_000, None)?;
+    let followee = create_icp_neuron(&context, 200_000_000, None)?
+        .neuron_id
+        .unwrap();
+
+    let missing_followee = validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::SetFollowing(SetFollowingArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            following: vec![FollowingArgs {
+                topic: Topic::Governance,
+                followees: vec![followee + 1_000_000],
+            }],
+        })),
+    );
+    assert!(missing_followee.is_err());
+
+    // Governance rejects more than 15 followees, which fails the whole change
+    let failed = manage_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::SetFollowing(SetFollowingArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            following: vec![
+                FollowingArgs {
+                    topic: Topic::Governance,
+                    followees: vec![followee],
+                },
+                FollowingArgs {
+                    topic: Topic::NetworkEconomics,
+                    followees: vec![followee; 16],
+                },
+            ],
+        })),
+    );
+    assert!(failed.is_err());
+    assert!(get_full_neuron(&context, neuron.subaccount)?
+        .followees
+        .is_empty());
+
+    let following = manage_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::SetFollowing(SetFollowingArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            following: vec![
+                FollowingArgs {
+                    topic: Topic::Governance,
+                    followees: vec![followee],
+                },
+                FollowingArgs {
+                    topic: Topic::NetworkEconomics,
+                    followees: vec![followee],
+                },
+            ],
+        })),
+    )?;
+    let ModuleResponse::Following(following) = following else {
+        return Err(format!("Unexpected response: {:?}", following));
+    };
+    assert_eq!(following.len(), 2);
+    assert!(following
+        .iter()
+        .all(|topic| topic.followees == vec![followee]));
+    Ok(())
+}
+
+#[test]
+fn test_validation_simulates_the_resulting_neuron() -> Result<(), String> {
+    let context = Context::new();
+    let neuron = create_icp_neuron(&context, 200_000_000, Some(15_552_000))?;
+    let followee = create_icp_neuron(&context, 200_000_000, None)?
+        .neuron_id
+        .unwrap();
+
+    let summary = validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::AddDissolveDelay(AddDissolveDelayArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            dissolve_delay_seconds: 864_000,
+        })),
+    )?;
+    let (_, simulation) = summary.split_once("### Simulation").unwrap();
+    assert!(simulation.contains("| Dissolve state | Not dissolving, 190 days delay |"));
+
+    let summary = validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::SetDissolveState(SetDissolveStateArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            start_dissolving: true,
+        })),
+    )?;
+    let (_, simulation) = summary.split_once("### Simulation").unwrap();
+    assert!(simulation.contains("| Dissolve state | Dissolving, 180 days left |"));
+
+    let summary = validate_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::SetFollowing(SetFollowingArgs {
+            neuron: NeuronSelector::Subaccount(neuron.subaccount),
+            following: vec![FollowingArgs {
+                topic: Topic::Governance,
+                followees: vec![followee],
+            }],
+        })),
+    )?;
+    let (_, simulation) = summary.split_once("### Simulation").unwrap();
+    assert!(simulation.contains(&format!("| Followees | Governance: {} |", followee)));
+    Ok(())
+}
+
+#[test]
+fn test_voting_power_refresh_is_restored_after_upgrade() -> Result<(), String> {
+    let context = Context::new();
+    create_icp_neuron(&context, 200_000_000, None)?;
+
+    // A new neuron is refreshed right away, which schedules the next refresh
+    tick(&context, 5);
+    let refreshes = get_voting_power_refreshes(&context)?;
+    assert_eq!(refreshes.len(), 1);
+    assert!(refreshes[0].last_error.is_none());
+    let last_run_at = refreshes[0].last_run_at.unwrap();
+    let next_run_at = refreshes[0].next_run_at;
+    assert!(next_run_at > last_run_at + 100 * 24 * 60 * 60 * 1_000_000_000);
+
+    context.upgrade()?;
+    let refreshes = get_voting_power_refreshes(&context)?;
+    assert_eq!(refreshes[0].last_run_at, Some(last_run_at));
+    assert_eq!(refreshes[0].next_run_at, next_run_at);
+
+    let now = context.pic.get_time().as_nanos_since_unix_epoch();
+    context
+        .pic
+        .advance_time(Duration::from_nanos(next_run_at - now + 1_000_000_000));
+    tick(&context, 5);
+    let refreshes = get_voting_power_refreshes(&context)?;
+    assert!(refreshes[0].last_run_at.unwrap() >= next_run_at);
+    assert!(refreshes[0].next_run_at > next_run_at);
+    Ok(())
+}
+
+#[test]
+fn test_refresh_stake_after_external_top_up() -> Result<(), String> {
+    let context = Context::new();
+    let neuron = create_icp_neuron(&context, 200_000_000, None)?;
+
+    // Sent straight to the stake account, without going through this canister
+    context.transfer_icp(
+        300_000_000,
+        Account {
+            owner: context.owner_account.owner,
+            subaccount: None,
+        },
+        Account {
+            owner: MAINNET_GOVERNANCE_CANISTER_ID,
+            subaccount: Some(neuron.subaccount),
+        },
+    );
+    assert_eq!(
+        get_full_neuron(&context, neuron.subaccount)?.cached_neuron_stake_e8s,
+        200_000_000
+    );
+
+    context.pic.advance_time(Duration::from_secs(6 * 60 * 60));
+    tick(&context, 5);
+    assert_eq!(
+        get_full_neuron(&context, neuron.subaccount)?.cached_neuron_stake_e8s,
+        500_000_000
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reconcile_imports_unknown_neurons() -> Result<(), String> {
+    let context = Context::new();
+    let known = create_icp_neuron(&context, 200_000_000, None)?;
+    let (unknown_id, unknown_subaccount) =
+        context.stake_icp_neuron(context.neuron_controller_canister, 1_000_000, 200_000_000)?;
+
+    let report = get_reconcile_report(&context)?;
+    assert_eq!(report.matched, vec![known.neuron_id.unwrap()]);
+    assert_eq!(report.unknown, vec![unknown_id]);
+    assert!(report.imported.is_empty());
+    assert!(report.missing.is_empty());
+
+    let reconciled = manage_neuron(&context, NeuronType::Icp(IcpNeuronArgs::Reconcile))?;
+    let ModuleResponse::Reconcile(report) = reconciled else {
+        return Err(format!("Unexpected response: {:?}", reconciled));
+    };
+    assert_eq!(report.imported.len(), 1);
+    assert_eq!(report.imported[0].neuron_id, Some(unknown_id));
+    assert_eq!(report.imported[0].subaccount, unknown_subaccount);
+
+    // The imported neuron is managed like any other neuron
+    let imported = get_full_neuron(&context, unknown_subaccount)?;
+    assert_eq!(imported.cached_neuron_stake_e8s, 200_000_000);
+
+    let report = get_reconcile_report(&context)?;
+    assert_eq!(report.matched.len(), 2);
+    assert!(report.unknown.is_empty());
+    assert!(report.missing.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_reconcile_skips_merged_neurons() -> Result<(), String> {
+    let context = Context::new();
+    let target = create_icp_neuron(&context, 200_000_000, None)?;
+    let source = create_icp_neuron(&context, 200_000_000, None)?;
+
+    manage_neuron(
+        &context,
+        NeuronType::Icp(IcpNeuronArgs::Merge(MergeArgs {
+            target: NeuronSelector::Subaccount(target.subaccount),
+            source: NeuronSelector::Subaccount(source.subaccount),
+        })),
+    )?;
+
+    // The emptied source still exists on governance but is not imported again
+    let reconciled = manage_neuron(&context, NeuronType::Icp(IcpNeuronArgs::Reconcile))?;
+    let ModuleResponse::Reconcile(report) = reconciled else {
+        return Err(format!("Unexpected response: {:?}", reconciled));
+    };
+    assert_eq!(report.matched, vec![target.neuron_id.unwrap()]);
+    assert!(report.unknown.is_empty());
+    assert!(report.imported.is_empty());
+
+    let refreshes = get_voting_power_refreshes(&context)?;
+    assert!(refreshes
+        .iter()
+        .all(|refresh| refresh.neuron_reference_id != source.storage_reference_id));
+    Ok(())
+}
+
+#[test]
+fn test_sns_create_and_claim_neuron() -> Result<(), String> {
+    let context = Context::new_with_sns();
+
+    let neuron = create_sns_neuron(&context)?;
+    assert!(neuron.neuron_id.is_some());
+
+    let full_neuron = get_full_sns_neuron(&context, neuron.subaccount)?;
+    assert!(full_neuron.cached_neuron_stake_e8s == 1_000_000_000);
+
+    // Tokens sent to the neuron by someone else are added to the stake by a claim
+    context.mint_sns(500_000_000, neuron.topup_account);
+    let args: NeuronType = NeuronType::Sns(SnsNeuronArgs::Claim(ClaimSnsNeuronArgs {
+        subaccount: neuron.subaccount,
+    }));
+    manage_neuron(&context, args)?;
+
+    let full_neuron = get_full_sns_neuron(&context, neuron.subaccount)?;
+    assert!(full_neuron.cached_neuron_stake_e8s == 1_500_000_000);
+    Ok(())
+}
+
+#[test]
+fn test_sns_concurrent_creates_use_distinct_subaccounts() -> Result<(), String> {
+    let context = Context::new_with_sns();
+    let args: NeuronType = NeuronType::Sns(SnsNeuronArgs::Create(CreateSnsNeuronArgs {
+        amount_e8s: 1_000_000_000,
+        dissolve_delay_seconds: None,
+    }));
+
+    // Both creations are in flight before either of the stake transfers completes
+    let message_ids = (0..2)
+        .map(|_| {
+            context
+                .pic
+                .submit_call(
+                    context.neuron_controller_canister,
+                    context.config.governance_canister_id,
+                    "tk_service_manage_neuron",
+                    encode_args((args.clone(),)).unwrap(),
+                )
+                .map_err(|e| format!("{:?}", e))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    let mut subaccounts = vec![];
+    for message_id in message_ids {
+        let response = context
+            .pic
+            .await_call(message_id)
+            .map_err(|e| format!("{:?}", e))?;
+        match decode_one::<CanisterResult<ModuleResponse>>(&response).map_err(|e| e.to_string())? {
+            Ok(ModuleResponse::SnsNeuron(neuron)) => {
+                assert!(neuron.neuron_id.is_some());
+                subaccounts.push(neuron.subaccount);
+            }
+            result => return Err(format!("Unexpected response: {:?}", result)),
+        }
+    }
+    assert_ne!(subaccounts[0], subaccounts[1]);
+
+    for subaccount in subaccounts {
+        let full_neuron = get_full_sns_neuron(&context, subaccount)?;
+        assert_eq!(full_neuron.cached_neuron_stake_e8s, 1_000_000_000);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_sns_set_following_is_all_or_nothing() -> Result<(), String> {
+    let context = Context::new_with_sns();
+    let follower = create_sns_neuron(&context)?;
+    let followee = create_sns_neuron(&context)?.neuron_id.unwrap();
+
+    // The unknown function fails after the first function was updated
+    let args: NeuronType = NeuronType::Sns(SnsNeuronArgs::SetFollowing(SetSnsFollowingArgs {
+        subaccount: follower.subaccount,
+        following: vec![
+            SnsFollowingArgs {
+                function_id: 1,
+                followees: vec![followee],
+            },
+            SnsFollowingArgs {
+                function_id: 999_999,
+                followees: vec![followee],
+            },
+        ],
+    }));
+    assert!(manage_neuron(&context, args).is_err());
+
+    let full_neuron = get_full_sns_neuron(&context, follower.subaccount)?;
+    assert!(full_neuron
+        .followees
+        .iter()
+        .all(|(_, followees)| followees.followees.is_empty()));
+
+    let args: NeuronType = NeuronType::Sns(SnsNeuronArgs::SetFollowing(SetSnsFollowingArgs {
+        subaccount: follower.subaccount,
+        following: vec![
+            SnsFollowingArgs {
+                function_id: 0,
+                followees: vec![followee],
+            },
+            SnsFollowingArgs {
+                function_id: 1,
+                followees: vec![followee],
+            },
+        ],
+    }));
+    manage_neuron(&context, args)?;
+
+    let full_neuron = get_full_sns_neuron(&context, follower.subaccount)?;
+    assert!(full_neuron.followees.len() == 2);
+    Ok(())
+}
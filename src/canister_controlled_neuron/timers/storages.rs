@@ -5,6 +5,8 @@ use crate::traits::timer_traits::{Timer, TimerActions};
 
 thread_local! {
     pub static NEURON_TIMERS: RefCell<HashMap<[u8; 32], TimerId>> = RefCell::new(HashMap::default());
+    pub static JOB_TIMERS: RefCell<HashMap<String, TimerId>> = RefCell::new(HashMap::default());
+    pub static OPERATION_TIMERS: RefCell<HashMap<u64, TimerId>> = RefCell::new(HashMap::default());
     pub static COUNTER: RefCell<u64> = const { RefCell::new(0) };
 }
 
@@ -19,3 +21,29 @@ impl Timer<[u8; 32]> for NeuronTimers {
 }
 
 impl TimerActions<[u8; 32]> for NeuronTimers {}
+
+/// Timers for canister-wide background jobs, keyed by job name.
+pub struct JobTimers;
+
+impl Timer<String> for JobTimers {
+    const NAME: &'static str = "job_timers";
+
+    fn with_timer<R>(f: impl FnOnce(&RefCell<HashMap<String, TimerId>>) -> R) -> R {
+        JOB_TIMERS.with(f)
+    }
+}
+
+impl TimerActions<String> for JobTimers {}
+
+/// Timers of queued operations, keyed by queued operation id.
+pub struct OperationTimers;
+
+impl Timer<u64> for OperationTimers {
+    const NAME: &'static str = "operation_timers";
+
+    fn with_timer<R>(f: impl FnOnce(&RefCell<HashMap<u64, TimerId>>) -> R) -> R {
+        OPERATION_TIMERS.with(f)
+    }
+}
+
+impl TimerActions<u64> for OperationTimers {}
@@ -0,0 +1,101 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+use super::modules::ModuleResponse;
+
+impl_versioned_storable_for!(AuditRecord);
+
+/// How a call from the governance canister was matched to the adopted proposal executing it.
+/// Proposals are matched on their payload, so proposals with the same payload can not be told
+/// apart.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum ProposalMatch {
+    Found(u64),
+    /// All proposals that have the executed payload and are not executed yet.
+    Ambiguous(Vec<u64>),
+    /// No proposal has the executed payload, or the proposals could not be listed.
+    NotFound,
+}
+
+impl ProposalMatch {
+    pub fn proposal_id(&self) -> Option<u64> {
+        match self {
+            ProposalMatch::Found(proposal_id) => Some(*proposal_id),
+            _ => None,
+        }
+    }
+}
+
+/// An append-only record of a `tk_service_manage_neuron` call. `args` holds the candid encoded
+/// `NeuronType` exactly as it was executed. `proposal_match` is only set for calls from the
+/// governance canister, `proposal_id` only when that match was unambiguous.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct AuditRecord {
+    pub timestamp: u64,
+    pub caller: Principal,
+    pub operation: String,
+    pub neuron_key: Option<[u8; 32]>,
+    pub args: Vec<u8>,
+    pub result: Result<ModuleResponse, String>,
+    pub proposal_id: Option<u64>,
+    pub proposal_match: Option<ProposalMatch>,
+}
+
+impl AuditRecord {
+    pub fn to_response(&self, id: u64) -> AuditRecordResponse {
+        AuditRecordResponse {
+            id,
+            timestamp: self.timestamp,
+            caller: self.caller,
+            operation: self.operation.clone(),
+            neuron_key: self.neuron_key,
+            args: self.args.clone(),
+            result: self.result.clone(),
+            proposal_id: self.proposal_id,
+            proposal_match: self.proposal_match.clone(),
+        }
+    }
+}
+
+impl VersionedStorable for AuditRecord {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+/// All filters are optional, `from_timestamp` and `to_timestamp` are inclusive. Records are
+/// returned in insertion order starting at `start`, pass the returned `next` to get the next page.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, Default)]
+pub struct GetAuditRecordsArgs {
+    pub neuron_key: Option<[u8; 32]>,
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
+    pub start: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct AuditRecordResponse {
+    pub id: u64,
+    pub timestamp: u64,
+    pub caller: Principal,
+    pub operation: String,
+    pub neuron_key: Option<[u8; 32]>,
+    pub args: Vec<u8>,
+    pub result: Result<ModuleResponse, String>,
+    pub proposal_id: Option<u64>,
+    pub proposal_match: Option<ProposalMatch>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct AuditRecordsResponse {
+    pub records: Vec<AuditRecordResponse>,
+    pub next: Option<u64>,
+}
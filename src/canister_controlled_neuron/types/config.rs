@@ -1,20 +1,70 @@
+use std::borrow::Cow;
+
 use candid::{CandidType, Principal};
+use ic_ledger_types::{MAINNET_GOVERNANCE_CANISTER_ID, MAINNET_LEDGER_CANISTER_ID};
+use ic_stable_structures::Storable;
 use serde::{Deserialize, Serialize};
-use toolkit_utils::impl_storable_for;
 
-impl_storable_for!(Config);
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+use super::legacy::ConfigV0;
+
+impl_versioned_storable_for!(Config);
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct Config {
     pub governance_canister_id: Principal,
     pub sns_ledger_canister_id: Principal,
+    pub icp_governance_canister_id: Principal,
+    pub icp_ledger_canister_id: Principal,
 }
 
 impl Config {
-    pub fn new(governance_canister_id: Principal, sns_ledger_canister_id: Principal) -> Self {
+    pub fn new(
+        governance_canister_id: Principal,
+        sns_ledger_canister_id: Principal,
+        icp_governance_canister_id: Option<Principal>,
+        icp_ledger_canister_id: Option<Principal>,
+    ) -> Self {
         Self {
             governance_canister_id,
             sns_ledger_canister_id,
+            icp_governance_canister_id: icp_governance_canister_id
+                .unwrap_or(MAINNET_GOVERNANCE_CANISTER_ID),
+            icp_ledger_canister_id: icp_ledger_canister_id.unwrap_or(MAINNET_LEDGER_CANISTER_ID),
+        }
+    }
+}
+
+impl VersionedStorable for Config {
+    const VERSION: u8 = 2;
+
+    fn migrate(version: u8, bytes: &[u8]) -> Self {
+        match version {
+            // Version 1 only added the version prefix, the payload is the same as version 0
+            0 | 1 => ConfigV0::from_bytes(Cow::Borrowed(bytes)).into(),
+            _ => unsupported_version(version),
         }
     }
 }
+
+/// The ICP canister ids are optional and default to the mainnet NNS canisters.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct InitArgs {
+    pub governance_canister_id: Principal,
+    pub sns_ledger_canister_id: Principal,
+    pub icp_governance_canister_id: Option<Principal>,
+    pub icp_ledger_canister_id: Option<Principal>,
+}
+
+/// Only the fields that are set are changed.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct UpdateConfigArgs {
+    pub governance_canister_id: Option<Principal>,
+    pub sns_ledger_canister_id: Option<Principal>,
+    pub icp_governance_canister_id: Option<Principal>,
+    pub icp_ledger_canister_id: Option<Principal>,
+}
@@ -0,0 +1,60 @@
+use std::borrow::Cow;
+
+use candid::{CandidType, Principal};
+use ic_stable_structures::Storable;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+use super::config::Config;
+
+impl_versioned_storable_for!(ConfigChange);
+
+/// An audit entry written for every config update. The configs are kept as their versioned
+/// encoding, so old entries are decoded through the migrations of `Config` when it changes.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ConfigChange {
+    pub timestamp: u64,
+    pub caller: Principal,
+    pub previous: Vec<u8>,
+    pub updated: Vec<u8>,
+}
+
+impl ConfigChange {
+    pub fn new(timestamp: u64, caller: Principal, previous: &Config, updated: &Config) -> Self {
+        Self {
+            timestamp,
+            caller,
+            previous: previous.to_bytes().into_owned(),
+            updated: updated.to_bytes().into_owned(),
+        }
+    }
+
+    pub fn to_response(&self) -> ConfigChangeResponse {
+        ConfigChangeResponse {
+            timestamp: self.timestamp,
+            caller: self.caller,
+            previous: Config::from_bytes(Cow::Borrowed(&self.previous)),
+            updated: Config::from_bytes(Cow::Borrowed(&self.updated)),
+        }
+    }
+}
+
+impl VersionedStorable for ConfigChange {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ConfigChangeResponse {
+    pub timestamp: u64,
+    pub caller: Principal,
+    pub previous: Config,
+    pub updated: Config,
+}
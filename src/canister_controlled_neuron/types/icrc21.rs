@@ -0,0 +1,69 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Icrc21ConsentMessageRequest {
+    pub method: String,
+    pub arg: Vec<u8>,
+    pub user_preferences: Icrc21ConsentMessageSpec,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Icrc21ConsentMessageSpec {
+    pub metadata: Icrc21ConsentMessageMetadata,
+    pub device_spec: Option<Icrc21DeviceSpec>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Icrc21ConsentMessageMetadata {
+    pub language: String,
+    pub utc_offset_minutes: Option<i16>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum Icrc21DeviceSpec {
+    GenericDisplay,
+    LineDisplay {
+        characters_per_line: u16,
+        lines_per_page: u16,
+    },
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Icrc21LineDisplayPage {
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum Icrc21ConsentMessage {
+    GenericDisplayMessage(String),
+    LineDisplayMessage { pages: Vec<Icrc21LineDisplayPage> },
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Icrc21ConsentInfo {
+    pub consent_message: Icrc21ConsentMessage,
+    pub metadata: Icrc21ConsentMessageMetadata,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Icrc21ErrorInfo {
+    pub description: String,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum Icrc21Error {
+    UnsupportedCanisterCall(Icrc21ErrorInfo),
+    ConsentMessageUnavailable(Icrc21ErrorInfo),
+    InsufficientPayment(Icrc21ErrorInfo),
+    GenericError {
+        error_code: Nat,
+        description: String,
+    },
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum Icrc21ConsentMessageResponse {
+    Ok(Icrc21ConsentInfo),
+    Err(Icrc21Error),
+}
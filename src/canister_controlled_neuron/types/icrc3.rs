@@ -0,0 +1,122 @@
+use candid::{CandidType, Int, Nat, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+impl_versioned_storable_for!(Block);
+
+/// The ICRC-3 generic value, blocks are returned and hashed in this representation.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum Icrc3Value {
+    Blob(Vec<u8>),
+    Text(String),
+    Nat(Nat),
+    Int(Int),
+    Array(Vec<Icrc3Value>),
+    Map(Vec<(String, Icrc3Value)>),
+}
+
+/// A neuron operation in the block log. `parent_hash` is the hash of the previous block and is
+/// only missing for the first block.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Block {
+    pub block_type: String,
+    pub timestamp: u64,
+    pub parent_hash: Option<[u8; 32]>,
+    pub caller: Principal,
+    pub operation: String,
+    pub neuron_key: Option<[u8; 32]>,
+    pub args: Vec<u8>,
+}
+
+impl Block {
+    pub fn to_value(&self) -> Icrc3Value {
+        let mut tx = vec![
+            ("op".to_string(), Icrc3Value::Text(self.operation.clone())),
+            (
+                "caller".to_string(),
+                Icrc3Value::Blob(self.caller.as_slice().to_vec()),
+            ),
+            ("args".to_string(), Icrc3Value::Blob(self.args.clone())),
+        ];
+        if let Some(neuron_key) = self.neuron_key {
+            tx.push(("neuron".to_string(), Icrc3Value::Blob(neuron_key.to_vec())));
+        }
+
+        let mut block = vec![
+            (
+                "btype".to_string(),
+                Icrc3Value::Text(self.block_type.clone()),
+            ),
+            ("ts".to_string(), Icrc3Value::Nat(Nat::from(self.timestamp))),
+            ("tx".to_string(), Icrc3Value::Map(tx)),
+        ];
+        if let Some(parent_hash) = self.parent_hash {
+            block.push(("phash".to_string(), Icrc3Value::Blob(parent_hash.to_vec())));
+        }
+
+        Icrc3Value::Map(block)
+    }
+}
+
+impl VersionedStorable for Block {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct GetBlocksArgs {
+    pub start: Nat,
+    pub length: Nat,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct BlockWithId {
+    pub id: Nat,
+    pub block: Icrc3Value,
+}
+
+candid::define_function!(pub QueryBlockArchiveFn : (Vec<GetBlocksArgs>) -> (GetBlocksResult) query);
+
+#[derive(Debug, CandidType, Deserialize, Clone)]
+pub struct ArchivedBlocks {
+    pub args: Vec<GetBlocksArgs>,
+    pub callback: QueryBlockArchiveFn,
+}
+
+#[derive(Debug, CandidType, Deserialize, Clone)]
+pub struct GetBlocksResult {
+    pub log_length: Nat,
+    pub blocks: Vec<BlockWithId>,
+    pub archived_blocks: Vec<ArchivedBlocks>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct GetArchivesArgs {
+    pub from: Option<Principal>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Icrc3ArchiveInfo {
+    pub canister_id: Principal,
+    pub start: Nat,
+    pub end: Nat,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Icrc3DataCertificate {
+    pub certificate: Vec<u8>,
+    pub hash_tree: Vec<u8>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct SupportedBlockType {
+    pub block_type: String,
+    pub url: String,
+}
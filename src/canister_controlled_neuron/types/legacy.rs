@@ -0,0 +1,51 @@
+//! Frozen copies of stored types as they were written by earlier schema versions.
+//! These must never change, they only exist to decode existing stable-memory entries.
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use toolkit_utils::impl_storable_for;
+
+use super::{config::Config, neuron_reference::NeuronReference};
+
+impl_storable_for!(NeuronReferenceV0);
+impl_storable_for!(ConfigV0);
+
+/// `NeuronReference` as stored before hot keys were tracked (versions 0 and 1).
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct NeuronReferenceV0 {
+    pub blockheight: u64,
+    pub subaccount: [u8; 32],
+    pub nonce: u64,
+    pub neuron_id: Option<u64>,
+    pub parent_subaccount: Option<[u8; 32]>,
+}
+
+impl From<NeuronReferenceV0> for NeuronReference {
+    fn from(value: NeuronReferenceV0) -> Self {
+        NeuronReference {
+            blockheight: value.blockheight,
+            subaccount: value.subaccount,
+            nonce: value.nonce,
+            neuron_id: value.neuron_id,
+            parent_subaccount: value.parent_subaccount,
+            hot_keys: vec![],
+        }
+    }
+}
+
+/// `Config` as stored before the ICP canister ids were added (versions 0 and 1).
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct ConfigV0 {
+    pub governance_canister_id: Principal,
+    pub sns_ledger_canister_id: Principal,
+}
+
+impl From<ConfigV0> for Config {
+    fn from(value: ConfigV0) -> Self {
+        Config::new(
+            value.governance_canister_id,
+            value.sns_ledger_canister_id,
+            None,
+            None,
+        )
+    }
+}
@@ -0,0 +1,100 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+impl_versioned_storable_for!(LogEntry);
+
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone, Copy, PartialEq, Eq)]
+pub enum LogLevel {
+    Info,
+    Warning,
+    Error,
+}
+
+/// A single log line. `neuron_key` is the subaccount of the ICP or SNS neuron the entry relates to.
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct LogEntry {
+    pub timestamp: u64,
+    pub level: LogLevel,
+    pub operation: String,
+    pub neuron_key: Option<[u8; 32]>,
+    pub message: String,
+    pub caller: Principal,
+}
+
+impl LogEntry {
+    pub fn to_response(&self, id: u64) -> LogEntryResponse {
+        LogEntryResponse {
+            id,
+            timestamp: self.timestamp,
+            level: self.level,
+            operation: self.operation.clone(),
+            neuron_key: self.neuron_key,
+            message: self.message.clone(),
+            caller: self.caller,
+        }
+    }
+}
+
+impl VersionedStorable for LogEntry {
+    const VERSION: u8 = 1;
+
+    /// Version `0` entries are the free-form strings that were logged before, most of them
+    /// prefixed with `"{time}: "`.
+    fn migrate(version: u8, bytes: &[u8]) -> Self {
+        match version {
+            0 => {
+                let line = String::from_utf8_lossy(bytes);
+                let (timestamp, message) = line
+                    .split_once(": ")
+                    .and_then(|(timestamp, message)| {
+                        timestamp.parse::<u64>().ok().map(|t| (t, message))
+                    })
+                    .unwrap_or((0, &line));
+
+                LogEntry {
+                    timestamp,
+                    level: LogLevel::Info,
+                    operation: "legacy".to_string(),
+                    neuron_key: None,
+                    message: message.to_string(),
+                    caller: Principal::anonymous(),
+                }
+            }
+            _ => unsupported_version(version),
+        }
+    }
+}
+
+/// All filters are optional, `from_timestamp` and `to_timestamp` are inclusive. Entries are
+/// returned in insertion order starting at `start`, pass the returned `next` to get the next page.
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone, Default)]
+pub struct GetLogsArgs {
+    pub level: Option<LogLevel>,
+    pub neuron_key: Option<[u8; 32]>,
+    pub from_timestamp: Option<u64>,
+    pub to_timestamp: Option<u64>,
+    pub start: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct LogEntryResponse {
+    pub id: u64,
+    pub timestamp: u64,
+    pub level: LogLevel,
+    pub operation: String,
+    pub neuron_key: Option<[u8; 32]>,
+    pub message: String,
+    pub caller: Principal,
+}
+
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct LogsResponse {
+    pub entries: Vec<LogEntryResponse>,
+    pub next: Option<u64>,
+}
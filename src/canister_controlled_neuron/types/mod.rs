@@ -1,4 +1,18 @@
+pub mod audit_record;
 pub mod config;
+pub mod config_change;
+pub mod icrc21;
+pub mod icrc3;
+pub mod legacy;
+pub mod log_entry;
 pub mod modules;
+pub mod neuron_creation;
 pub mod neuron_reference;
+pub mod policy;
+pub mod queued_operation;
+pub mod reconcile;
+pub mod role;
+pub mod sns_neuron_reference;
 pub mod topic;
+pub mod voting_power_refresh;
+pub mod voting_rule;
@@ -1,15 +1,168 @@
-use candid::CandidType;
+use candid::{CandidType, Principal};
+use ic_ledger_types::AccountIdentifier;
 use serde::{Deserialize, Serialize};
+use toolkit_utils::{icrc_ledger_types::icrc1::account::Account, result::CanisterResult};
 
-use crate::api::icp_governance_api::{
-    MakeProposalRequest, MakeProposalResponse, ManageNeuronCommandRequest, ManageNeuronResponse,
+use crate::{
+    api::icp_governance_api::{
+        MakeProposalRequest, MakeProposalResponse, ManageNeuronCommandRequest, ManageNeuronResponse,
+    },
+    storage::neuron_reference_storage::NeuronReferenceStore,
 };
 
-use super::{neuron_reference::NeuronReferenceResponse, topic::Topic};
+use super::{
+    neuron_reference::NeuronReferenceResponse, reconcile::ReconcileReport, role::Role,
+    sns_neuron_reference::SnsNeuronReferenceResponse, topic::Topic,
+};
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub enum NeuronType {
     Icp(IcpNeuronArgs),
+    Sns(SnsNeuronArgs),
+}
+
+impl NeuronType {
+    /// The subaccount of the neuron the args act on, `None` for operations that create a neuron
+    /// or act on all neurons and for a neuron id without a reference. For a merge this is the
+    /// target neuron.
+    pub fn neuron_key(&self) -> Option<[u8; 32]> {
+        match self {
+            NeuronType::Icp(args) => match args {
+                IcpNeuronArgs::Create(_) | IcpNeuronArgs::Reconcile => None,
+                IcpNeuronArgs::TopUp(args) => args.neuron.key(),
+                IcpNeuronArgs::AddDissolveDelay(args) => args.neuron.key(),
+                IcpNeuronArgs::SetDissolveState(args) => args.neuron.key(),
+                IcpNeuronArgs::AutoStake(args) => args.neuron.key(),
+                IcpNeuronArgs::Spawn(args) => args.parent.key(),
+                IcpNeuronArgs::CreateProposal(args) => args.neuron.key(),
+                IcpNeuronArgs::Vote(args) => args.neuron.key(),
+                IcpNeuronArgs::Disburse(args) => args.neuron.key(),
+                IcpNeuronArgs::SetFollowing(args) => args.neuron.key(),
+                IcpNeuronArgs::Command(args) => args.neuron.key(),
+                IcpNeuronArgs::Split(args) => args.neuron.key(),
+                IcpNeuronArgs::Merge(args) => args.target.key(),
+                IcpNeuronArgs::StakeMaturity(args) => args.neuron.key(),
+                IcpNeuronArgs::DisburseMaturity(args) => args.neuron.key(),
+                IcpNeuronArgs::AddHotKey(args) => args.neuron.key(),
+                IcpNeuronArgs::RemoveHotKey(args) => args.neuron.key(),
+            },
+            NeuronType::Sns(args) => match args {
+                SnsNeuronArgs::Create(_) => None,
+                SnsNeuronArgs::TopUp(args) => Some(args.subaccount),
+                SnsNeuronArgs::Claim(args) => Some(args.subaccount),
+                SnsNeuronArgs::AddDissolveDelay(args) => Some(args.subaccount),
+                SnsNeuronArgs::SetDissolveState(args) => Some(args.subaccount),
+                SnsNeuronArgs::Vote(args) => Some(args.subaccount),
+                SnsNeuronArgs::SetFollowing(args) => Some(args.subaccount),
+                SnsNeuronArgs::Disburse(args) => Some(args.subaccount),
+            },
+        }
+    }
+
+    /// Voting and refreshing can be delegated to operators, everything that moves funds or
+    /// changes the neuron requires an admin.
+    pub fn required_role(&self) -> Role {
+        match self {
+            NeuronType::Icp(IcpNeuronArgs::Vote(_))
+            | NeuronType::Icp(IcpNeuronArgs::Reconcile)
+            | NeuronType::Sns(SnsNeuronArgs::Vote(_)) => Role::Operator,
+            NeuronType::Icp(IcpNeuronArgs::Command(args)) if is_operator_command(&args.command) => {
+                Role::Operator
+            }
+            _ => Role::Admin,
+        }
+    }
+
+    /// Operations that move funds out of a neuron or start dissolving it, these are queued when
+    /// the policy sets an execution delay. Raw commands that operators may send are not.
+    pub fn is_high_risk(&self) -> bool {
+        if let NeuronType::Icp(IcpNeuronArgs::Command(args)) = self {
+            return !is_operator_command(&args.command);
+        }
+
+        matches!(
+            self,
+            NeuronType::Icp(IcpNeuronArgs::Disburse(_))
+                | NeuronType::Icp(IcpNeuronArgs::DisburseMaturity(_))
+                | NeuronType::Icp(IcpNeuronArgs::SetDissolveState(SetDissolveStateArgs {
+                    start_dissolving: true,
+                    ..
+                }))
+                | NeuronType::Sns(SnsNeuronArgs::Disburse(_))
+                | NeuronType::Sns(SnsNeuronArgs::SetDissolveState(SetSnsDissolveStateArgs {
+                    start_dissolving: true,
+                    ..
+                }))
+        )
+    }
+
+    pub fn operation(&self) -> &'static str {
+        match self {
+            NeuronType::Icp(args) => match args {
+                IcpNeuronArgs::Create(_) => "create",
+                IcpNeuronArgs::TopUp(_) => "top_up",
+                IcpNeuronArgs::AddDissolveDelay(_) => "add_dissolve_delay",
+                IcpNeuronArgs::SetDissolveState(_) => "set_dissolve_state",
+                IcpNeuronArgs::AutoStake(_) => "auto_stake",
+                IcpNeuronArgs::Spawn(_) => "spawn",
+                IcpNeuronArgs::CreateProposal(_) => "create_proposal",
+                IcpNeuronArgs::Vote(_) => "vote",
+                IcpNeuronArgs::Disburse(_) => "disburse",
+                IcpNeuronArgs::SetFollowing(_) => "set_following",
+                IcpNeuronArgs::Command(_) => "command",
+                IcpNeuronArgs::Split(_) => "split",
+                IcpNeuronArgs::Merge(_) => "merge",
+                IcpNeuronArgs::StakeMaturity(_) => "stake_maturity",
+                IcpNeuronArgs::DisburseMaturity(_) => "disburse_maturity",
+                IcpNeuronArgs::AddHotKey(_) => "add_hot_key",
+                IcpNeuronArgs::RemoveHotKey(_) => "remove_hot_key",
+                IcpNeuronArgs::Reconcile => "reconcile",
+            },
+            NeuronType::Sns(args) => match args {
+                SnsNeuronArgs::Create(_) => "sns_create",
+                SnsNeuronArgs::TopUp(_) => "sns_top_up",
+                SnsNeuronArgs::Claim(_) => "sns_claim",
+                SnsNeuronArgs::AddDissolveDelay(_) => "sns_add_dissolve_delay",
+                SnsNeuronArgs::SetDissolveState(_) => "sns_set_dissolve_state",
+                SnsNeuronArgs::Vote(_) => "sns_vote",
+                SnsNeuronArgs::SetFollowing(_) => "sns_set_following",
+                SnsNeuronArgs::Disburse(_) => "sns_disburse",
+            },
+        }
+    }
+}
+
+/// Raw commands that vote or refresh without moving funds, operators may send these directly.
+fn is_operator_command(command: &ManageNeuronCommandRequest) -> bool {
+    matches!(
+        command,
+        ManageNeuronCommandRequest::RegisterVote(_)
+            | ManageNeuronCommandRequest::RefreshVotingPower(_)
+            | ManageNeuronCommandRequest::ClaimOrRefresh(_)
+    )
+}
+
+/// Addresses a managed neuron by the subaccount or the neuron id of its reference.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum NeuronSelector {
+    Subaccount([u8; 32]),
+    NeuronId(u64),
+}
+
+impl NeuronSelector {
+    /// The subaccount of the referenced neuron, looked up through the indexes of the store.
+    pub fn resolve(&self) -> CanisterResult<[u8; 32]> {
+        let (_, neuron) = NeuronReferenceStore::get_by_selector(*self)?;
+        Ok(neuron.subaccount)
+    }
+
+    /// The subaccount without checking that it is referenced, `None` for an unknown neuron id.
+    pub fn key(&self) -> Option<[u8; 32]> {
+        match self {
+            NeuronSelector::Subaccount(subaccount) => Some(*subaccount),
+            NeuronSelector::NeuronId(_) => self.resolve().ok(),
+        }
+    }
 }
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
@@ -25,11 +178,18 @@ pub enum IcpNeuronArgs {
     Disburse(DisburseArgs),
     SetFollowing(SetFollowingArgs),
     Command(CommandNeuronArgs),
+    Split(SplitArgs),
+    Merge(MergeArgs),
+    StakeMaturity(StakeMaturityArgs),
+    DisburseMaturity(DisburseMaturityArgs),
+    AddHotKey(HotKeyArgs),
+    RemoveHotKey(HotKeyArgs),
+    Reconcile,
 }
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct SetFollowingArgs {
-    pub subaccount: [u8; 32],
+    pub neuron: NeuronSelector,
     pub following: Vec<FollowingArgs>,
 }
 
@@ -39,14 +199,31 @@ pub struct FollowingArgs {
     pub followees: Vec<u64>,
 }
 
+/// The followees of a neuron for a single topic, as reported by the governance canister.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct TopicFollowees {
+    pub topic: i32,
+    pub followees: Vec<u64>,
+}
+
+/// Disburses `amount_e8s`, or the full stake when omitted. Without a destination the stake is
+/// sent to the default account of the governance canister.
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct DisburseArgs {
-    pub subaccount: [u8; 32],
+    pub neuron: NeuronSelector,
+    pub amount_e8s: Option<u64>,
+    pub destination: Option<DisburseDestination>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum DisburseDestination {
+    Account(Account),
+    AccountIdentifier(AccountIdentifier),
 }
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct VoteArgs {
-    pub subaccount: [u8; 32],
+    pub neuron: NeuronSelector,
     pub proposal_id: u64,
     pub vote: Vote,
 }
@@ -59,31 +236,66 @@ pub enum Vote {
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct CreateProposalArgs {
-    pub subaccount: [u8; 32],
+    pub neuron: NeuronSelector,
     pub proposal: MakeProposalRequest,
 }
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct SpawnArgs {
-    pub parent_subaccount: [u8; 32],
+    pub parent: NeuronSelector,
     pub start_dissolving: bool,
 }
 
+/// The memo is passed on to governance, when omitted the nonce of the new neuron reference is used.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct SplitArgs {
+    pub neuron: NeuronSelector,
+    pub amount_e8s: u64,
+    pub memo: Option<u64>,
+}
+
+/// Merges the source neuron into the target neuron, the source neuron reference is removed.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct MergeArgs {
+    pub target: NeuronSelector,
+    pub source: NeuronSelector,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct StakeMaturityArgs {
+    pub neuron: NeuronSelector,
+    pub percentage: u32,
+}
+
+/// Disburses maturity to `to_account`, or to the default account of this canister when omitted.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct DisburseMaturityArgs {
+    pub neuron: NeuronSelector,
+    pub percentage: u32,
+    pub to_account: Option<Account>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct HotKeyArgs {
+    pub neuron: NeuronSelector,
+    pub hot_key: Principal,
+}
+
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct AutoStakeArgs {
-    pub subaccount: [u8; 32],
+    pub neuron: NeuronSelector,
     pub auto_stake: bool,
 }
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct SetDissolveStateArgs {
-    pub subaccount: [u8; 32],
+    pub neuron: NeuronSelector,
     pub start_dissolving: bool,
 }
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct AddDissolveDelayArgs {
-    pub subaccount: [u8; 32],
+    pub neuron: NeuronSelector,
     pub dissolve_delay_seconds: u64,
 }
 
@@ -96,21 +308,91 @@ pub struct CreateNeuronArgs {
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct TopUpNeuronArgs {
-    pub subaccount: [u8; 32],
+    pub neuron: NeuronSelector,
     pub amount_e8s: u64,
 }
 
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub struct CommandNeuronArgs {
-    pub subaccount: [u8; 32],
+    pub neuron: NeuronSelector,
     pub command: ManageNeuronCommandRequest,
 }
 
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum SnsNeuronArgs {
+    Create(CreateSnsNeuronArgs),
+    TopUp(TopUpSnsNeuronArgs),
+    Claim(ClaimSnsNeuronArgs),
+    AddDissolveDelay(AddSnsDissolveDelayArgs),
+    SetDissolveState(SetSnsDissolveStateArgs),
+    Vote(SnsVoteArgs),
+    SetFollowing(SetSnsFollowingArgs),
+    Disburse(DisburseSnsNeuronArgs),
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct CreateSnsNeuronArgs {
+    pub amount_e8s: u64,
+    pub dissolve_delay_seconds: Option<u64>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct TopUpSnsNeuronArgs {
+    pub subaccount: [u8; 32],
+    pub amount_e8s: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ClaimSnsNeuronArgs {
+    pub subaccount: [u8; 32],
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct AddSnsDissolveDelayArgs {
+    pub subaccount: [u8; 32],
+    pub dissolve_delay_seconds: u64,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct SetSnsDissolveStateArgs {
+    pub subaccount: [u8; 32],
+    pub start_dissolving: bool,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct SnsVoteArgs {
+    pub subaccount: [u8; 32],
+    pub proposal_id: u64,
+    pub vote: Vote,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct SetSnsFollowingArgs {
+    pub subaccount: [u8; 32],
+    pub following: Vec<SnsFollowingArgs>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct SnsFollowingArgs {
+    pub function_id: u64,
+    pub followees: Vec<[u8; 32]>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct DisburseSnsNeuronArgs {
+    pub subaccount: [u8; 32],
+}
+
 #[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
 pub enum ModuleResponse {
     Boolean(bool),
     Neuron(Box<NeuronReferenceResponse>),
+    SnsNeuron(Box<SnsNeuronReferenceResponse>),
     BlockHeight(u64),
     ManageNeuronResponse(Box<ManageNeuronResponse>),
     MakeProposalResponse(Box<MakeProposalResponse>),
+    Following(Vec<TopicFollowees>),
+    Reconcile(Box<ReconcileReport>),
+    /// The id of the queued operation, high-risk operations are executed after a delay.
+    Queued(u64),
 }
@@ -0,0 +1,119 @@
+use candid::CandidType;
+use ic_cdk::api::time;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+impl_versioned_storable_for!(NeuronCreationOperation);
+
+/// The steps of a neuron creation, each state means the step has been completed.
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone, PartialEq, Eq)]
+pub enum NeuronCreationState {
+    Pending,
+    Transferred,
+    Claimed,
+    Configured,
+}
+
+/// The governance the neuron is created in, which also decides the store of its reference.
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone, PartialEq, Eq)]
+pub enum NeuronCreationKind {
+    Icp,
+    Sns,
+}
+
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct NeuronCreationOperation {
+    pub neuron_reference_id: u64,
+    pub amount_e8s: u64,
+    pub auto_stake: Option<bool>,
+    pub dissolve_delay_seconds: Option<u64>,
+    pub state: NeuronCreationState,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+    /// The `created_at_time` of the stake transfer, `created_at` when not set. It is only
+    /// replaced when the ledger no longer deduplicates the transfer and the stake did not arrive.
+    pub transfer_created_at: Option<u64>,
+    /// `Icp` when not set, operations stored before SNS neurons were created this way.
+    pub kind: Option<NeuronCreationKind>,
+}
+
+impl NeuronCreationOperation {
+    pub fn new(
+        kind: NeuronCreationKind,
+        neuron_reference_id: u64,
+        amount_e8s: u64,
+        auto_stake: Option<bool>,
+        dissolve_delay_seconds: Option<u64>,
+    ) -> Self {
+        Self {
+            neuron_reference_id,
+            amount_e8s,
+            auto_stake,
+            dissolve_delay_seconds,
+            state: NeuronCreationState::Pending,
+            attempts: 0,
+            last_error: None,
+            created_at: time(),
+            updated_at: time(),
+            transfer_created_at: None,
+            kind: Some(kind),
+        }
+    }
+
+    pub fn kind(&self) -> NeuronCreationKind {
+        self.kind.clone().unwrap_or(NeuronCreationKind::Icp)
+    }
+
+    pub fn transfer_created_at(&self) -> u64 {
+        self.transfer_created_at.unwrap_or(self.created_at)
+    }
+
+    pub fn is_pending(&self) -> bool {
+        self.state != NeuronCreationState::Configured
+    }
+
+    pub fn to_response(&self, id: u64) -> NeuronCreationOperationResponse {
+        NeuronCreationOperationResponse {
+            id,
+            kind: self.kind(),
+            neuron_reference_id: self.neuron_reference_id,
+            amount_e8s: self.amount_e8s,
+            auto_stake: self.auto_stake,
+            dissolve_delay_seconds: self.dissolve_delay_seconds,
+            state: self.state.clone(),
+            attempts: self.attempts,
+            last_error: self.last_error.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+impl VersionedStorable for NeuronCreationOperation {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct NeuronCreationOperationResponse {
+    pub id: u64,
+    pub kind: NeuronCreationKind,
+    pub neuron_reference_id: u64,
+    pub amount_e8s: u64,
+    pub auto_stake: Option<bool>,
+    pub dissolve_delay_seconds: Option<u64>,
+    pub state: NeuronCreationState,
+    pub attempts: u32,
+    pub last_error: Option<String>,
+    pub created_at: u64,
+    pub updated_at: u64,
+}
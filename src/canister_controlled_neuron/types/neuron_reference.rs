@@ -1,32 +1,44 @@
-use candid::CandidType;
+use std::borrow::Cow;
+
+use candid::{CandidType, Principal};
+use ic_cdk::api::time;
 use ic_ledger_types::{
-    transfer, AccountIdentifier, Memo, Subaccount, Tokens, TransferArgs, DEFAULT_SUBACCOUNT,
-    MAINNET_GOVERNANCE_CANISTER_ID, MAINNET_LEDGER_CANISTER_ID,
+    AccountBalanceArgs, AccountIdentifier, Memo, Subaccount, Timestamp, Tokens, TransferArgs,
+    TransferError,
 };
+use ic_stable_structures::Storable;
 use serde::{Deserialize, Serialize};
-use toolkit_utils::{
-    api_error::ApiError, cell::CellStorage, impl_storable_for, result::CanisterResult,
-};
+use toolkit_utils::{api_error::ApiError, cell::CellStorage, result::CanisterResult};
 
 use crate::{
     api::{
         api_clients::ApiClients,
         icp_governance_api::{
-            Account, AccountIdentifier as ApiAccountIdentifier, By, ChangeAutoStakeMaturity,
-            ClaimOrRefresh, ClaimOrRefreshResponse, Command1, Configure, Disburse,
-            DisburseResponse, Follow, IncreaseDissolveDelay, MakeProposalRequest,
-            MakeProposalResponse, ManageNeuronCommandRequest, ManageNeuronRequest,
-            ManageNeuronResponse, Neuron as GovNeuron, NeuronId, NeuronIdOrSubaccount, Operation,
-            ProposalId, RegisterVote, Result2, SetVisibility, Spawn, SpawnResponse,
+            Account, AccountIdentifier as ApiAccountIdentifier, AddHotKey, Amount, By,
+            ChangeAutoStakeMaturity, ClaimOrRefresh, ClaimOrRefreshResponse, Command1, Configure,
+            Disburse, DisburseMaturity, DisburseMaturityResponse, DisburseResponse, DissolveState,
+            FolloweesForTopic, IncreaseDissolveDelay, MakeProposalRequest, MakeProposalResponse,
+            ManageNeuronCommandRequest, ManageNeuronRequest, ManageNeuronResponse, Merge,
+            MergeResponse, Neuron as GovNeuron, NeuronId, NeuronIdOrSubaccount, Operation,
+            ProposalId, RefreshVotingPower, RegisterVote, RemoveHotKey, Result2, SetFollowing,
+            SetVisibility, Spawn, SpawnResponse, Split, StakeMaturity, StakeMaturityResponse,
         },
     },
-    helpers::subaccount_helper::generate_subaccount_by_nonce,
-    storage::{config_storage::config_store, neuron_reference_storage::NeuronReferenceStore},
+    helpers::{
+        subaccount_helper::generate_subaccount_by_nonce,
+        versioned_storable::{unsupported_version, VersionedStorable},
+    },
+    impl_versioned_storable_for,
+    storage::config_storage::config_store,
 };
 
-use super::{modules::Vote, topic::Topic};
+use super::{
+    legacy::NeuronReferenceV0,
+    modules::{FollowingArgs, Vote},
+};
 
-impl_storable_for!(NeuronReference);
+impl_versioned_storable_for!(NeuronReference);
+impl_versioned_storable_for!(NonceCounter);
 
 #[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
 pub struct NeuronReference {
@@ -35,57 +47,82 @@ pub struct NeuronReference {
     pub nonce: u64,
     pub neuron_id: Option<u64>,
     pub parent_subaccount: Option<[u8; 32]>,
+    pub hot_keys: Vec<Principal>,
 }
 
 impl NeuronReference {
-    pub async fn new(amount_e8s: u64) -> CanisterResult<NeuronReference> {
-        let fee = 10_000;
-
-        if amount_e8s < 100_000_000 + fee {
-            return Err(ApiError::bad_request(&format!(
-                "Amount is too small, minimum is {} e8s",
-                100_000_000 + fee
-            )));
+    pub fn new(nonce: u64) -> NeuronReference {
+        NeuronReference {
+            blockheight: 0,
+            subaccount: generate_subaccount_by_nonce(nonce),
+            nonce,
+            neuron_id: None,
+            parent_subaccount: None,
+            hot_keys: vec![],
         }
+    }
 
-        let nonce = NeuronReferenceStore::get_latest_key() + 1;
-        let subaccount = generate_subaccount_by_nonce(nonce);
-        let account_identifier =
-            AccountIdentifier::new(&MAINNET_GOVERNANCE_CANISTER_ID, &Subaccount(subaccount));
+    /// Transfers the initial stake to the neuron subaccount. The transfer is deduplicated by the
+    /// ledger on `created_at_time`, so retrying it with the same timestamp never sends twice.
+    /// Ledger rejections are returned as is, so the caller can handle `TxTooOld`.
+    pub async fn fund(
+        &self,
+        amount_e8s: u64,
+        created_at_time: u64,
+    ) -> CanisterResult<Result<u64, TransferError>> {
+        let account_identifier = self.stake_account_identifier()?;
 
         let transfer_args = TransferArgs {
-            memo: Memo(nonce),
+            memo: Memo(self.nonce),
             amount: Tokens::from_e8s(amount_e8s),
             fee: Tokens::from_e8s(10000),
             from_subaccount: None,
             to: account_identifier,
-            created_at_time: None,
+            created_at_time: Some(Timestamp {
+                timestamp_nanos: created_at_time,
+            }),
         };
 
-        let blockheight = transfer(MAINNET_LEDGER_CANISTER_ID, &transfer_args)
+        let (result,) = ApiClients::icp_ledger()?
+            .transfer(transfer_args)
             .await
-            .map_err(|e| ApiError::external_service_error(e.to_string().as_str()))?
-            .map_err(|e| ApiError::external_service_error(e.to_string().as_str()))?;
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
 
-        let neuron = NeuronReference {
-            blockheight,
-            subaccount,
-            nonce,
-            neuron_id: None,
-            parent_subaccount: None,
-        };
+        match result {
+            Err(TransferError::TxDuplicate { duplicate_of }) => Ok(Ok(duplicate_of)),
+            result => Ok(result),
+        }
+    }
+
+    pub async fn stake_balance(&self) -> CanisterResult<u64> {
+        let account = self.stake_account_identifier()?;
+
+        let (balance,) = ApiClients::icp_ledger()?
+            .account_balance(AccountBalanceArgs { account })
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
 
-        Ok(neuron)
+        Ok(balance.e8s())
     }
 
+    /// Claims the neuron by memo, once claimed it is refreshed by its subaccount. Neurons that
+    /// were imported by reconciliation are always claimed and have no known memo.
     pub async fn claim_or_refresh(&mut self) -> CanisterResult<ClaimOrRefreshResponse> {
-        let (result,) = ApiClients::icp_governance()
+        let (by, neuron_id_or_subaccount) = match self.neuron_id {
+            Some(_) => (
+                By::NeuronIdOrSubaccount {},
+                Some(NeuronIdOrSubaccount::Subaccount(self.subaccount.to_vec())),
+            ),
+            None => (By::Memo(self.nonce), None),
+        };
+
+        let (result,) = ApiClients::icp_governance()?
             .manage_neuron(ManageNeuronRequest {
                 id: None,
                 command: Some(ManageNeuronCommandRequest::ClaimOrRefresh(ClaimOrRefresh {
-                    by: Some(By::Memo(self.nonce)),
+                    by: Some(by),
                 })),
-                neuron_id_or_subaccount: None,
+                neuron_id_or_subaccount,
             })
             .await
             .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
@@ -98,10 +135,7 @@ impl NeuronReference {
     }
 
     pub async fn top_up(&self, amount_e8s: u64) -> CanisterResult<u64> {
-        let account_identifier = AccountIdentifier::new(
-            &MAINNET_GOVERNANCE_CANISTER_ID,
-            &Subaccount(self.subaccount),
-        );
+        let account_identifier = self.stake_account_identifier()?;
 
         let transfer_args = TransferArgs {
             memo: Memo(0),
@@ -112,10 +146,21 @@ impl NeuronReference {
             created_at_time: None,
         };
 
-        transfer(MAINNET_LEDGER_CANISTER_ID, &transfer_args)
+        let (result,) = ApiClients::icp_ledger()?
+            .transfer(transfer_args)
             .await
-            .map_err(|e| ApiError::external_service_error(e.to_string().as_str()))?
-            .map_err(|e| ApiError::external_service_error(e.to_string().as_str()))
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        result.map_err(|e| ApiError::external_service_error(e.to_string().as_str()))
+    }
+
+    /// The ledger account of the neuron stake, owned by the ICP governance canister.
+    fn stake_account_identifier(&self) -> CanisterResult<AccountIdentifier> {
+        let config = config_store().get()?;
+        Ok(AccountIdentifier::new(
+            &config.icp_governance_canister_id,
+            &Subaccount(self.subaccount),
+        ))
     }
 
     pub async fn configure(&self, operation: Operation) -> CanisterResult<()> {
@@ -146,6 +191,25 @@ impl NeuronReference {
         .await
     }
 
+    /// Raises the dissolve delay to at least `dissolve_delay` seconds. Unlike
+    /// `increase_dissolve_delay` this can safely be retried.
+    pub async fn ensure_dissolve_delay(&self, dissolve_delay: u64) -> CanisterResult<()> {
+        let current_dissolve_delay = match self.get_full_neuron().await?.dissolve_state {
+            Some(DissolveState::DissolveDelaySeconds(seconds)) => seconds,
+            Some(DissolveState::WhenDissolvedTimestampSeconds(timestamp)) => {
+                timestamp.saturating_sub(time() / 1_000_000_000)
+            }
+            None => 0,
+        };
+
+        if current_dissolve_delay < dissolve_delay {
+            self.increase_dissolve_delay(dissolve_delay - current_dissolve_delay)
+                .await
+        } else {
+            Ok(())
+        }
+    }
+
     pub async fn set_dissolve_state(&self, start_dissolving: bool) -> CanisterResult<()> {
         if start_dissolving {
             self.configure(Operation::StartDissolving {})
@@ -155,6 +219,20 @@ impl NeuronReference {
         .await
     }
 
+    pub async fn add_hot_key(&self, hot_key: Principal) -> CanisterResult<()> {
+        self.configure(Operation::AddHotKey(AddHotKey {
+            new_hot_key: Some(hot_key),
+        }))
+        .await
+    }
+
+    pub async fn remove_hot_key(&self, hot_key: Principal) -> CanisterResult<()> {
+        self.configure(Operation::RemoveHotKey(RemoveHotKey {
+            hot_key_to_remove: Some(hot_key),
+        }))
+        .await
+    }
+
     pub async fn set_publicity(&self, publicity: SetVisibility) -> CanisterResult<()> {
         self.configure(Operation::SetVisibility(publicity)).await
     }
@@ -177,6 +255,77 @@ impl NeuronReference {
         }
     }
 
+    pub async fn split(&self, amount_e8s: u64, nonce: u64) -> CanisterResult<SpawnResponse> {
+        let result = self
+            .command(ManageNeuronCommandRequest::Split(Split {
+                amount_e8s,
+                memo: Some(nonce),
+            }))
+            .await?;
+        match result.command {
+            Some(Command1::Split(response)) => Ok(response),
+            _ => Err(ApiError::external_service_error("Unknown command")),
+        }
+    }
+
+    pub async fn merge(&self, source_neuron_id: u64) -> CanisterResult<MergeResponse> {
+        let result = self
+            .command(ManageNeuronCommandRequest::Merge(Merge {
+                source_neuron_id: Some(NeuronId {
+                    id: source_neuron_id,
+                }),
+            }))
+            .await?;
+        match result.command {
+            Some(Command1::Merge(response)) => Ok(response),
+            _ => Err(ApiError::external_service_error("Unknown command")),
+        }
+    }
+
+    pub async fn stake_maturity(&self, percentage: u32) -> CanisterResult<StakeMaturityResponse> {
+        let result = self
+            .command(ManageNeuronCommandRequest::StakeMaturity(StakeMaturity {
+                percentage_to_stake: Some(percentage),
+            }))
+            .await?;
+        match result.command {
+            Some(Command1::StakeMaturity(response)) => Ok(response),
+            _ => Err(ApiError::external_service_error("Unknown command")),
+        }
+    }
+
+    pub async fn disburse_maturity(
+        &self,
+        percentage: u32,
+        to_account: Account,
+    ) -> CanisterResult<DisburseMaturityResponse> {
+        let result = self
+            .command(ManageNeuronCommandRequest::DisburseMaturity(
+                DisburseMaturity {
+                    to_account_identifier: None,
+                    to_account: Some(to_account),
+                    percentage_to_disburse: percentage,
+                },
+            ))
+            .await?;
+        match result.command {
+            Some(Command1::DisburseMaturity(response)) => Ok(response),
+            _ => Err(ApiError::external_service_error("Unknown command")),
+        }
+    }
+
+    pub async fn refresh_voting_power(&self) -> CanisterResult<()> {
+        let result = self
+            .command(ManageNeuronCommandRequest::RefreshVotingPower(
+                RefreshVotingPower {},
+            ))
+            .await?;
+        match result.command {
+            Some(Command1::RefreshVotingPower(_)) => Ok(()),
+            _ => Err(ApiError::external_service_error("Unknown command")),
+        }
+    }
+
     pub async fn create_proposal(
         &self,
         proposal: MakeProposalRequest,
@@ -212,36 +361,45 @@ impl NeuronReference {
         }
     }
 
-    pub async fn set_following(
-        &self,
-        topic: Topic,
-        following_neurons: Vec<u64>,
-    ) -> CanisterResult<()> {
-        let followees = following_neurons
-            .iter()
-            .map(|id| NeuronId { id: *id })
-            .collect();
-
+    /// Replaces the followees of all given topics in a single command, so either all or none
+    /// of the topics are updated.
+    pub async fn set_following(&self, following: Vec<FollowingArgs>) -> CanisterResult<()> {
         let result = self
-            .command(ManageNeuronCommandRequest::Follow(Follow {
-                topic: topic.into(),
-                followees,
-            }))
+            .command(NeuronReference::set_following_command(following))
             .await?;
 
         match result.command {
-            Some(Command1::Follow {}) => Ok(()),
+            Some(Command1::SetFollowing(_)) => Ok(()),
             _ => Err(ApiError::external_service_error("Unexpected response")),
         }
     }
 
+    pub fn set_following_command(following: Vec<FollowingArgs>) -> ManageNeuronCommandRequest {
+        let topic_following = following
+            .into_iter()
+            .map(|args| FolloweesForTopic {
+                topic: Some(args.topic.into()),
+                followees: Some(
+                    args.followees
+                        .into_iter()
+                        .map(|id| NeuronId { id })
+                        .collect(),
+                ),
+            })
+            .collect();
+
+        ManageNeuronCommandRequest::SetFollowing(SetFollowing {
+            topic_following: Some(topic_following),
+        })
+    }
+
     pub async fn command(
         &self,
         command: ManageNeuronCommandRequest,
     ) -> CanisterResult<ManageNeuronResponse> {
         match self.neuron_id {
             Some(neuron_id) => {
-                let (result,) = ApiClients::icp_governance()
+                let (result,) = ApiClients::icp_governance()?
                     .manage_neuron(ManageNeuronRequest {
                         id: None,
                         command: Some(command),
@@ -270,20 +428,49 @@ impl NeuronReference {
         }
     }
 
-    pub async fn disburse(&self) -> CanisterResult<DisburseResponse> {
-        let config = config_store().get()?;
-        let account_identifier =
-            AccountIdentifier::new(&config.governance_canister_id, &DEFAULT_SUBACCOUNT);
-        let x = self
+    /// Runs the command against a copy of the neuron on the governance canister, nothing is
+    /// changed. A command that would fail is returned as a bad request.
+    pub async fn simulate(&self, command: ManageNeuronCommandRequest) -> CanisterResult<Command1> {
+        let neuron_id = self
+            .neuron_id
+            .ok_or(ApiError::bad_request("Neuron not claimed yet"))?;
+
+        let (result,) = ApiClients::icp_governance()?
+            .simulate_manage_neuron(ManageNeuronRequest {
+                id: None,
+                command: Some(command),
+                neuron_id_or_subaccount: Some(NeuronIdOrSubaccount::NeuronId(NeuronId {
+                    id: neuron_id,
+                })),
+            })
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        match result.command {
+            Some(Command1::Error(e)) => Err(ApiError::bad_request(&format!(
+                "Simulation failed: {}",
+                e.error_message
+            ))),
+            Some(command_result) => Ok(command_result),
+            None => Err(ApiError::external_service_error("Unknown command")),
+        }
+    }
+
+    pub async fn disburse(
+        &self,
+        amount_e8s: Option<u64>,
+        to_account: AccountIdentifier,
+    ) -> CanisterResult<DisburseResponse> {
+        let result = self
             .command(ManageNeuronCommandRequest::Disburse(Disburse {
                 to_account: Some(ApiAccountIdentifier {
-                    hash: account_identifier.as_bytes().to_vec(),
+                    hash: to_account.as_bytes().to_vec(),
                 }),
-                amount: None,
+                amount: amount_e8s.map(|e8s| Amount { e8s }),
             }))
             .await?;
 
-        match x.command {
+        match result.command {
             Some(Command1::Disburse(response)) => Ok(response),
             _ => Err(ApiError::external_service_error("Unexpected response")),
         }
@@ -291,7 +478,7 @@ impl NeuronReference {
 
     pub async fn get_full_neuron(&self) -> CanisterResult<GovNeuron> {
         if let Some(neuron_id) = self.neuron_id {
-            let (result,) = ApiClients::icp_governance()
+            let (result,) = ApiClients::icp_governance()?
                 .get_full_neuron(neuron_id)
                 .await
                 .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
@@ -305,18 +492,35 @@ impl NeuronReference {
         }
     }
 
-    pub fn to_response(&self, storage_reference_id: u64) -> NeuronReferenceResponse {
-        NeuronReferenceResponse {
+    pub fn to_response(
+        &self,
+        storage_reference_id: u64,
+    ) -> CanisterResult<NeuronReferenceResponse> {
+        let config = config_store().get()?;
+        Ok(NeuronReferenceResponse {
             storage_reference_id,
             blockheight: self.blockheight,
             subaccount: self.subaccount,
             nonce: self.nonce,
             neuron_id: self.neuron_id,
             parent_subaccount: self.parent_subaccount,
+            hot_keys: self.hot_keys.clone(),
             topup_account: Account {
-                owner: Some(MAINNET_GOVERNANCE_CANISTER_ID),
+                owner: Some(config.icp_governance_canister_id),
                 subaccount: Some(self.subaccount.to_vec()),
             },
+        })
+    }
+}
+
+impl VersionedStorable for NeuronReference {
+    const VERSION: u8 = 2;
+
+    fn migrate(version: u8, bytes: &[u8]) -> Self {
+        match version {
+            // Version 1 only added the version prefix, the payload is the same as version 0
+            0 | 1 => NeuronReferenceV0::from_bytes(Cow::Borrowed(bytes)).into(),
+            _ => unsupported_version(version),
         }
     }
 }
@@ -329,5 +533,21 @@ pub struct NeuronReferenceResponse {
     pub nonce: u64,
     pub neuron_id: Option<u64>,
     pub parent_subaccount: Option<[u8; 32]>,
+    pub hot_keys: Vec<Principal>,
     pub topup_account: Account,
 }
+
+/// The next nonce to derive a stake subaccount from. A nonce is never handed out twice, also not
+/// after the reference that used it was removed.
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone, Default)]
+pub struct NonceCounter {
+    pub next: u64,
+}
+
+impl VersionedStorable for NonceCounter {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
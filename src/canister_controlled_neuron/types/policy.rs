@@ -0,0 +1,62 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+impl_versioned_storable_for!(Policy);
+impl_versioned_storable_for!(Disbursement);
+
+/// Restrictions on top of the role guards, checked when an operation is validated and again
+/// when it is executed. Fields that are not set do not restrict anything.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, Default)]
+pub struct Policy {
+    /// New neurons need at least this dissolve delay, and neurons can only start dissolving
+    /// while their remaining dissolve delay is at least this long.
+    pub dissolve_delay_floor_seconds: Option<u64>,
+    /// The ICP that can be disbursed from stake and maturity within a rolling period.
+    pub disbursement_cap: Option<DisbursementCap>,
+    /// Hex account identifiers ICP can be disbursed to, next to the accounts of this canister
+    /// and the governance canister.
+    pub allowed_destinations: Option<Vec<String>>,
+    /// Names of the `ManageNeuronCommandRequest` variants that can not be sent as a `Command`.
+    pub denied_commands: Vec<String>,
+    /// High-risk operations are queued for this long before they are executed.
+    pub execution_delay_seconds: Option<u64>,
+    /// The SNS tokens that can be disbursed from SNS neurons within a rolling period.
+    pub sns_disbursement_cap: Option<DisbursementCap>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct DisbursementCap {
+    pub amount_e8s: u64,
+    pub period_seconds: u64,
+}
+
+impl VersionedStorable for Policy {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+/// A disbursement counted against the disbursement cap, the timestamp is in nanoseconds.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct Disbursement {
+    pub timestamp: u64,
+    pub amount_e8s: u64,
+    pub neuron_key: Option<[u8; 32]>,
+    /// The ledger of the SNS token, not set for ICP.
+    pub ledger: Option<Principal>,
+}
+
+impl VersionedStorable for Disbursement {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
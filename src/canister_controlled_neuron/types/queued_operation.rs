@@ -0,0 +1,88 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+use super::{
+    audit_record::ProposalMatch,
+    modules::{ModuleResponse, NeuronType},
+};
+
+impl_versioned_storable_for!(QueuedOperation);
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum QueuedOperationStatus {
+    Queued,
+    Executing,
+    Executed,
+    /// The operation was executed and returned an error, `result` holds the error.
+    Failed,
+    Cancelled,
+    /// The canister was upgraded or trapped while the operation was executing, so it is not
+    /// known whether it was applied. The neuron has to be checked before it is queued again.
+    Interrupted,
+}
+
+/// A high-risk operation waiting for its time lock to expire. Timestamps are in nanoseconds,
+/// `result` is set once the operation has been executed or failed.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct QueuedOperation {
+    pub args: NeuronType,
+    pub caller: Principal,
+    pub proposal_id: Option<u64>,
+    pub status: QueuedOperationStatus,
+    pub queued_at: u64,
+    pub execute_at: u64,
+    pub updated_at: u64,
+    pub result: Option<Result<ModuleResponse, String>>,
+    pub proposal_match: Option<ProposalMatch>,
+}
+
+impl QueuedOperation {
+    /// Operations queued before proposals were matched explicitly only stored the proposal id.
+    pub fn proposal_match(&self) -> Option<ProposalMatch> {
+        self.proposal_match
+            .clone()
+            .or(self.proposal_id.map(ProposalMatch::Found))
+    }
+
+    pub fn to_response(&self, id: u64) -> QueuedOperationResponse {
+        QueuedOperationResponse {
+            id,
+            args: self.args.clone(),
+            caller: self.caller,
+            proposal_id: self.proposal_id,
+            status: self.status.clone(),
+            queued_at: self.queued_at,
+            execute_at: self.execute_at,
+            updated_at: self.updated_at,
+            result: self.result.clone(),
+            proposal_match: self.proposal_match(),
+        }
+    }
+}
+
+impl VersionedStorable for QueuedOperation {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct QueuedOperationResponse {
+    pub id: u64,
+    pub args: NeuronType,
+    pub caller: Principal,
+    pub proposal_id: Option<u64>,
+    pub status: QueuedOperationStatus,
+    pub queued_at: u64,
+    pub execute_at: u64,
+    pub updated_at: u64,
+    pub result: Option<Result<ModuleResponse, String>>,
+    pub proposal_match: Option<ProposalMatch>,
+}
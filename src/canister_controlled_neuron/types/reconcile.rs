@@ -0,0 +1,18 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use super::neuron_reference::NeuronReferenceResponse;
+
+/// The difference between the stored neuron references and the neurons this canister controls
+/// according to the governance canister.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct ReconcileReport {
+    /// Ids of neurons that have a matching reference.
+    pub matched: Vec<u64>,
+    /// Ids of neurons without a reference.
+    pub unknown: Vec<u64>,
+    /// References that were created for unknown neurons, empty when nothing was imported.
+    pub imported: Vec<NeuronReferenceResponse>,
+    /// References of claimed neurons that no longer exist on the governance canister.
+    pub missing: Vec<NeuronReferenceResponse>,
+}
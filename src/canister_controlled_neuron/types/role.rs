@@ -0,0 +1,83 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+impl_versioned_storable_for!(RoleAssignment);
+
+/// Roles are cumulative, an operator can do everything a viewer can and an admin everything an
+/// operator can. The governance canister always has the admin role and is the only principal
+/// that has it, so everything that moves funds goes through a proposal.
+///
+/// The viewer role guards the reads that call governance, `get_full_neuron`,
+/// `get_full_sns_neuron` and `get_reconcile_report`, as those cost the canister cycles. The
+/// queries on the state of the canister are public on purpose, like `get_config` has always
+/// been: every change to that state is made by a public proposal or recorded in the public
+/// ICRC-3 block log. This covers the neuron and SNS neuron references, `get_hot_keys`,
+/// `get_logs`, `get_audit_records`, `get_pending_operations`, the queued operations, the
+/// policy, the roles, the voting rules and their decisions.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub enum Role {
+    Admin,
+    Operator,
+    Viewer,
+}
+
+impl Role {
+    pub fn includes(&self, required: Role) -> bool {
+        self.rank() >= required.rank()
+    }
+
+    fn rank(&self) -> u8 {
+        match self {
+            Role::Viewer => 0,
+            Role::Operator => 1,
+            Role::Admin => 2,
+        }
+    }
+}
+
+/// The role of a principal, keyed by the principal.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct RoleAssignment {
+    pub role: Role,
+    pub assigned_at: u64,
+    pub assigned_by: Principal,
+}
+
+impl RoleAssignment {
+    pub fn to_response(&self, principal: Principal) -> RoleAssignmentResponse {
+        RoleAssignmentResponse {
+            principal,
+            role: self.role,
+            assigned_at: self.assigned_at,
+            assigned_by: self.assigned_by,
+        }
+    }
+}
+
+impl VersionedStorable for RoleAssignment {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct RoleAssignmentResponse {
+    pub principal: Principal,
+    pub role: Role,
+    pub assigned_at: u64,
+    pub assigned_by: Principal,
+}
+
+/// Assigns `role` to `principal`, replacing its current role. `None` revokes the role.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct SetRoleArgs {
+    pub principal: Principal,
+    pub role: Option<Role>,
+}
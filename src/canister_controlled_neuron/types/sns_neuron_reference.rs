@@ -0,0 +1,326 @@
+use candid::{CandidType, Nat};
+use ic_cdk::api::{canister_self, time};
+use serde::{Deserialize, Serialize};
+use toolkit_utils::{
+    api_error::ApiError,
+    cell::CellStorage,
+    icrc_ledger_types::icrc1::{
+        account::Account,
+        transfer::{Memo, TransferArg, TransferError},
+    },
+    result::CanisterResult,
+};
+
+use crate::{
+    api::{
+        api_clients::ApiClients,
+        sns_governance_api::{
+            Account as SnsAccount, By, ClaimOrRefresh, ClaimOrRefreshResponse, Command, Command1,
+            Configure, Disburse, DisburseResponse, DissolveState, Follow, GetNeuron,
+            IncreaseDissolveDelay, ManageNeuron, ManageNeuronResponse, MemoAndController,
+            Neuron as SnsGovNeuron, NeuronId, Operation, ProposalId, RegisterVote, Result1,
+        },
+    },
+    helpers::{
+        nat_helper::nat_to_u64,
+        subaccount_helper::generate_subaccount_by_nonce,
+        versioned_storable::{unsupported_version, VersionedStorable},
+    },
+    impl_versioned_storable_for,
+    storage::config_storage::config_store,
+};
+
+use super::modules::Vote;
+
+impl_versioned_storable_for!(SnsNeuronReference);
+
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct SnsNeuronReference {
+    pub blockheight: u64,
+    pub subaccount: [u8; 32],
+    pub nonce: u64,
+    pub neuron_id: Option<[u8; 32]>,
+}
+
+impl SnsNeuronReference {
+    pub fn new(nonce: u64) -> Self {
+        Self {
+            blockheight: 0,
+            subaccount: generate_subaccount_by_nonce(nonce),
+            nonce,
+            neuron_id: None,
+        }
+    }
+
+    fn stake_account(&self) -> CanisterResult<Account> {
+        Ok(Account {
+            owner: config_store().get()?.governance_canister_id,
+            subaccount: Some(self.subaccount),
+        })
+    }
+
+    /// Transfers the stake to the neuron subaccount. A transfer the ledger reports as a duplicate
+    /// already went through, so its block height is returned as a success.
+    pub async fn fund(
+        &self,
+        amount_e8s: u64,
+        created_at_time: u64,
+    ) -> CanisterResult<Result<u64, TransferError>> {
+        let transfer_args = TransferArg {
+            from_subaccount: None,
+            to: self.stake_account()?,
+            fee: None,
+            created_at_time: Some(created_at_time),
+            memo: Some(Memo::from(self.nonce)),
+            amount: Nat::from(amount_e8s),
+        };
+
+        let (result,) = ApiClients::sns_ledger()?
+            .icrc1_transfer(transfer_args)
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        match result {
+            Ok(blockheight)
+            | Err(TransferError::Duplicate {
+                duplicate_of: blockheight,
+            }) => Ok(Ok(nat_to_u64(&blockheight)?)),
+            Err(e) => Ok(Err(e)),
+        }
+    }
+
+    pub async fn stake_balance(&self) -> CanisterResult<u64> {
+        let (balance,) = ApiClients::sns_ledger()?
+            .icrc1_balance_of(self.stake_account()?)
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        nat_to_u64(&balance)
+    }
+
+    pub async fn claim_or_refresh(&mut self) -> CanisterResult<ClaimOrRefreshResponse> {
+        let result = self
+            .manage_neuron(Command::ClaimOrRefresh(ClaimOrRefresh {
+                by: Some(By::MemoAndController(MemoAndController {
+                    controller: Some(canister_self()),
+                    memo: self.nonce,
+                })),
+            }))
+            .await?;
+
+        match result.command {
+            Some(Command1::ClaimOrRefresh(response)) => Ok(response),
+            _ => Err(ApiError::external_service_error("Unexpected response")),
+        }
+    }
+
+    pub async fn top_up(&self, amount_e8s: u64) -> CanisterResult<u64> {
+        let transfer_args = TransferArg {
+            from_subaccount: None,
+            to: self.stake_account()?,
+            fee: None,
+            created_at_time: None,
+            memo: None,
+            amount: Nat::from(amount_e8s),
+        };
+
+        let (result,) = ApiClients::sns_ledger()?
+            .icrc1_transfer(transfer_args)
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        let blockheight =
+            result.map_err(|e| ApiError::external_service_error(format!("{:?}", e).as_str()))?;
+
+        nat_to_u64(&blockheight)
+    }
+
+    pub async fn configure(&self, operation: Operation) -> CanisterResult<()> {
+        let result = self
+            .command(Command::Configure(Configure {
+                operation: Some(operation),
+            }))
+            .await?;
+        match result.command {
+            Some(Command1::Configure {}) => Ok(()),
+            _ => Err(ApiError::external_service_error("Unexpected response")),
+        }
+    }
+
+    pub async fn increase_dissolve_delay(&self, dissolve_delay: u64) -> CanisterResult<()> {
+        self.configure(Operation::IncreaseDissolveDelay(IncreaseDissolveDelay {
+            additional_dissolve_delay_seconds: dissolve_delay as u32,
+        }))
+        .await
+    }
+
+    pub async fn ensure_dissolve_delay(&self, dissolve_delay: u64) -> CanisterResult<()> {
+        let current_dissolve_delay = match self.get_full_neuron().await?.dissolve_state {
+            Some(DissolveState::DissolveDelaySeconds(seconds)) => seconds,
+            Some(DissolveState::WhenDissolvedTimestampSeconds(timestamp)) => {
+                timestamp.saturating_sub(time() / 1_000_000_000)
+            }
+            None => 0,
+        };
+
+        if current_dissolve_delay < dissolve_delay {
+            self.increase_dissolve_delay(dissolve_delay - current_dissolve_delay)
+                .await
+        } else {
+            Ok(())
+        }
+    }
+
+    pub async fn set_dissolve_state(&self, start_dissolving: bool) -> CanisterResult<()> {
+        if start_dissolving {
+            self.configure(Operation::StartDissolving {})
+        } else {
+            self.configure(Operation::StopDissolving {})
+        }
+        .await
+    }
+
+    pub async fn vote(&self, proposal_id: u64, vote: Vote) -> CanisterResult<bool> {
+        let result = self
+            .command(Command::RegisterVote(RegisterVote {
+                proposal: Some(ProposalId { id: proposal_id }),
+                vote: match vote {
+                    Vote::Approve => 1,
+                    Vote::Reject => 2,
+                },
+            }))
+            .await?;
+        match result.command {
+            Some(Command1::RegisterVote {}) => Ok(true),
+            _ => Err(ApiError::external_service_error("Unexpected response")),
+        }
+    }
+
+    pub async fn set_following(
+        &self,
+        function_id: u64,
+        following_neurons: Vec<[u8; 32]>,
+    ) -> CanisterResult<()> {
+        let followees = following_neurons
+            .iter()
+            .map(|id| NeuronId { id: id.to_vec() })
+            .collect();
+
+        let result = self
+            .command(Command::Follow(Follow {
+                function_id,
+                followees,
+            }))
+            .await?;
+
+        match result.command {
+            Some(Command1::Follow {}) => Ok(()),
+            _ => Err(ApiError::external_service_error("Unexpected response")),
+        }
+    }
+
+    /// Disburses the full stake back to the default account of this canister,
+    /// which is where the stake was originally taken from.
+    pub async fn disburse(&self) -> CanisterResult<DisburseResponse> {
+        let result = self
+            .command(Command::Disburse(Disburse {
+                to_account: Some(SnsAccount {
+                    owner: Some(canister_self()),
+                    subaccount: None,
+                }),
+                amount: None,
+            }))
+            .await?;
+
+        match result.command {
+            Some(Command1::Disburse(response)) => Ok(response),
+            _ => Err(ApiError::external_service_error("Unexpected response")),
+        }
+    }
+
+    pub async fn command(&self, command: Command) -> CanisterResult<ManageNeuronResponse> {
+        match self.neuron_id {
+            Some(_) => self.manage_neuron(command).await,
+            None => Err(ApiError::bad_request("Neuron not claimed yet")),
+        }
+    }
+
+    async fn manage_neuron(&self, command: Command) -> CanisterResult<ManageNeuronResponse> {
+        let (result,) = ApiClients::sns_governance()?
+            .manage_neuron(ManageNeuron {
+                subaccount: self.subaccount.to_vec(),
+                command: Some(command),
+            })
+            .await
+            .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+        match result.command {
+            Some(Command1::Error(e)) => {
+                Err(ApiError::external_service_error(e.error_message.as_str()))
+            }
+            Some(command_result) => Ok(ManageNeuronResponse {
+                command: Some(command_result),
+            }),
+            None => Err(ApiError::external_service_error("Unknown command")),
+        }
+    }
+
+    pub async fn get_full_neuron(&self) -> CanisterResult<SnsGovNeuron> {
+        if let Some(neuron_id) = self.neuron_id {
+            let (result,) = ApiClients::sns_governance()?
+                .get_neuron(GetNeuron {
+                    neuron_id: Some(NeuronId {
+                        id: neuron_id.to_vec(),
+                    }),
+                })
+                .await
+                .map_err(|(_, e)| ApiError::external_service_error(e.as_str()))?;
+
+            match result.result {
+                Some(Result1::Neuron(neuron)) => Ok(neuron),
+                Some(Result1::Error(e)) => {
+                    Err(ApiError::external_service_error(e.error_message.as_str()))
+                }
+                None => Err(ApiError::not_found("Neuron not found")),
+            }
+        } else {
+            Err(ApiError::bad_request("Neuron not claimed yet"))
+        }
+    }
+
+    pub fn to_response(
+        &self,
+        storage_reference_id: u64,
+    ) -> CanisterResult<SnsNeuronReferenceResponse> {
+        let config = config_store().get()?;
+        Ok(SnsNeuronReferenceResponse {
+            storage_reference_id,
+            blockheight: self.blockheight,
+            subaccount: self.subaccount,
+            nonce: self.nonce,
+            neuron_id: self.neuron_id,
+            topup_account: Account {
+                owner: config.governance_canister_id,
+                subaccount: Some(self.subaccount),
+            },
+        })
+    }
+}
+
+impl VersionedStorable for SnsNeuronReference {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct SnsNeuronReferenceResponse {
+    pub storage_reference_id: u64,
+    pub blockheight: u64,
+    pub subaccount: [u8; 32],
+    pub nonce: u64,
+    pub neuron_id: Option<[u8; 32]>,
+    pub topup_account: Account,
+}
@@ -98,3 +98,31 @@ impl From<Topic> for i32 {
         topic as i32
     }
 }
+
+impl TryFrom<i32> for Topic {
+    type Error = i32;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Topic::Unspecified),
+            1 => Ok(Topic::NeuronManagement),
+            2 => Ok(Topic::ExchangeRate),
+            3 => Ok(Topic::NetworkEconomics),
+            4 => Ok(Topic::Governance),
+            5 => Ok(Topic::NodeAdmin),
+            6 => Ok(Topic::ParticipantManagement),
+            7 => Ok(Topic::SubnetManagement),
+            8 => Ok(Topic::NetworkCanisterManagement),
+            9 => Ok(Topic::Kyc),
+            10 => Ok(Topic::NodeProviderRewards),
+            12 => Ok(Topic::IcOsVersionDeployment),
+            13 => Ok(Topic::IcOsVersionElection),
+            14 => Ok(Topic::SnsAndCommunityFund),
+            15 => Ok(Topic::ApiBoundaryNodeManagement),
+            16 => Ok(Topic::SubnetRental),
+            17 => Ok(Topic::ProtocolCanisterManagement),
+            18 => Ok(Topic::ServiceNervousSystemManagement),
+            _ => Err(value),
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+impl_versioned_storable_for!(VotingPowerRefresh);
+
+/// The voting power refresh schedule of a neuron, keyed by its neuron reference id.
+/// Timestamps are in nanoseconds, except the one reported by the governance canister.
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct VotingPowerRefresh {
+    pub next_run_at: u64,
+    pub last_run_at: Option<u64>,
+    pub last_error: Option<String>,
+    pub voting_power_refreshed_timestamp_seconds: Option<u64>,
+}
+
+impl VotingPowerRefresh {
+    pub fn to_response(&self, neuron_reference_id: u64) -> VotingPowerRefreshResponse {
+        VotingPowerRefreshResponse {
+            neuron_reference_id,
+            next_run_at: self.next_run_at,
+            last_run_at: self.last_run_at,
+            last_error: self.last_error.clone(),
+            voting_power_refreshed_timestamp_seconds: self.voting_power_refreshed_timestamp_seconds,
+        }
+    }
+}
+
+impl VersionedStorable for VotingPowerRefresh {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, CandidType, Clone)]
+pub struct VotingPowerRefreshResponse {
+    pub neuron_reference_id: u64,
+    pub next_run_at: u64,
+    pub last_run_at: Option<u64>,
+    pub last_error: Option<String>,
+    pub voting_power_refreshed_timestamp_seconds: Option<u64>,
+}
@@ -0,0 +1,124 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    helpers::versioned_storable::{unsupported_version, VersionedStorable},
+    impl_versioned_storable_for,
+};
+
+use super::{modules::Vote, topic::Topic};
+
+impl_versioned_storable_for!(VotingRules);
+impl_versioned_storable_for!(VoteDecision);
+
+/// What the voting engine does for the controlled neurons when a proposal matches a rule.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub enum VotingAction {
+    Reject,
+    /// Votes the same way as the given neuron once it has voted, its ballots have to be public.
+    Follow(u64),
+    /// Does not vote on the proposal.
+    Abstain,
+    /// Leaves the vote to a `Vote` operation.
+    Manual,
+}
+
+/// Matches proposals on `topic` made by the `proposer` neuron, a filter that is not set matches
+/// every proposal.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct VotingRule {
+    pub topic: Option<Topic>,
+    pub proposer: Option<u64>,
+    pub action: VotingAction,
+}
+
+impl VotingRule {
+    pub fn matches(&self, topic: i32, proposer: Option<u64>) -> bool {
+        self.topic
+            .as_ref()
+            .is_none_or(|rule_topic| i32::from(rule_topic.clone()) == topic)
+            && self
+                .proposer
+                .is_none_or(|rule_proposer| proposer == Some(rule_proposer))
+    }
+}
+
+/// The rules are evaluated in order and the first matching rule decides, so a rule for a single
+/// proposer or topic has to come before a broader rule it overrides.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, Default)]
+pub struct VotingRules {
+    pub rules: Vec<VotingRule>,
+}
+
+impl VersionedStorable for VotingRules {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+/// What the voting engine decided for a neuron on a proposal, `vote` is not set when the neuron
+/// did not vote. The timestamp is in nanoseconds. Decisions are kept while the proposal is
+/// pending.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct VoteDecision {
+    pub proposal_id: u64,
+    pub neuron_id: u64,
+    pub topic: i32,
+    pub proposer: Option<u64>,
+    pub vote: Option<Vote>,
+    pub reason: String,
+    pub timestamp: u64,
+}
+
+impl VoteDecision {
+    pub fn to_response(&self, id: u64) -> VoteDecisionResponse {
+        VoteDecisionResponse {
+            id,
+            proposal_id: self.proposal_id,
+            neuron_id: self.neuron_id,
+            topic: self.topic,
+            proposer: self.proposer,
+            vote: self.vote.clone(),
+            reason: self.reason.clone(),
+            timestamp: self.timestamp,
+        }
+    }
+}
+
+impl VersionedStorable for VoteDecision {
+    const VERSION: u8 = 1;
+
+    fn migrate(version: u8, _bytes: &[u8]) -> Self {
+        unsupported_version(version)
+    }
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct VoteDecisionResponse {
+    pub id: u64,
+    pub proposal_id: u64,
+    pub neuron_id: u64,
+    pub topic: i32,
+    pub proposer: Option<u64>,
+    pub vote: Option<Vote>,
+    pub reason: String,
+    pub timestamp: u64,
+}
+
+/// All filters are optional. Decisions are returned in insertion order starting at `start`, pass
+/// the returned `next` to get the next page.
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone, Default)]
+pub struct GetVoteDecisionsArgs {
+    pub proposal_id: Option<u64>,
+    pub neuron_id: Option<u64>,
+    pub start: Option<u64>,
+    pub limit: Option<u64>,
+}
+
+#[derive(Debug, CandidType, Serialize, Deserialize, Clone)]
+pub struct VoteDecisionsResponse {
+    pub decisions: Vec<VoteDecisionResponse>,
+    pub next: Option<u64>,
+}
@@ -1,12 +1,16 @@
 use std::{env, path::PathBuf};
 
 use candid::{encode_args, CandidType, Decode, Nat, Principal};
-use canister_controlled_neuron::api::icp_governance_api::ProposalInfo;
-use canister_controlled_neuron::types::config::Config;
+use canister_controlled_neuron::api::icp_governance_api::{
+    By, ClaimOrRefresh, ClaimOrRefreshNeuronFromAccount, Command1, ManageNeuronCommandRequest,
+    ManageNeuronRequest, ManageNeuronResponse, ProposalInfo,
+};
+use canister_controlled_neuron::types::config::{Config, InitArgs};
 
 use ic_management_canister_types::CanisterSettings;
 use pocket_ic::{PocketIc, PocketIcBuilder};
 use serde::de::DeserializeOwned;
+use sha2::{Digest, Sha256};
 use toolkit_utils::ic_ledger_types::{
     Subaccount, MAINNET_GOVERNANCE_CANISTER_ID, MAINNET_LEDGER_CANISTER_ID,
 };
@@ -16,11 +20,17 @@ use toolkit_utils::icrc_ledger_types::icrc2::allowance::{Allowance, AllowanceArg
 use toolkit_utils::icrc_ledger_types::icrc2::approve::ApproveArgs;
 
 use crate::sender::Sender;
+use crate::sns::SnsCanisters;
 use crate::utils::generate_principal;
 
 pub static OWNER_PRINCIPAL: &str =
     "vafd2-aurwj-5igu3-htth5-olb42-6ficf-ttehy-2oyrp-u6nsy-qjlay-7ae";
 
+pub static SNS_BALANCE_E8S: u64 = 100_000_000_000;
+
+static CANISTER_CONTROLLED_NEURON_WASM: &[u8] =
+    include_bytes!("../../wasm/canister_controlled_neuron.wasm.gz");
+
 pub struct Context {
     pub pic: PocketIc,
     pub owner_account: Account,
@@ -36,6 +46,16 @@ impl Default for Context {
 
 impl Context {
     pub fn new() -> Self {
+        Self::build(false)
+    }
+
+    /// Deploys an SNS next to the canister and configures the canister with its governance and
+    /// ledger canister, the canister starts with `SNS_BALANCE_E8S` SNS tokens.
+    pub fn new_with_sns() -> Self {
+        Self::build(true)
+    }
+
+    fn build(with_sns: bool) -> Self {
         let owner_account = Account::from(Principal::from_text(OWNER_PRINCIPAL).unwrap());
 
         let default_install_settings: Option<CanisterSettings> = Some(CanisterSettings {
@@ -74,18 +94,32 @@ impl Context {
 
         pic.add_cycles(canister_controlled_neuron_canister, 2_000_000_000_000);
 
-        let canister_controlled_neuron_wasm_bytes =
-            include_bytes!("../../wasm/canister_controlled_neuron.wasm.gz");
+        let (governance_canister_id, sns_ledger_canister_id) = if with_sns {
+            let sns =
+                SnsCanisters::deploy(&pic, canister_controlled_neuron_canister, SNS_BALANCE_E8S);
+            (sns.governance, sns.ledger)
+        } else {
+            (generate_principal(), generate_principal())
+        };
 
-        let config = Config {
-            governance_canister_id: generate_principal(),
-            sns_ledger_canister_id: generate_principal(),
+        let init_args = InitArgs {
+            governance_canister_id,
+            sns_ledger_canister_id,
+            icp_governance_canister_id: None,
+            icp_ledger_canister_id: None,
         };
 
+        let config = Config::new(
+            init_args.governance_canister_id,
+            init_args.sns_ledger_canister_id,
+            init_args.icp_governance_canister_id,
+            init_args.icp_ledger_canister_id,
+        );
+
         pic.install_canister(
             canister_controlled_neuron_canister,
-            canister_controlled_neuron_wasm_bytes.to_vec(),
-            encode_args((config.clone(),)).unwrap(),
+            CANISTER_CONTROLLED_NEURON_WASM.to_vec(),
+            encode_args((init_args,)).unwrap(),
             Some(owner_account.owner),
         );
 
@@ -100,6 +134,17 @@ impl Context {
         context
     }
 
+    pub fn upgrade(&self) -> Result<(), String> {
+        self.pic
+            .upgrade_canister(
+                self.neuron_controller_canister,
+                CANISTER_CONTROLLED_NEURON_WASM.to_vec(),
+                encode_args(()).unwrap(),
+                Some(self.owner_account.owner),
+            )
+            .map_err(|e| e.to_string())
+    }
+
     pub fn query<T: DeserializeOwned + CandidType>(
         &self,
         sender: Sender,
@@ -183,6 +228,27 @@ impl Context {
             .expect("Failed to call canister");
     }
 
+    /// Mints SNS tokens to `to`, only works on a context created with `new_with_sns`.
+    pub fn mint_sns(&self, amount: u64, to: Account) {
+        let transfer_args = TransferArg {
+            from_subaccount: None,
+            to,
+            fee: None,
+            created_at_time: None,
+            memo: None,
+            amount: Nat::from(amount),
+        };
+
+        self.pic
+            .update_call(
+                self.config.sns_ledger_canister_id,
+                self.config.governance_canister_id,
+                "icrc1_transfer",
+                encode_args((transfer_args,)).unwrap(),
+            )
+            .expect("Failed to call canister");
+    }
+
     pub fn get_proposal(
         &self,
         proposal_id: u64,
@@ -201,6 +267,62 @@ impl Context {
         }
     }
 
+    /// Stakes a neuron for `controller` on the governance canister without going through the
+    /// canister, as if it was created elsewhere. Returns the neuron id and its subaccount.
+    pub fn stake_icp_neuron(
+        &self,
+        controller: Principal,
+        memo: u64,
+        amount: u64,
+    ) -> Result<(u64, [u8; 32]), String> {
+        let mut hasher = Sha256::new();
+        hasher.update([0x0c]);
+        hasher.update(b"neuron-stake");
+        hasher.update(controller.as_slice());
+        hasher.update(memo.to_be_bytes());
+        let subaccount: [u8; 32] = hasher.finalize().into();
+
+        self.transfer_icp(
+            amount,
+            self.owner_account,
+            Account {
+                owner: MAINNET_GOVERNANCE_CANISTER_ID,
+                subaccount: Some(subaccount),
+            },
+        );
+
+        let request = ManageNeuronRequest {
+            id: None,
+            command: Some(ManageNeuronCommandRequest::ClaimOrRefresh(ClaimOrRefresh {
+                by: Some(By::MemoAndController(ClaimOrRefreshNeuronFromAccount {
+                    controller: Some(controller),
+                    memo,
+                })),
+            })),
+            neuron_id_or_subaccount: None,
+        };
+        let res = self
+            .pic
+            .update_call(
+                MAINNET_GOVERNANCE_CANISTER_ID,
+                self.owner_account.owner,
+                "manage_neuron",
+                encode_args((request,)).unwrap(),
+            )
+            .map_err(|e| e.to_string())?;
+
+        match Decode!(res.as_slice(), ManageNeuronResponse)
+            .map_err(|e| e.to_string())?
+            .command
+        {
+            Some(Command1::ClaimOrRefresh(response)) => response
+                .refreshed_neuron_id
+                .map(|id| (id.id, subaccount))
+                .ok_or("Neuron not claimed".to_string()),
+            command => Err(format!("Unexpected response: {:?}", command)),
+        }
+    }
+
     pub fn mint_icp_subaccount(
         &self,
         amount: u64,
@@ -278,13 +400,17 @@ impl Context {
     }
 
     pub fn get_icp_balance(&self, user_principal: Principal) -> Result<Nat, String> {
+        self.get_icp_account_balance(Account::from(user_principal))
+    }
+
+    pub fn get_icp_account_balance(&self, account: Account) -> Result<Nat, String> {
         let icp_balance_result = self
             .pic
             .update_call(
                 MAINNET_LEDGER_CANISTER_ID,
                 MAINNET_GOVERNANCE_CANISTER_ID,
                 "icrc1_balance_of",
-                encode_args((Account::from(user_principal),)).unwrap(),
+                encode_args((account,)).unwrap(),
             )
             .expect("Failed to call canister");
 
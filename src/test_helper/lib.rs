@@ -1,3 +1,4 @@
 pub mod context;
 pub mod sender;
+pub mod sns;
 pub mod utils;
@@ -0,0 +1,289 @@
+use candid::{encode_one, CandidType, Decode, Empty, Nat, Principal};
+use pocket_ic::PocketIc;
+use serde::Deserialize;
+use toolkit_utils::icrc_ledger_types::icrc1::account::Account;
+
+use crate::utils::generate_principal;
+
+pub static SNS_WASM_CANISTER_ID: &str = "qaa6y-5yaaa-aaaaa-aaafa-cai";
+
+const E8S_PER_TOKEN: u64 = 100_000_000;
+const ONE_DAY_SECONDS: u64 = 24 * 60 * 60;
+const ONE_YEAR_SECONDS: u64 = 31_557_600;
+/// `Mode::Normal` of SNS governance, neurons can only be claimed in normal mode.
+const NORMAL_MODE: i32 = 1;
+/// All `NeuronPermissionType` values except `Unspecified`.
+const ALL_PERMISSIONS: [i32; 10] = [1, 2, 3, 4, 5, 6, 7, 8, 9, 10];
+
+/// An SNS governance and ledger canister, installed with the wasms that are stored on the SNS-W
+/// canister of the NNS state.
+pub struct SnsCanisters {
+    pub governance: Principal,
+    pub ledger: Principal,
+}
+
+impl SnsCanisters {
+    /// Deploys the SNS in normal mode, the ledger starts with `initial_balance_e8s` tokens on the
+    /// default account of `holder`.
+    pub fn deploy(pic: &PocketIc, holder: Principal, initial_balance_e8s: u64) -> Self {
+        let governance = pic.create_canister();
+        let ledger = pic.create_canister();
+        pic.add_cycles(governance, 2_000_000_000_000);
+        pic.add_cycles(ledger, 2_000_000_000_000);
+
+        let ledger_init = LedgerArg::Init(LedgerInitArgs {
+            minting_account: Account::from(governance),
+            fee_collector_account: None,
+            transfer_fee: Nat::from(10_000u64),
+            decimals: Some(8),
+            max_memo_length: Some(32),
+            token_symbol: "TST".to_string(),
+            token_name: "Test".to_string(),
+            metadata: vec![],
+            initial_balances: vec![(Account::from(holder), Nat::from(initial_balance_e8s))],
+            archive_options: ArchiveOptions {
+                num_blocks_to_archive: 1_000,
+                trigger_threshold: 2_000,
+                max_transactions_per_response: None,
+                max_message_size_bytes: None,
+                cycles_for_archive_creation: None,
+                node_max_memory_size_bytes: None,
+                controller_id: governance,
+                more_controller_ids: None,
+            },
+            index_principal: None,
+        });
+        pic.install_canister(
+            ledger,
+            get_wasm(pic, "Ledger"),
+            encode_one(ledger_init).unwrap(),
+            None,
+        );
+
+        let governance_init = GovernanceInit {
+            root_canister_id: Some(generate_principal()),
+            ledger_canister_id: Some(ledger),
+            swap_canister_id: Some(generate_principal()),
+            mode: NORMAL_MODE,
+            parameters: Some(NervousSystemParameters::default_values()),
+            sns_metadata: Some(SnsMetadata {
+                url: Some("https://example.com".to_string()),
+                logo: None,
+                name: Some("Test SNS".to_string()),
+                description: Some("An SNS to test SNS neurons with".to_string()),
+            }),
+            sns_initialization_parameters: String::new(),
+            genesis_timestamp_seconds: pic.get_time().as_nanos_since_unix_epoch() / 1_000_000_000,
+            neurons: vec![],
+            proposals: vec![],
+            in_flight_commands: vec![],
+            id_to_nervous_system_functions: vec![],
+        };
+        pic.install_canister(
+            governance,
+            get_wasm(pic, "Governance"),
+            encode_one(governance_init).unwrap(),
+            None,
+        );
+
+        Self { governance, ledger }
+    }
+}
+
+/// Fetches the wasm of the latest SNS version for `canister_type`, as named by
+/// `get_latest_sns_version_pretty`.
+fn get_wasm(pic: &PocketIc, canister_type: &str) -> Vec<u8> {
+    let sns_wasm = Principal::from_text(SNS_WASM_CANISTER_ID).unwrap();
+
+    let response = pic
+        .query_call(
+            sns_wasm,
+            Principal::anonymous(),
+            "get_latest_sns_version_pretty",
+            encode_one(()).unwrap(),
+        )
+        .expect("Failed to get the latest SNS version");
+    let version = Decode!(response.as_slice(), Vec<(String, String)>).unwrap();
+    let (_, hash) = version
+        .into_iter()
+        .find(|(name, _)| name == canister_type)
+        .unwrap_or_else(|| panic!("No {} wasm found on SNS-W", canister_type));
+
+    let response = pic
+        .query_call(
+            sns_wasm,
+            Principal::anonymous(),
+            "get_wasm",
+            encode_one(GetWasmRequest {
+                hash: hex_to_bytes(&hash),
+            })
+            .unwrap(),
+        )
+        .expect("Failed to get the SNS wasm");
+    Decode!(response.as_slice(), GetWasmResponse)
+        .unwrap()
+        .wasm
+        .unwrap_or_else(|| panic!("SNS-W does not store the {} wasm", canister_type))
+        .wasm
+}
+
+fn hex_to_bytes(hex: &str) -> Vec<u8> {
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[derive(CandidType)]
+struct GetWasmRequest {
+    hash: Vec<u8>,
+}
+
+#[derive(CandidType, Deserialize)]
+struct GetWasmResponse {
+    wasm: Option<SnsWasm>,
+}
+
+#[derive(CandidType, Deserialize)]
+struct SnsWasm {
+    wasm: Vec<u8>,
+}
+
+#[derive(CandidType)]
+enum LedgerArg {
+    Init(LedgerInitArgs),
+}
+
+#[derive(CandidType)]
+struct LedgerInitArgs {
+    minting_account: Account,
+    fee_collector_account: Option<Account>,
+    transfer_fee: Nat,
+    decimals: Option<u8>,
+    max_memo_length: Option<u16>,
+    token_symbol: String,
+    token_name: String,
+    metadata: Vec<(String, Empty)>,
+    initial_balances: Vec<(Account, Nat)>,
+    archive_options: ArchiveOptions,
+    index_principal: Option<Principal>,
+}
+
+#[derive(CandidType)]
+struct ArchiveOptions {
+    num_blocks_to_archive: u64,
+    trigger_threshold: u64,
+    max_transactions_per_response: Option<u64>,
+    max_message_size_bytes: Option<u64>,
+    cycles_for_archive_creation: Option<u64>,
+    node_max_memory_size_bytes: Option<u64>,
+    controller_id: Principal,
+    more_controller_ids: Option<Vec<Principal>>,
+}
+
+/// The fields of the `Governance` init arg that are needed to start an SNS without neurons or
+/// proposals. The maps are always empty and `Empty` is a subtype of every value type.
+#[derive(CandidType)]
+struct GovernanceInit {
+    root_canister_id: Option<Principal>,
+    ledger_canister_id: Option<Principal>,
+    swap_canister_id: Option<Principal>,
+    mode: i32,
+    parameters: Option<NervousSystemParameters>,
+    sns_metadata: Option<SnsMetadata>,
+    sns_initialization_parameters: String,
+    genesis_timestamp_seconds: u64,
+    neurons: Vec<(String, Empty)>,
+    proposals: Vec<(u64, Empty)>,
+    in_flight_commands: Vec<(String, Empty)>,
+    id_to_nervous_system_functions: Vec<(u64, Empty)>,
+}
+
+#[derive(CandidType)]
+struct SnsMetadata {
+    url: Option<String>,
+    logo: Option<String>,
+    name: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(CandidType)]
+struct NeuronPermissionList {
+    permissions: Vec<i32>,
+}
+
+#[derive(CandidType)]
+struct DefaultFollowees {
+    followees: Vec<(u64, Empty)>,
+}
+
+#[derive(CandidType)]
+struct VotingRewardsParameters {
+    round_duration_seconds: Option<u64>,
+    reward_rate_transition_duration_seconds: Option<u64>,
+    initial_reward_rate_basis_points: Option<u64>,
+    final_reward_rate_basis_points: Option<u64>,
+}
+
+#[derive(CandidType)]
+struct NervousSystemParameters {
+    reject_cost_e8s: Option<u64>,
+    neuron_minimum_stake_e8s: Option<u64>,
+    transaction_fee_e8s: Option<u64>,
+    max_proposals_to_keep_per_action: Option<u32>,
+    initial_voting_period_seconds: Option<u64>,
+    wait_for_quiet_deadline_increase_seconds: Option<u64>,
+    default_followees: Option<DefaultFollowees>,
+    max_number_of_neurons: Option<u64>,
+    neuron_minimum_dissolve_delay_to_vote_seconds: Option<u64>,
+    max_followees_per_function: Option<u64>,
+    max_dissolve_delay_seconds: Option<u64>,
+    max_neuron_age_for_age_bonus: Option<u64>,
+    max_number_of_proposals_with_ballots: Option<u64>,
+    neuron_claimer_permissions: Option<NeuronPermissionList>,
+    neuron_grantable_permissions: Option<NeuronPermissionList>,
+    max_number_of_principals_per_neuron: Option<u64>,
+    voting_rewards_parameters: Option<VotingRewardsParameters>,
+    max_dissolve_delay_bonus_percentage: Option<u64>,
+    max_age_bonus_percentage: Option<u64>,
+    maturity_modulation_disabled: Option<bool>,
+    automatically_advance_target_version: Option<bool>,
+}
+
+impl NervousSystemParameters {
+    /// The defaults SNS governance uses for new SNSes.
+    fn default_values() -> Self {
+        Self {
+            reject_cost_e8s: Some(E8S_PER_TOKEN),
+            neuron_minimum_stake_e8s: Some(E8S_PER_TOKEN),
+            transaction_fee_e8s: Some(10_000),
+            max_proposals_to_keep_per_action: Some(100),
+            initial_voting_period_seconds: Some(4 * ONE_DAY_SECONDS),
+            wait_for_quiet_deadline_increase_seconds: Some(ONE_DAY_SECONDS),
+            default_followees: Some(DefaultFollowees { followees: vec![] }),
+            max_number_of_neurons: Some(200_000),
+            neuron_minimum_dissolve_delay_to_vote_seconds: Some(ONE_YEAR_SECONDS / 2),
+            max_followees_per_function: Some(15),
+            max_dissolve_delay_seconds: Some(8 * ONE_YEAR_SECONDS),
+            max_neuron_age_for_age_bonus: Some(4 * ONE_YEAR_SECONDS),
+            max_number_of_proposals_with_ballots: Some(700),
+            neuron_claimer_permissions: Some(NeuronPermissionList {
+                permissions: ALL_PERMISSIONS.to_vec(),
+            }),
+            neuron_grantable_permissions: Some(NeuronPermissionList {
+                permissions: ALL_PERMISSIONS.to_vec(),
+            }),
+            max_number_of_principals_per_neuron: Some(5),
+            voting_rewards_parameters: Some(VotingRewardsParameters {
+                round_duration_seconds: Some(ONE_DAY_SECONDS),
+                reward_rate_transition_duration_seconds: Some(0),
+                initial_reward_rate_basis_points: Some(0),
+                final_reward_rate_basis_points: Some(0),
+            }),
+            max_dissolve_delay_bonus_percentage: Some(100),
+            max_age_bonus_percentage: Some(25),
+            maturity_modulation_disabled: Some(false),
+            automatically_advance_target_version: Some(false),
+        }
+    }
+}